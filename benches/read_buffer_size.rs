@@ -0,0 +1,61 @@
+//! Measures the effect of the read buffer size on PE checksum verification.
+//!
+//! Run with `cargo bench --bench read_buffer_size`. Set `FLIST_BENCH_FILE` to
+//! a large file on the storage of interest (e.g. a network share) to measure
+//! it instead of a generated 64 MiB file in the temporary directory.
+
+use flist::version_reader::verify_pe_checksum_with_buffer;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+const GENERATED_SIZE: usize = 64 * 1024 * 1024;
+const BUFFER_SIZES: [usize; 6] = [
+    4 * 1024,
+    16 * 1024,
+    64 * 1024,
+    256 * 1024,
+    1024 * 1024,
+    4 * 1024 * 1024,
+];
+const ITERATIONS: u32 = 5;
+
+/// Writes a PE-like file of [`GENERATED_SIZE`] bytes.
+fn generate_file() -> PathBuf {
+    let mut image: Vec<u8> = (0..GENERATED_SIZE).map(|i| (i * 7) as u8).collect();
+    image[0..2].copy_from_slice(b"MZ");
+    image[0x3C..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+    image[0x80..0x84].copy_from_slice(b"PE\0\0");
+
+    let path = std::env::temp_dir().join("flist_bench_read_buffer_size.exe");
+    std::fs::write(&path, image).expect("failed to write benchmark file");
+    path
+}
+
+fn main() {
+    let (path, generated) = match std::env::var_os("FLIST_BENCH_FILE") {
+        Some(path) => (PathBuf::from(path), false),
+        None => (generate_file(), true),
+    };
+    let size = std::fs::metadata(&path).expect("benchmark file").len();
+    println!("{} ({} MiB)", path.display(), size / (1024 * 1024));
+
+    for buffer_size in BUFFER_SIZES {
+        let mut best = Duration::MAX;
+        for _ in 0..ITERATIONS {
+            let start = Instant::now();
+            verify_pe_checksum_with_buffer(&path, buffer_size).expect("checksum failed");
+            best = best.min(start.elapsed());
+        }
+        let throughput = size as f64 / (1024.0 * 1024.0) / best.as_secs_f64();
+        println!(
+            "buffer {:>5} KiB: {:>8.2} ms {:>9.1} MiB/s",
+            buffer_size / 1024,
+            best.as_secs_f64() * 1000.0,
+            throughput
+        );
+    }
+
+    if generated {
+        std::fs::remove_file(&path).ok();
+    }
+}
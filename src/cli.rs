@@ -2,7 +2,14 @@
 //!
 //! This module defines the command-line interface for FList using the `clap` crate.
 
-use clap::Parser;
+use crate::file_lister::{self, SortKey, VersionField};
+use crate::file_version::SemanticVersion;
+use crate::open_limiter::OpenFileLimiter;
+use crate::output::{ColorChoice, OutputFormat, PathStyle, VERBOSITY_NORMAL};
+use crate::version_reader::{DEFAULT_READ_BUFFER_SIZE, PeArch, StringField};
+use clap::builder::ArgPredicate;
+use clap::{ArgAction, Parser};
+use std::path::PathBuf;
 
 /// Command-line arguments for FList.
 ///
@@ -16,55 +23,489 @@ use clap::Parser;
 /// use flist::cli::CliArgs;
 ///
 /// let args = CliArgs::parse();
-/// println!("Pattern: {}", args.pattern);
+/// println!("Pattern: {:?}", args.pattern);
 /// ```
 #[derive(Parser, Debug)]
 #[command(name = "flist")]
 #[command(version)]
 #[command(about = "List files in directories with optional file version information", long_about = None)]
 pub struct CliArgs {
-    /// Search pattern (e.g., *.dll, *.exe)
-    #[arg(default_value = "*")]
-    pub pattern: String,
+    /// Search patterns (e.g., *.dll *.exe), or the paths of files to inspect
+    /// A file is listed if its name matches any pattern
+    /// Defaults to * unless patterns are read with --pattern-from
+    #[arg(
+        default_value = "*",
+        default_value_if("pattern_from", ArgPredicate::IsPresent, None)
+    )]
+    pub pattern: Vec<String>,
+
+    /// Read additional search patterns from a file, one per line
+    /// Blank lines and lines starting with # are ignored
+    #[arg(long = "pattern-from", value_name = "FILE")]
+    pub pattern_from: Option<String>,
+
+    /// Also list directories whose name matches the pattern, shown with a trailing separator
+    #[arg(long = "match-dirs")]
+    pub match_dirs: bool,
 
     /// Include file version information
     #[arg(short = 'i', long = "ifs")]
     pub include_file_version: bool,
 
-    /// Sort output by file path
-    #[arg(short = 's', long = "sp")]
+    /// Include the product version, shown after the file version
+    #[arg(long = "product-version")]
+    pub product_version: bool,
+
+    /// Include the AssemblyVersion of .NET assemblies, shown after the product version
+    #[arg(long = "assembly-version")]
+    pub assembly_version: bool,
+
+    /// Include the ProductVersion string as a semantic version (e.g., 1.2.3-beta),
+    /// shown after the assembly version; empty if the string is not a semantic version
+    #[arg(long = "semver")]
+    pub semver: bool,
+
+    /// Which version the version filters use: file or product
+    /// Applies to --minv, --maxv, --version-range, --neqv, --eq and --at-least-version-of
+    #[arg(long = "version-field", value_name = "FIELD", value_enum, default_value_t = VersionField::File)]
+    pub version_field: VersionField,
+
+    /// Show version strings: company, product, description, copyright
+    /// Format: --strings company,product
+    #[arg(
+        long = "strings",
+        value_name = "FIELDS",
+        value_enum,
+        value_delimiter = ','
+    )]
+    pub strings: Vec<StringField>,
+
+    /// Language of the version strings as a hexadecimal language ID (e.g., 0409)
+    /// Files without strings in this language show their first language
+    #[arg(long = "language", value_name = "LANGID", value_parser = parse_language)]
+    pub language: Option<u16>,
+
+    /// Sort output by file path (the default; kept for compatibility)
+    #[arg(short = 's', long = "sp", conflicts_with = "unordered")]
     pub sort_by_path: bool,
 
-    /// Minimum version filter (e.g., 1.2.3.4)
+    /// Sort by one or more comma separated keys: path, name, version, ext
+    /// Format: --sort version,path (default: path)
+    #[arg(
+        long = "sort",
+        value_name = "KEYS",
+        value_enum,
+        value_delimiter = ',',
+        conflicts_with = "unordered"
+    )]
+    pub sort: Vec<SortKey>,
+
+    /// Exit with CODE (default 1) when no file passes the filters
+    /// Format: --fail-if-empty or --fail-if-empty=3
+    #[arg(
+        long = "fail-if-empty",
+        value_name = "CODE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "1",
+        value_parser = clap::value_parser!(u8).range(1..)
+    )]
+    pub fail_if_empty: Option<u8>,
+
+    /// Print only the number of matching files, after all filters
+    #[arg(short = 'c', long = "count", conflicts_with_all = ["format", "detect_regressions"])]
+    pub count: bool,
+
+    /// Separate paths with NUL instead of newlines and print nothing else,
+    /// for piping to `xargs -0`; also applies to --output
+    #[arg(
+        short = '0',
+        long = "null",
+        conflicts_with_all = ["format", "count", "detect_regressions"]
+    )]
+    pub null: bool,
+
+    /// Only show the first N files, after filtering and sorting
+    #[arg(short = 'n', long = "limit", value_name = "N")]
+    pub limit: Option<usize>,
+
+    /// Reverse the sort order, e.g. newest version or last path first
+    #[arg(short = 'R', long = "reverse", conflicts_with_all = ["unordered", "after_path"])]
+    pub reverse: bool,
+
+    /// Compare numbers in paths and names by value, so file2 sorts before file10
+    #[arg(long = "natural-sort", conflicts_with_all = ["unordered", "after_path"])]
+    pub natural_sort: bool,
+
+    /// Skip sorting and print files in filesystem enumeration order
+    /// The order is platform and filesystem dependent and not reproducible
+    #[arg(long = "unordered")]
+    pub unordered: bool,
+
+    /// Minimum version filter (e.g., 1.2.3.4, or 1.2.* for 1.2.0.0)
     /// Format: -minv:1.2.3.4 or --minv 1.2.3.4
     #[arg(long = "minv", value_name = "VERSION")]
     pub min_version: Option<String>,
 
-    /// Maximum version filter (e.g., 2.0.0.0)
+    /// Maximum version filter (e.g., 2.0.0.0, or 1.2.* for the whole 1.2 line)
     /// Format: -maxv:2.0.0.0 or --maxv 2.0.0.0
     #[arg(long = "maxv", value_name = "VERSION")]
     pub max_version: Option<String>,
 
+    /// Inclusive version range, instead of --minv and --maxv
+    /// Format: 1.0.0.0..2.0.0.0, or 1.0.0.0.. and ..2.0.0.0 for open ranges
+    #[arg(
+        long = "version-range",
+        value_name = "RANGE",
+        conflicts_with_all = ["min_version", "max_version"]
+    )]
+    pub version_range: Option<String>,
+
+    /// Exclude files whose version matches a pattern (e.g., 1.2.3.4 or 1.2.*)
+    /// Can be repeated; a file is excluded if it matches any pattern
+    #[arg(long = "neqv", value_name = "VERSION")]
+    pub exclude_versions: Vec<String>,
+
+    /// Only show files whose version equals the given one (e.g., 1.2.3.4)
+    /// Omitted trailing parts match anything, so 1.2 matches every 1.2.x.x version
+    #[arg(long = "eq", visible_alias = "eqv", value_name = "VERSION")]
+    pub exact_version: Option<String>,
+
+    /// Only show files whose ProductVersion string is at least the given semantic
+    /// version (e.g., 1.2.3); prereleases such as 1.2.3-beta are lower than their release
+    #[arg(long = "min-semver", value_name = "VERSION", value_parser = parse_semver)]
+    pub min_semver: Option<SemanticVersion>,
+
+    /// Only show files whose ProductVersion string is at most the given semantic
+    /// version (e.g., 2.0.0-rc.1); files without a semantic version are excluded
+    #[arg(long = "max-semver", value_name = "VERSION", value_parser = parse_semver)]
+    pub max_semver: Option<SemanticVersion>,
+
+    /// Only show files created at or after the given time (e.g., 2024-01-31 or 7d)
+    /// Creation time is not available on every platform and filesystem
+    #[arg(long = "created-after", value_name = "TIME")]
+    pub created_after: Option<String>,
+
+    /// Only show files created at or before the given time (e.g., 2024-01-31T12:00)
+    #[arg(long = "created-before", value_name = "TIME")]
+    pub created_before: Option<String>,
+
+    /// Only show files modified at or after the given time (e.g., 2024-01-31 or 7d)
+    #[arg(long = "modified-after", value_name = "TIME")]
+    pub modified_after: Option<String>,
+
+    /// Only show files modified at or before the given time (e.g., 2024-01-31T12:00)
+    #[arg(long = "modified-before", value_name = "TIME")]
+    pub modified_before: Option<String>,
+
+    /// Only show files of at least the given size (e.g., 500K, 10MB, 1G)
+    #[arg(long = "min-size", value_name = "SIZE", value_parser = parse_size)]
+    pub min_size: Option<u64>,
+
+    /// Only show files of at most the given size (e.g., 500K, 10MB, 1G)
+    #[arg(long = "max-size", value_name = "SIZE", value_parser = parse_size)]
+    pub max_size: Option<u64>,
+
+    /// Show the size of files in bytes
+    #[arg(long = "show-size")]
+    pub show_size: bool,
+
+    /// Show the creation time of files (UTC)
+    #[arg(long = "show-created")]
+    pub show_created: bool,
+
+    /// Show the link time from the PE header (UTC)
+    /// Reproducible builds store zero or a hash instead of a time
+    #[arg(long = "show-pe-timestamp")]
+    pub show_pe_timestamp: bool,
+
+    /// Show the target architecture from the PE header: x86, x64, arm or arm64
+    #[arg(long = "show-arch")]
+    pub show_arch: bool,
+
+    /// Only show PE files built for the architecture: x86, x64, arm or arm64
+    #[arg(long = "arch", value_name = "ARCH")]
+    pub arch: Option<PeArch>,
+
+    /// Only show PE files without an embedded signature (certificate table)
+    /// The signature is not validated, only its presence is checked
+    #[arg(long = "unsigned")]
+    pub unsigned: bool,
+
     /// Working directory to search
     /// Format: -d:C:\path or --directory C:\path
     #[arg(short = 'd', long = "directory", value_name = "PATH")]
     pub directory: Option<String>,
 
+    /// Only list files with one of these extensions, in addition to the pattern
+    /// Format: --ext dll,exe,sys (case-insensitive)
+    #[arg(long = "ext", value_name = "EXTS", value_delimiter = ',')]
+    pub ext: Vec<String>,
+
+    /// Print entries that could not be read, e.g. without permission, to stderr
+    /// after the listing instead of skipping them silently
+    #[arg(long = "show-errors")]
+    pub show_errors: bool,
+
+    /// Search every directory listed in a file, one per line, or - for stdin
+    /// Blank lines and lines starting with # are ignored
+    #[arg(long = "dirs-from", value_name = "FILE", conflicts_with = "directory")]
+    pub dirs_from: Option<String>,
+
+    /// Read the files to list from stdin, one path per line, instead of searching
+    /// e.g. git diff --name-only | flist --stdin-paths -i
+    /// Paths that are not existing files are reported on stderr and left out
+    #[arg(
+        long = "stdin-paths",
+        conflicts_with_all = ["pattern", "pattern_from", "dirs_from", "full_path"]
+    )]
+    pub stdin_paths: bool,
+
+    /// Show the search directory each file was found in, with --dirs-from
+    #[arg(long = "show-root")]
+    pub show_root: bool,
+
     /// Output file path (in addition to console output)
     /// Format: -o:output.txt or --output output.txt
     #[arg(short = 'o', long = "output", value_name = "FILE")]
     pub output_file: Option<String>,
 
-    /// Quiet mode - only show results
-    #[arg(short = 'q', long = "quiet")]
-    pub quiet: bool,
+    /// Append to the output file instead of overwriting it
+    /// A CSV header row is only written into an empty file
+    #[arg(long = "append", requires = "output_file")]
+    pub append: bool,
+
+    /// Include the "Found N files." summary lines in the output file
+    /// Written even with --quiet, which only affects the console
+    #[arg(long = "file-summary")]
+    pub file_summary: bool,
+
+    /// Write a JSON summary of the run (counts, timing, parameters) to a file
+    #[arg(long = "summary-json", value_name = "FILE")]
+    pub summary_json: Option<String>,
+
+    /// Report files with a lower version than a same-named file on an earlier path
+    #[arg(long = "detect-regressions")]
+    pub detect_regressions: bool,
+
+    /// Print how many files have each major version, after the listing
+    #[arg(long = "summarize", conflicts_with_all = ["format", "count", "null"])]
+    pub summarize: bool,
+
+    /// Hash the contents of files and list groups of identical files, after the listing
+    #[arg(long = "dedup", conflicts_with_all = ["format", "count", "null"])]
+    pub dedup: bool,
+
+    /// Verify the checksum stored in PE files and show the result
+    #[arg(long = "verify-pe-checksum")]
+    pub verify_pe_checksum: bool,
+
+    /// Only show PE files whose stored checksum does not match their contents
+    #[arg(long = "bad-checksum-only")]
+    pub bad_checksum_only: bool,
+
+    /// Only show files whose file version and product version differ
+    /// Files missing either version are left out
+    #[arg(long = "mismatched")]
+    pub mismatched: bool,
+
+    /// Only show files that have version information
+    #[arg(long = "has-version", conflicts_with = "no_version")]
+    pub has_version: bool,
+
+    /// Only show files without version information
+    #[arg(long = "no-version")]
+    pub no_version: bool,
+
+    /// Of files with the same name, only show the one with the highest version
+    /// Ties go to the lowest path; files without version are all shown
+    #[arg(long = "keep-highest")]
+    pub keep_highest: bool,
+
+    /// List alternate data streams of matched files (Windows only)
+    #[arg(long = "ads")]
+    pub ads: bool,
+
+    /// Maximum number of files open at the same time while reading
+    #[arg(
+        long = "max-open-files",
+        value_name = "N",
+        default_value_t = OpenFileLimiter::DEFAULT_MAX_OPEN_FILES,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub max_open_files: usize,
+
+    /// Number of threads reading files in parallel (default: one per core)
+    #[arg(
+        long = "threads",
+        value_name = "N",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub threads: Option<usize>,
+
+    /// Read buffer size for file contents that are not memory mapped (e.g., 64K, 1M)
+    /// Only affects streamed reads such as --verify-pe-checksum
+    #[arg(
+        long = "buffer-size",
+        value_name = "SIZE",
+        default_value_t = DEFAULT_READ_BUFFER_SIZE,
+        value_parser = parse_buffer_size
+    )]
+    pub buffer_size: usize,
+
+    /// Only show files whose path sorts after the given path, as listed or relative to
+    /// the search directory (e.g., sub/a.dll); resumes an interrupted scan in path order
+    #[arg(long = "after-path", value_name = "PATH", conflicts_with = "unordered")]
+    pub after_path: Option<String>,
+
+    /// Shorten displayed paths longer than N characters in the middle
+    /// Only affects console output, files written with -o keep full paths
+    #[arg(long = "max-width", value_name = "N")]
+    pub max_width: Option<usize>,
+
+    /// Output format for the console and -o (default: text)
+    /// The formats other than text hold path, size, modification time and the requested fields
+    #[arg(long = "format", value_name = "FORMAT", value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Format of the -o file (default: same as --format)
+    #[arg(
+        long = "output-format",
+        value_name = "FORMAT",
+        value_enum,
+        requires = "output_file"
+    )]
+    pub output_format: Option<OutputFormat>,
+
+    /// When to color console output: auto (default), always or never
+    /// auto colors only a terminal and respects the NO_COLOR environment variable
+    #[arg(long = "color", value_name = "WHEN", value_enum, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+
+    /// How to print paths: full (as found), relative, absolute or both
+    #[arg(long = "paths", value_name = "STYLE", value_enum, default_value_t = PathStyle::Full)]
+    pub paths: PathStyle,
+
+    /// Print canonical absolute paths, with symbolic links and .. resolved
+    #[arg(long = "absolute", conflicts_with_all = ["paths", "relative"])]
+    pub absolute: bool,
+
+    /// Print paths relative to the search directory, short for --paths relative
+    #[arg(long = "relative", conflicts_with = "paths")]
+    pub relative: bool,
+
+    /// Separator between the version and path columns instead of padding
+    /// Format: --field-separator "|" or --field-separator "\t" for a tab
+    #[arg(long = "field-separator", value_name = "STR")]
+    pub field_separator: Option<String>,
+
+    /// Print each file as a template instead of columns, e.g. "{version}\t{name}"
+    /// Placeholders: {path}, {name}, {dir}, {version}, {product_version}, {assembly_version},
+    /// {size}, {modified}, {created} and {arch}; {{ and }} are literal braces
+    #[arg(
+        long = "template",
+        value_name = "TEMPLATE",
+        conflicts_with_all = ["format", "null"]
+    )]
+    pub template: Option<String>,
+
+    /// Insert a blank line between groups of the first sort key
+    /// Files of one directory when sorting by path, otherwise files with equal keys,
+    /// e.g. the same version with --sort version or extension with --sort ext
+    /// Only affects console output
+    #[arg(long = "group-separator", conflicts_with = "unordered")]
+    pub group_separator: bool,
+
+    /// Group the listing by directory: a "DIR:" header per directory, followed
+    /// by the indented file names and their columns
+    /// Only affects text output, JSON, CSV and the other formats stay flat
+    #[arg(long = "group-by-dir", conflicts_with_all = ["null", "group_separator"])]
+    pub group_by_dir: bool,
+
+    /// Print whether PATTERN matches the file NAME and exit, without searching
+    /// Uses the same matching as the search, e.g. --test-pattern "*.dll" bin/core.dll
+    #[arg(long = "test-pattern", num_args = 2, value_names = ["PATTERN", "NAME"])]
+    pub test_pattern: Option<Vec<String>>,
+
+    /// Print the JSON Schema of the --format json output and exit, without searching
+    #[arg(long = "print-schema")]
+    pub print_schema: bool,
+
+    /// Validate the patterns, excludes and directory, then exit without searching
+    /// Exits with a non-zero code and a description of the first problem found
+    #[arg(long = "check")]
+    pub check: bool,
+
+    /// Skip files and directories whose name matches the glob pattern
+    /// Can be repeated, e.g. -e node_modules -e .git -e "*.tmp"
+    #[arg(short = 'e', long = "exclude", value_name = "PATTERN")]
+    pub exclude: Vec<String>,
+
+    /// Ignore letter case when matching patterns and excludes, so *.DLL matches core.dll
+    #[arg(short = 'I', long = "ignore-case")]
+    pub ignore_case: bool,
+
+    /// Match patterns against the path relative to the search directory, e.g. src/**/*.rs
+    /// Without it, patterns are matched against file names only
+    #[arg(short = 'p', long = "full-path")]
+    pub full_path: bool,
+
+    /// Skip files ignored by .gitignore and .ignore files and the global git excludes
+    #[arg(long = "use-gitignore")]
+    pub use_gitignore: bool,
+
+    /// Follow symbolic links to files and directories
+    /// Links to a directory containing them are skipped with a warning
+    #[arg(short = 'l', long = "follow-symlinks")]
+    pub follow_symlinks: bool,
+
+    /// Ignore the .flistignore file of the search directory
+    /// By default its patterns are skipped like --exclude patterns
+    #[arg(long = "no-flistignore")]
+    pub no_flistignore: bool,
+
+    /// Descend at most N directory levels (1 = only files directly in the directory)
+    /// Speeds up scans of large trees such as network shares
+    #[arg(short = 'L', long = "depth", value_name = "N")]
+    pub depth: Option<usize>,
+
+    /// Print a progress line to stderr every SECS seconds during the scan (0 = off)
+    /// Disabled by --quiet
+    #[arg(long = "progress-interval", value_name = "SECS", default_value_t = 0)]
+    pub progress_interval: u64,
+
+    /// Show a progress bar with files found and processed on stderr during the scan
+    /// Only on a terminal, and not with --quiet
+    #[arg(long = "progress", conflicts_with = "progress_interval")]
+    pub progress: bool,
+
+    /// Quiet mode: -q hides the header and summary lines, -qq also the warnings
+    /// on stderr, leaving only the results
+    #[arg(short = 'q', long = "quiet", action = ArgAction::Count, conflicts_with = "verbose")]
+    pub quiet: u8,
+
+    /// Verbose mode: -v explains why files were skipped by a filter, -vv also
+    /// prints how long the scan, filtering and output took
+    #[arg(short = 'v', long = "verbose", action = ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Only show files older than the version of the given reference file
+    /// Format: --at-least-version-of C:\path\reference.dll
+    #[arg(long = "at-least-version-of", value_name = "FILE")]
+    pub at_least_version_of: Option<String>,
 }
 
 impl CliArgs {
     /// Normalizes the command-line arguments.
     ///
-    /// Auto-enables `include_file_version` if `min_version` or `max_version` is specified,
-    /// since version filtering requires version information to be extracted.
+    /// Auto-enables `include_file_version` if `min_version`, `max_version`,
+    /// `exclude_versions`, `exact_version`, `at_least_version_of` or `detect_regressions` is
+    /// specified, since version filtering requires version information to be
+    /// extracted. With `--version-field product`, the version filters enable
+    /// `product_version` instead. Likewise, `bad_checksum_only` enables `verify_pe_checksum`,
+    /// and `mismatched` enables both `include_file_version` and `product_version`.
+    /// Sorting by version enables `include_file_version` as well, and without
+    /// `--sort`, results are sorted by path. `relative` selects relative paths.
     ///
     /// # Examples
     ///
@@ -72,77 +513,1047 @@ impl CliArgs {
     /// use flist::cli::CliArgs;
     /// use clap::Parser;
     ///
-    /// let mut args = CliArgs::parse_from(&["flist", "--minv", "1.0.0.0"]);
+    /// let mut args = CliArgs::parse_from(["flist", "--minv", "1.0.0.0"]);
     /// args.normalize();
     /// assert!(args.include_file_version);
     /// ```
     pub fn normalize(&mut self) {
-        if self.min_version.is_some() || self.max_version.is_some() {
+        let version_filters = self.min_version.is_some()
+            || self.max_version.is_some()
+            || self.version_range.is_some()
+            || !self.exclude_versions.is_empty()
+            || self.exact_version.is_some()
+            || self.at_least_version_of.is_some();
+        if version_filters && self.version_field == VersionField::Product {
+            self.product_version = true;
+        } else if version_filters {
+            self.include_file_version = true;
+        }
+        if self.detect_regressions
+            || self.summarize
+            || self.keep_highest
+            || self.has_version
+            || self.no_version
+        {
+            self.include_file_version = true;
+        }
+        if self.bad_checksum_only {
+            self.verify_pe_checksum = true;
+        }
+        if self.mismatched {
             self.include_file_version = true;
+            self.product_version = true;
+        }
+        if self.sort.contains(&SortKey::Version) {
+            self.include_file_version = true;
+        }
+        if self.sort.is_empty() {
+            self.sort.push(SortKey::Path);
+        }
+        if self.relative {
+            self.paths = PathStyle::Relative;
+        }
+    }
+
+    /// Returns the format of the `-o` file, `--output-format` or else `--format`.
+    pub fn file_format(&self) -> OutputFormat {
+        self.output_format.unwrap_or(self.format)
+    }
+
+    /// Returns whether files can be printed one by one as they are found.
+    ///
+    /// This is the case for unsorted text or NDJSON output when no option needs the
+    /// complete result set first: version, time and size filters, the checksum
+    /// filter, regression detection and the output and summary files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flist::cli::CliArgs;
+    /// use clap::Parser;
+    ///
+    /// let args = CliArgs::parse_from(["flist", "--unordered", "-i"]);
+    /// assert!(args.can_stream());
+    /// let args = CliArgs::parse_from(["flist", "-i"]);
+    /// assert!(!args.can_stream());
+    /// ```
+    pub fn can_stream(&self) -> bool {
+        let filters = self.min_version.is_some()
+            || self.max_version.is_some()
+            || self.version_range.is_some()
+            || !self.exclude_versions.is_empty()
+            || self.exact_version.is_some()
+            || self.at_least_version_of.is_some()
+            || self.min_semver.is_some()
+            || self.max_semver.is_some()
+            || self.created_after.is_some()
+            || self.created_before.is_some()
+            || self.modified_after.is_some()
+            || self.modified_before.is_some()
+            || self.min_size.is_some()
+            || self.max_size.is_some()
+            || self.bad_checksum_only
+            || self.mismatched
+            || self.keep_highest
+            || self.has_version
+            || self.no_version
+            || self.arch.is_some()
+            || self.unsigned;
+
+        self.unordered
+            && matches!(self.format, OutputFormat::Text | OutputFormat::Ndjson)
+            && !filters
+            && !self.detect_regressions
+            && !self.summarize
+            && !self.dedup
+            && !self.group_by_dir
+            && self.dirs_from.is_none()
+            && self.after_path.is_none()
+            && self.output_file.is_none()
+            && self.summary_json.is_none()
+    }
+
+    /// Returns the verbosity selected by `-q` and `-v`.
+    ///
+    /// This is [`VERBOSITY_NORMAL`] without either flag; each `-v` raises it and
+    /// each `-q` lowers it by one, see the `VERBOSITY_*` levels in
+    /// [`crate::output`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clap::Parser;
+    /// use flist::cli::CliArgs;
+    /// use flist::output::{VERBOSITY_NORMAL, VERBOSITY_SILENT, VERBOSITY_TIMING};
+    ///
+    /// assert_eq!(CliArgs::parse_from(["flist"]).verbosity(), VERBOSITY_NORMAL);
+    /// assert_eq!(CliArgs::parse_from(["flist", "-qq"]).verbosity(), VERBOSITY_SILENT);
+    /// assert_eq!(CliArgs::parse_from(["flist", "-vv"]).verbosity(), VERBOSITY_TIMING);
+    /// ```
+    pub fn verbosity(&self) -> u8 {
+        VERBOSITY_NORMAL
+            .saturating_add(self.verbose)
+            .saturating_sub(self.quiet)
+    }
+
+    /// Returns the files given directly as patterns, which are listed without
+    /// searching a directory.
+    ///
+    /// Every pattern must be a path to an existing file, see
+    /// [`file_lister::direct_file_path`]. Patterns are always searched for with
+    /// `-d`, `--dirs-from` or `--pattern-from`. Paths matching `--exclude` are
+    /// left out, as they would be by the search.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(Some(paths))` if the patterns are files to list directly, `Ok(None)`
+    /// if they are searched for, or an error if an exclude pattern is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clap::Parser;
+    /// use flist::cli::CliArgs;
+    ///
+    /// let args = CliArgs::parse_from(["flist", "*.dll"]);
+    /// assert_eq!(args.direct_files().unwrap(), None);
+    /// ```
+    pub fn direct_files(&self) -> Result<Option<Vec<PathBuf>>, anyhow::Error> {
+        if self.directory.is_some() || self.dirs_from.is_some() || self.pattern_from.is_some() {
+            return Ok(None);
+        }
+        let paths: Option<Vec<PathBuf>> = self
+            .pattern
+            .iter()
+            .map(|pattern| file_lister::direct_file_path(pattern))
+            .collect();
+        match paths {
+            Some(paths) if !paths.is_empty() => {
+                let excludes: Vec<&str> = self.exclude.iter().map(String::as_str).collect();
+                file_lister::filter_excluded_paths(paths, &excludes, self.ignore_case).map(Some)
+            }
+            _ => Ok(None),
         }
     }
 }
 
+/// Parses a human-readable size such as `64K`, `1.5M` or `4096` into bytes.
+///
+/// The units `K`, `M` and `G` are powers of 1024 and may be followed by `B` or
+/// `iB` (`64KB`, `64KiB`). A plain number or a `B` suffix means bytes. Units are
+/// case-insensitive.
+///
+/// # Arguments
+///
+/// * `size` - The size to parse
+///
+/// # Returns
+///
+/// The size in bytes, or a description of the problem.
+///
+/// # Examples
+///
+/// ```
+/// use flist::cli::parse_size;
+///
+/// assert_eq!(parse_size("64K"), Ok(65536));
+/// assert_eq!(parse_size("1.5MiB"), Ok(1572864));
+/// assert!(parse_size("ten").is_err());
+/// ```
+pub fn parse_size(size: &str) -> Result<u64, String> {
+    let size = size.trim();
+    let split = size
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1024,
+        "M" | "MB" | "MIB" => 1024 * 1024,
+        "G" | "GB" | "GIB" => 1024 * 1024 * 1024,
+        _ => return Err(format!("unknown size unit '{}', expected K, M or G", unit)),
+    };
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size '{}'", size))?;
+
+    Ok((number * multiplier as f64) as u64)
+}
+
+/// Parses a hexadecimal language ID such as `0409` or `0x0409`.
+fn parse_language(language: &str) -> Result<u16, String> {
+    let digits = language
+        .strip_prefix("0x")
+        .or_else(|| language.strip_prefix("0X"))
+        .unwrap_or(language);
+    u16::from_str_radix(digits, 16).map_err(|_| {
+        format!(
+            "invalid language ID '{}', expected hex such as 0409",
+            language
+        )
+    })
+}
+
+/// Parses a semantic version such as `1.2.3` or `1.2.3-rc.1`.
+fn parse_semver(version: &str) -> Result<SemanticVersion, String> {
+    version.parse().map_err(|e| {
+        format!(
+            "invalid semantic version '{}': {}, expected e.g. 1.2.3 or 1.2.3-beta",
+            version, e
+        )
+    })
+}
+
+/// Parses the `--buffer-size` value, which must be at least one byte.
+fn parse_buffer_size(size: &str) -> Result<usize, String> {
+    match parse_size(size)? {
+        0 => Err("buffer size must be at least 1 byte".to_string()),
+        bytes => usize::try_from(bytes).map_err(|_| format!("buffer size '{}' is too large", size)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::output::{VERBOSITY_QUIET, VERBOSITY_SILENT, VERBOSITY_TIMING, VERBOSITY_VERBOSE};
+    use clap::{CommandFactory, ValueEnum};
 
     #[test]
     fn test_default_pattern() {
-        let args = CliArgs::parse_from(&["flist"]);
-        assert_eq!(args.pattern, "*");
+        let args = CliArgs::parse_from(["flist"]);
+        assert_eq!(args.pattern, vec!["*"]);
         assert!(!args.include_file_version);
         assert!(!args.sort_by_path);
-        assert!(!args.quiet);
+        assert!(!args.unordered);
+        assert_eq!(args.verbosity(), VERBOSITY_NORMAL);
     }
 
     #[test]
     fn test_custom_pattern() {
-        let args = CliArgs::parse_from(&["flist", "*.dll"]);
-        assert_eq!(args.pattern, "*.dll");
+        let args = CliArgs::parse_from(["flist", "*.dll"]);
+        assert_eq!(args.pattern, vec!["*.dll"]);
+    }
+
+    #[test]
+    fn test_multiple_patterns() {
+        let args = CliArgs::parse_from(["flist", "*.dll", "*.exe", "-i"]);
+        assert_eq!(args.pattern, vec!["*.dll", "*.exe"]);
+        assert!(args.include_file_version);
+    }
+
+    #[test]
+    fn test_pattern_from() {
+        // The default pattern is dropped so only the file's patterns apply
+        let args = CliArgs::parse_from(["flist", "--pattern-from", "patterns.txt"]);
+        assert_eq!(args.pattern_from, Some("patterns.txt".to_string()));
+        assert!(args.pattern.is_empty());
+
+        // An explicit pattern is kept and combined with the file's patterns
+        let args = CliArgs::parse_from(["flist", "*.exe", "--pattern-from", "patterns.txt"]);
+        assert_eq!(args.pattern, vec!["*.exe"]);
+    }
+
+    #[test]
+    fn test_ext() {
+        let args =
+            CliArgs::parse_from(["flist", "--ext", "dll,exe", "--ext", "sys", "--unordered"]);
+        assert_eq!(args.ext, vec!["dll", "exe", "sys"]);
+        assert_eq!(args.pattern, vec!["*"]);
+        assert!(args.can_stream());
+    }
+
+    #[test]
+    fn test_dirs_from() {
+        let args = CliArgs::parse_from(["flist", "*.dll", "--dirs-from", "-", "--show-root"]);
+        assert_eq!(args.dirs_from, Some("-".to_string()));
+        assert!(args.show_root);
+        assert!(
+            !CliArgs::parse_from(["flist", "--unordered", "--dirs-from", "dirs.txt"]).can_stream()
+        );
+        assert!(
+            CliArgs::try_parse_from(["flist", "--dirs-from", "dirs.txt", "-d", "C:\\"]).is_err()
+        );
     }
 
     #[test]
     fn test_flags() {
-        let args = CliArgs::parse_from(&["flist", "-i", "-s", "-q"]);
+        let args = CliArgs::parse_from(["flist", "-i", "-s", "-q"]);
         assert!(args.include_file_version);
         assert!(args.sort_by_path);
-        assert!(args.quiet);
+        assert_eq!(args.verbosity(), VERBOSITY_QUIET);
+    }
+
+    #[test]
+    fn test_verbosity() {
+        for (flags, verbosity) in [
+            (&["-qq"][..], VERBOSITY_SILENT),
+            (&["-q", "--quiet"], VERBOSITY_SILENT),
+            (&["-v"], VERBOSITY_VERBOSE),
+            (&["-vv"], VERBOSITY_TIMING),
+            (&["--verbose", "-v"], VERBOSITY_TIMING),
+        ] {
+            let args = CliArgs::parse_from(["flist"].iter().chain(flags));
+            assert_eq!(args.verbosity(), verbosity, "{:?}", flags);
+        }
+        assert!(CliArgs::try_parse_from(["flist", "-q", "-v"]).is_err());
     }
 
     #[test]
     fn test_long_flags() {
-        let args = CliArgs::parse_from(&["flist", "--ifs", "--sp", "--quiet"]);
+        let args = CliArgs::parse_from(["flist", "--ifs", "--sp", "--quiet"]);
         assert!(args.include_file_version);
         assert!(args.sort_by_path);
-        assert!(args.quiet);
+        assert_eq!(args.verbosity(), VERBOSITY_QUIET);
+    }
+
+    #[test]
+    fn test_unordered() {
+        let args = CliArgs::parse_from(["flist", "--unordered"]);
+        assert!(args.unordered);
+        assert!(!args.sort_by_path);
+
+        // Sorting by path and skipping the sort contradict each other
+        let result = CliArgs::try_parse_from(["flist", "-s", "--unordered"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_created_filters() {
+        let args = CliArgs::parse_from([
+            "flist",
+            "--created-after",
+            "2024-01-01",
+            "--created-before",
+            "7d",
+            "--show-created",
+        ]);
+        assert_eq!(args.created_after, Some("2024-01-01".to_string()));
+        assert_eq!(args.created_before, Some("7d".to_string()));
+        assert!(args.show_created);
+    }
+
+    #[test]
+    fn test_show_pe_timestamp() {
+        assert!(!CliArgs::parse_from(["flist"]).show_pe_timestamp);
+        assert!(CliArgs::parse_from(["flist", "*.exe", "--show-pe-timestamp"]).show_pe_timestamp);
+    }
+
+    #[test]
+    fn test_arch() {
+        let args = CliArgs::parse_from(["flist", "*.dll", "--arch", "x86", "--show-arch"]);
+        assert_eq!(args.arch, Some(PeArch::X86));
+        assert!(args.show_arch);
+        assert!(!CliArgs::parse_from(["flist", "--unordered", "--arch", "arm64"]).can_stream());
+        assert!(CliArgs::parse_from(["flist", "--unordered", "--show-arch"]).can_stream());
+        assert!(CliArgs::try_parse_from(["flist", "--arch", "mips"]).is_err());
+    }
+
+    #[test]
+    fn test_direct_files() {
+        // Tests run in the crate root
+        let args = CliArgs::parse_from(["flist", "./Cargo.toml", "-i"]);
+        assert_eq!(
+            args.direct_files().unwrap(),
+            Some(vec![PathBuf::from("./Cargo.toml")])
+        );
+
+        // A bare name is searched for, including in subdirectories
+        let args = CliArgs::parse_from(["flist", "Cargo.toml"]);
+        assert_eq!(args.direct_files().unwrap(), None);
+
+        // A search directory makes every pattern a search pattern
+        let args = CliArgs::parse_from(["flist", "./Cargo.toml", "-d", "src"]);
+        assert_eq!(args.direct_files().unwrap(), None);
+        let args = CliArgs::parse_from(["flist", "./Cargo.toml", "--dirs-from", "d.txt"]);
+        assert_eq!(args.direct_files().unwrap(), None);
+
+        // Excludes apply to direct files too
+        let args = CliArgs::parse_from(["flist", "./Cargo.toml", "-e", "Cargo.toml"]);
+        assert_eq!(args.direct_files().unwrap(), Some(vec![]));
+        let args = CliArgs::parse_from([
+            "flist",
+            "./Cargo.toml",
+            "./src/lib.rs",
+            "-e",
+            "SRC",
+            "--ignore-case",
+        ]);
+        assert_eq!(
+            args.direct_files().unwrap(),
+            Some(vec![PathBuf::from("./Cargo.toml")])
+        );
+    }
+
+    #[test]
+    fn test_unsigned() {
+        assert!(!CliArgs::parse_from(["flist", "*.exe"]).unsigned);
+        assert!(CliArgs::parse_from(["flist", "*.exe", "--unsigned"]).unsigned);
+        assert!(!CliArgs::parse_from(["flist", "--unordered", "--unsigned"]).can_stream());
+    }
+
+    #[test]
+    fn test_modified_filters() {
+        let args = CliArgs::parse_from([
+            "flist",
+            "--modified-after",
+            "2024-01-01",
+            "--modified-before",
+            "1d",
+        ]);
+        assert_eq!(args.modified_after, Some("2024-01-01".to_string()));
+        assert_eq!(args.modified_before, Some("1d".to_string()));
+    }
+
+    #[test]
+    fn test_progress_interval() {
+        assert_eq!(CliArgs::parse_from(["flist"]).progress_interval, 0);
+        let args = CliArgs::parse_from(["flist", "--progress-interval", "30"]);
+        assert_eq!(args.progress_interval, 30);
+        assert!(CliArgs::try_parse_from(["flist", "--progress-interval", "-1"]).is_err());
+        assert!(CliArgs::parse_from(["flist", "--progress"]).progress);
+        assert!(
+            CliArgs::try_parse_from(["flist", "--progress", "--progress-interval", "5"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_match_dirs() {
+        let args = CliArgs::parse_from(["flist", "*cache*", "--match-dirs"]);
+        assert!(args.match_dirs);
+        assert!(!CliArgs::parse_from(["flist"]).match_dirs);
+    }
+
+    #[test]
+    fn test_test_pattern() {
+        let args = CliArgs::parse_from(["flist", "--test-pattern", "*.dll", "core.dll"]);
+        assert_eq!(
+            args.test_pattern,
+            Some(vec!["*.dll".to_string(), "core.dll".to_string()])
+        );
+
+        assert!(CliArgs::try_parse_from(["flist", "--test-pattern", "*.dll"]).is_err());
+    }
+
+    #[test]
+    fn test_print_schema() {
+        assert!(!CliArgs::parse_from(["flist"]).print_schema);
+        assert!(CliArgs::parse_from(["flist", "--print-schema"]).print_schema);
+    }
+
+    #[test]
+    fn test_check() {
+        assert!(!CliArgs::parse_from(["flist"]).check);
+        let args = CliArgs::parse_from(["flist", "--check", "*.{dll", "-d", "/nope"]);
+        assert!(args.check);
+        assert_eq!(args.pattern, vec!["*.{dll"]);
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("4096"), Ok(4096));
+        assert_eq!(parse_size("512b"), Ok(512));
+        assert_eq!(parse_size("64K"), Ok(64 * 1024));
+        assert_eq!(parse_size("64kb"), Ok(64 * 1024));
+        assert_eq!(parse_size("1 MiB"), Ok(1024 * 1024));
+        assert_eq!(parse_size("0.5M"), Ok(512 * 1024));
+        assert_eq!(parse_size("2G"), Ok(2 * 1024 * 1024 * 1024));
+        assert!(parse_size("").is_err());
+        assert!(parse_size("10X").is_err());
+        assert!(parse_size("K").is_err());
+    }
+
+    #[test]
+    fn test_buffer_size() {
+        let args = CliArgs::parse_from(["flist"]);
+        assert_eq!(args.buffer_size, DEFAULT_READ_BUFFER_SIZE);
+
+        let args = CliArgs::parse_from(["flist", "--buffer-size", "1M"]);
+        assert_eq!(args.buffer_size, 1024 * 1024);
+
+        assert!(CliArgs::try_parse_from(["flist", "--buffer-size", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_exclude_versions() {
+        let mut args = CliArgs::parse_from(["flist", "--neqv", "1.2.3.4", "--neqv", "2.*"]);
+        assert_eq!(args.exclude_versions, vec!["1.2.3.4", "2.*"]);
+        args.normalize();
+        assert!(args.include_file_version);
+    }
+
+    #[test]
+    fn test_exact_version() {
+        let mut args = CliArgs::parse_from(["flist", "--eq", "1.2"]);
+        assert_eq!(args.exact_version.as_deref(), Some("1.2"));
+        assert!(!args.can_stream());
+        args.normalize();
+        assert!(args.include_file_version);
+
+        let args = CliArgs::parse_from(["flist", "--eqv", "1.2.3.4"]);
+        assert_eq!(args.exact_version.as_deref(), Some("1.2.3.4"));
+    }
+
+    #[test]
+    fn test_fail_if_empty() {
+        assert_eq!(CliArgs::parse_from(["flist"]).fail_if_empty, None);
+        let args = CliArgs::parse_from(["flist", "--fail-if-empty", "*.dll"]);
+        assert_eq!(args.fail_if_empty, Some(1));
+        assert_eq!(args.pattern, vec!["*.dll"]);
+        let args = CliArgs::parse_from(["flist", "--fail-if-empty=3"]);
+        assert_eq!(args.fail_if_empty, Some(3));
+        assert!(CliArgs::try_parse_from(["flist", "--fail-if-empty=0"]).is_err());
+        assert!(CliArgs::try_parse_from(["flist", "--fail-if-empty=256"]).is_err());
+        // Without '=' the next argument is a pattern, not the code
+        let args = CliArgs::parse_from(["flist", "--fail-if-empty", "3"]);
+        assert_eq!(args.fail_if_empty, Some(1));
+    }
+
+    #[test]
+    fn test_count() {
+        assert!(!CliArgs::parse_from(["flist"]).count);
+        let args = CliArgs::parse_from(["flist", "*.dll", "-c", "--minv", "1.0"]);
+        assert!(args.count);
+        assert!(CliArgs::try_parse_from(["flist", "-c", "--format", "json"]).is_err());
+        assert!(CliArgs::try_parse_from(["flist", "-c", "--detect-regressions"]).is_err());
+    }
+
+    #[test]
+    fn test_null() {
+        assert!(!CliArgs::parse_from(["flist"]).null);
+        let args = CliArgs::parse_from(["flist", "-0", "*.dll", "-i"]);
+        assert!(args.null);
+        assert_eq!(args.pattern, vec!["*.dll"]);
+        assert!(CliArgs::parse_from(["flist", "--null"]).null);
+        assert!(CliArgs::try_parse_from(["flist", "-0", "--format", "csv"]).is_err());
+        assert!(CliArgs::try_parse_from(["flist", "-0", "--count"]).is_err());
+    }
+
+    #[test]
+    fn test_limit() {
+        assert_eq!(CliArgs::parse_from(["flist"]).limit, None);
+        assert_eq!(CliArgs::parse_from(["flist", "-n", "100"]).limit, Some(100));
+        assert_eq!(
+            CliArgs::parse_from(["flist", "--limit", "0"]).limit,
+            Some(0)
+        );
+        assert!(CliArgs::try_parse_from(["flist", "--limit", "-1"]).is_err());
+    }
+
+    #[test]
+    fn test_reverse() {
+        assert!(!CliArgs::parse_from(["flist"]).reverse);
+        let args = CliArgs::parse_from(["flist", "-R", "--sort", "version"]);
+        assert!(args.reverse);
+        assert!(CliArgs::try_parse_from(["flist", "--reverse", "--unordered"]).is_err());
+        assert!(CliArgs::try_parse_from(["flist", "-R", "--after-path", "a.dll"]).is_err());
+    }
+
+    #[test]
+    fn test_natural_sort() {
+        assert!(!CliArgs::parse_from(["flist"]).natural_sort);
+        assert!(CliArgs::parse_from(["flist", "--natural-sort"]).natural_sort);
+        assert!(CliArgs::try_parse_from(["flist", "--natural-sort", "--unordered"]).is_err());
+        assert!(CliArgs::try_parse_from(["flist", "--natural-sort", "--after-path", "a"]).is_err());
+    }
+
+    #[test]
+    fn test_sort_keys() {
+        let mut args = CliArgs::parse_from(["flist", "-i"]);
+        args.normalize();
+        assert_eq!(args.sort, vec![SortKey::Path]);
+
+        let mut args = CliArgs::parse_from(["flist", "--sort", "version,path"]);
+        args.normalize();
+        assert_eq!(args.sort, vec![SortKey::Version, SortKey::Path]);
+        assert!(args.include_file_version);
+
+        let mut args = CliArgs::parse_from(["flist", "--sort", "ext", "--group-separator"]);
+        args.normalize();
+        assert_eq!(args.sort, vec![SortKey::Extension]);
+        assert!(!args.include_file_version);
+
+        assert!(CliArgs::try_parse_from(["flist", "--sort", "size"]).is_err());
+        assert!(CliArgs::try_parse_from(["flist", "--sort", "path", "--unordered"]).is_err());
+    }
+
+    #[test]
+    fn test_file_summary() {
+        let args = CliArgs::parse_from(["flist", "-o", "out.txt"]);
+        assert!(!args.file_summary);
+
+        let args = CliArgs::parse_from(["flist", "-o", "out.txt", "--file-summary", "-q"]);
+        assert!(args.file_summary);
+        assert_eq!(args.verbosity(), VERBOSITY_QUIET);
+    }
+
+    #[test]
+    fn test_summary_json() {
+        let args = CliArgs::parse_from(["flist", "--summary-json", "summary.json"]);
+        assert_eq!(args.summary_json, Some("summary.json".to_string()));
+        assert_eq!(args.output_file, None);
+    }
+
+    #[test]
+    fn test_mismatched() {
+        let mut args = CliArgs::parse_from(["flist", "*.dll", "--mismatched", "--unordered"]);
+        assert!(args.mismatched);
+        assert!(!args.can_stream());
+        args.normalize();
+        assert!(args.include_file_version);
+        assert!(args.product_version);
+    }
+
+    #[test]
+    fn test_has_version() {
+        for flag in ["--has-version", "--no-version"] {
+            let mut args = CliArgs::parse_from(["flist", "*.exe", flag, "--unordered"]);
+            assert!(!args.can_stream());
+            args.normalize();
+            assert!(args.include_file_version);
+        }
+        assert!(CliArgs::parse_from(["flist", "--no-version"]).no_version);
+        assert!(CliArgs::try_parse_from(["flist", "--has-version", "--no-version"]).is_err());
+    }
+
+    #[test]
+    fn test_keep_highest() {
+        let mut args = CliArgs::parse_from(["flist", "*.dll", "--keep-highest", "--unordered"]);
+        assert!(args.keep_highest);
+        assert!(!args.can_stream());
+        args.normalize();
+        assert!(args.include_file_version);
+    }
+
+    #[test]
+    fn test_dedup() {
+        let args = CliArgs::parse_from(["flist", "*.dll", "--dedup", "--unordered"]);
+        assert!(args.dedup);
+        assert!(!args.can_stream());
+        assert!(CliArgs::try_parse_from(["flist", "--dedup", "--format", "csv"]).is_err());
+        assert!(CliArgs::try_parse_from(["flist", "--dedup", "-0"]).is_err());
+    }
+
+    #[test]
+    fn test_summarize() {
+        let mut args = CliArgs::parse_from(["flist", "*.dll", "--summarize", "--unordered"]);
+        assert!(args.summarize);
+        assert!(!args.can_stream());
+        args.normalize();
+        assert!(args.include_file_version);
+        assert!(CliArgs::try_parse_from(["flist", "--summarize", "--format", "json"]).is_err());
+        assert!(CliArgs::try_parse_from(["flist", "--summarize", "-c"]).is_err());
+    }
+
+    #[test]
+    fn test_detect_regressions() {
+        let mut args = CliArgs::parse_from(["flist", "*.dll", "--detect-regressions"]);
+        assert!(args.detect_regressions);
+        args.normalize();
+        assert!(args.include_file_version);
+    }
+
+    #[test]
+    fn test_checksum_flags() {
+        let args = CliArgs::parse_from(["flist", "--verify-pe-checksum"]);
+        assert!(args.verify_pe_checksum);
+        assert!(!args.bad_checksum_only);
+
+        let mut args = CliArgs::parse_from(["flist", "--bad-checksum-only"]);
+        assert!(!args.verify_pe_checksum);
+        args.normalize();
+        assert!(args.verify_pe_checksum);
+    }
+
+    #[test]
+    fn test_ads() {
+        let args = CliArgs::parse_from(["flist", "--ads"]);
+        assert!(args.ads);
+    }
+
+    #[test]
+    fn test_max_open_files() {
+        let args = CliArgs::parse_from(["flist"]);
+        assert_eq!(args.max_open_files, OpenFileLimiter::DEFAULT_MAX_OPEN_FILES);
+
+        let args = CliArgs::parse_from(["flist", "--max-open-files", "8"]);
+        assert_eq!(args.max_open_files, 8);
+
+        let result = CliArgs::try_parse_from(["flist", "--max-open-files", "0"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_size_filters() {
+        let args = CliArgs::parse_from(["flist", "--min-size", "10MB", "--max-size", "1G"]);
+        assert_eq!(args.min_size, Some(10 * 1024 * 1024));
+        assert_eq!(args.max_size, Some(1024 * 1024 * 1024));
+
+        let args = CliArgs::parse_from(["flist", "--min-size", "500K"]);
+        assert_eq!(args.min_size, Some(500 * 1024));
+        assert_eq!(args.max_size, None);
+
+        assert!(CliArgs::try_parse_from(["flist", "--min-size", "10XB"]).is_err());
+    }
+
+    #[test]
+    fn test_can_stream() {
+        let args = CliArgs::parse_from(["flist", "--unordered"]);
+        assert!(args.can_stream());
+        let args = CliArgs::parse_from(["flist", "--unordered", "-i", "--ads", "-L", "2"]);
+        assert!(args.can_stream());
+
+        // Sorting, filters and summaries need all files first
+        for extra in [
+            &["--minv", "1.0"][..],
+            &["--neqv", "1.*"],
+            &["--min-semver", "1.0.0"],
+            &["--modified-after", "7d"],
+            &["--min-size", "1M"],
+            &["--bad-checksum-only"],
+            &["--detect-regressions"],
+            &["-o", "out.txt"],
+            &["--summary-json", "summary.json"],
+        ] {
+            let mut argv = vec!["flist", "--unordered"];
+            argv.extend_from_slice(extra);
+            assert!(!CliArgs::parse_from(argv).can_stream(), "{:?}", extra);
+        }
+        assert!(!CliArgs::parse_from(["flist"]).can_stream());
+    }
+
+    #[test]
+    fn test_semver_filters() {
+        let args = CliArgs::parse_from(["flist", "--min-semver", "1.2.3-beta", "--semver"]);
+        assert_eq!(args.min_semver, Some("1.2.3-beta".parse().unwrap()));
+        assert_eq!(args.max_semver, None);
+        assert!(args.semver);
+
+        let args = CliArgs::parse_from(["flist", "--max-semver", "v2.0"]);
+        assert_eq!(args.max_semver, Some("2.0.0".parse().unwrap()));
+
+        assert!(CliArgs::try_parse_from(["flist", "--min-semver", "1.2.3.4"]).is_err());
+        assert!(CliArgs::try_parse_from(["flist", "--max-semver", "1.0-"]).is_err());
+    }
+
+    #[test]
+    fn test_version_field() {
+        let args = CliArgs::parse_from(["flist"]);
+        assert_eq!(args.version_field, VersionField::File);
+        assert!(!args.product_version);
+
+        // Filters on the product version read the product version only
+        let mut args =
+            CliArgs::parse_from(["flist", "--minv", "2.0", "--version-field", "product"]);
+        args.normalize();
+        assert!(args.product_version);
+        assert!(!args.include_file_version);
+
+        let mut args = CliArgs::parse_from(["flist", "--product-version", "-i"]);
+        args.normalize();
+        assert!(args.product_version);
+        assert!(args.include_file_version);
+
+        assert!(CliArgs::try_parse_from(["flist", "--version-field", "company"]).is_err());
+    }
+
+    #[test]
+    fn test_strings() {
+        let args = CliArgs::parse_from(["flist", "--strings", "company,description"]);
+        assert_eq!(
+            args.strings,
+            vec![StringField::Company, StringField::Description]
+        );
+        assert_eq!(args.language, None);
+
+        let args = CliArgs::parse_from(["flist", "--strings", "product", "--language", "0407"]);
+        assert_eq!(args.language, Some(0x0407));
+        let args = CliArgs::parse_from(["flist", "--language", "0x409"]);
+        assert_eq!(args.language, Some(0x0409));
+
+        assert!(CliArgs::try_parse_from(["flist", "--strings", "version"]).is_err());
+        assert!(CliArgs::try_parse_from(["flist", "--language", "english"]).is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(CliArgs::parse_from(["flist"]).format, OutputFormat::Text);
+        let args = CliArgs::parse_from(["flist", "--format", "json", "--unordered"]);
+        assert_eq!(args.format, OutputFormat::Json);
+        assert!(!args.can_stream());
+        assert_eq!(
+            CliArgs::parse_from(["flist", "--format", "csv"]).format,
+            OutputFormat::Csv
+        );
+        assert_eq!(
+            CliArgs::parse_from(["flist", "--format", "xml"]).format,
+            OutputFormat::Xml
+        );
+        assert_eq!(
+            CliArgs::parse_from(["flist", "--format", "markdown"]).format,
+            OutputFormat::Markdown
+        );
+        let args = CliArgs::parse_from(["flist", "--format", "ndjson", "--unordered"]);
+        assert_eq!(args.format, OutputFormat::Ndjson);
+        assert!(args.can_stream());
+        assert!(CliArgs::try_parse_from(["flist", "--format", "yaml"]).is_err());
+    }
+
+    #[test]
+    fn test_output_format() {
+        let args = CliArgs::parse_from(["flist", "-o", "files.txt"]);
+        assert_eq!(args.output_format, None);
+        assert_eq!(args.file_format(), OutputFormat::Text);
+
+        let args = CliArgs::parse_from(["flist", "--format", "csv", "-o", "files.csv"]);
+        assert_eq!(args.file_format(), OutputFormat::Csv);
+
+        let args = CliArgs::parse_from(["flist", "-o", "files.json", "--output-format", "json"]);
+        assert_eq!(args.format, OutputFormat::Text);
+        assert_eq!(args.file_format(), OutputFormat::Json);
+        assert!(CliArgs::try_parse_from(["flist", "--output-format", "json"]).is_err());
+
+        // The help lists every format, as clap renders the values of the enum
+        let help = CliArgs::command().render_long_help().to_string();
+        for option in ["--format <", "--output-format <"] {
+            let section: Vec<&str> = help
+                .lines()
+                .skip_while(|line| !line.trim_start().starts_with(option))
+                .skip(1)
+                .take_while(|line| line.is_empty() || line.starts_with("          "))
+                .collect();
+            for format in OutputFormat::value_variants() {
+                let name = format.to_possible_value().unwrap();
+                let entry = format!("- {}:", name.get_name());
+                assert!(
+                    section
+                        .iter()
+                        .any(|line| line.trim_start().starts_with(&entry)),
+                    "{} missing from {}{:?}",
+                    entry,
+                    option,
+                    section
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_threads() {
+        let args = CliArgs::parse_from(["flist"]);
+        assert_eq!(args.threads, None);
+
+        let args = CliArgs::parse_from(["flist", "--threads", "4"]);
+        assert_eq!(args.threads, Some(4));
+
+        assert!(CliArgs::try_parse_from(["flist", "--threads", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_after_path() {
+        let args = CliArgs::parse_from(["flist", "--after-path", "C:\\test\\b.dll"]);
+        assert_eq!(args.after_path, Some("C:\\test\\b.dll".to_string()));
+
+        // Resuming needs a deterministic order
+        let result = CliArgs::try_parse_from(["flist", "--after-path", "b.dll", "--unordered"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_max_width() {
+        let args = CliArgs::parse_from(["flist", "--max-width", "60"]);
+        assert_eq!(args.max_width, Some(60));
+
+        let args = CliArgs::parse_from(["flist"]);
+        assert_eq!(args.max_width, None);
+    }
+
+    #[test]
+    fn test_color() {
+        assert_eq!(CliArgs::parse_from(["flist"]).color, ColorChoice::Auto);
+        let args = CliArgs::parse_from(["flist", "--color", "never"]);
+        assert_eq!(args.color, ColorChoice::Never);
+        assert!(CliArgs::try_parse_from(["flist", "--color", "sometimes"]).is_err());
+    }
+
+    #[test]
+    fn test_paths() {
+        let args = CliArgs::parse_from(["flist"]);
+        assert_eq!(args.paths, PathStyle::Full);
+
+        let args = CliArgs::parse_from(["flist", "--paths", "both"]);
+        assert_eq!(args.paths, PathStyle::Both);
+
+        let result = CliArgs::try_parse_from(["flist", "--paths", "sideways"]);
+        assert!(result.is_err());
+
+        let mut args = CliArgs::parse_from(["flist", "--relative"]);
+        args.normalize();
+        assert_eq!(args.paths, PathStyle::Relative);
+        let result = CliArgs::try_parse_from(["flist", "--relative", "--paths", "both"]);
+        assert!(result.is_err());
+
+        assert!(CliArgs::parse_from(["flist", "--absolute"]).absolute);
+        let result = CliArgs::try_parse_from(["flist", "--absolute", "--relative"]);
+        assert!(result.is_err());
+        let result = CliArgs::try_parse_from(["flist", "--absolute", "--paths", "full"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_field_separator() {
+        let args = CliArgs::parse_from(["flist", "-i", "--field-separator", "|"]);
+        assert_eq!(args.field_separator, Some("|".to_string()));
+    }
+
+    #[test]
+    fn test_template() {
+        let args = CliArgs::parse_from(["flist", "--template", "{name} {version}"]);
+        assert_eq!(args.template.as_deref(), Some("{name} {version}"));
+        assert!(CliArgs::try_parse_from(["flist", "--template", "{path}", "-0"]).is_err());
+        assert!(
+            CliArgs::try_parse_from(["flist", "--template", "{path}", "--format", "csv"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_group_separator() {
+        let args = CliArgs::parse_from(["flist", "--group-separator"]);
+        assert!(args.group_separator);
+
+        // Groups are only meaningful for sorted output
+        let result = CliArgs::try_parse_from(["flist", "--group-separator", "--unordered"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_group_by_dir() {
+        let args = CliArgs::parse_from(["flist", "--group-by-dir", "--unordered"]);
+        assert!(args.group_by_dir);
+        // Directories are grouped once all files are known
+        assert!(!args.can_stream());
+
+        for other in ["--null", "--group-separator"] {
+            let result = CliArgs::try_parse_from(["flist", "--group-by-dir", other]);
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn test_stdin_paths() {
+        assert!(!CliArgs::parse_from(["flist"]).stdin_paths);
+        assert!(CliArgs::parse_from(["flist", "--stdin-paths", "-i"]).stdin_paths);
+
+        for other in [
+            &["*.dll"][..],
+            &["--pattern-from", "p.txt"],
+            &["--dirs-from", "d.txt"],
+        ] {
+            let result =
+                CliArgs::try_parse_from(["flist", "--stdin-paths"].iter().chain(other.iter()));
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn test_append() {
+        assert!(!CliArgs::parse_from(["flist", "-o", "report.txt"]).append);
+        assert!(CliArgs::parse_from(["flist", "-o", "report.txt", "--append"]).append);
+        assert!(CliArgs::try_parse_from(["flist", "--append"]).is_err());
+    }
+
+    #[test]
+    fn test_version_range() {
+        let mut args = CliArgs::parse_from(["flist", "--version-range", "1.0..2.0"]);
+        assert_eq!(args.version_range, Some("1.0..2.0".to_string()));
+        args.normalize();
+        assert!(args.include_file_version);
+        assert!(
+            !CliArgs::parse_from(["flist", "--unordered", "--version-range", "..2"]).can_stream()
+        );
+
+        let error = CliArgs::try_parse_from(["flist", "--version-range", "1.0..", "--minv", "1.0"])
+            .unwrap_err();
+        assert_eq!(error.kind(), clap::error::ErrorKind::ArgumentConflict);
+        assert!(
+            CliArgs::try_parse_from(["flist", "--maxv", "2.0", "--version-range", "..2.0"])
+                .is_err()
+        );
     }
 
     #[test]
     fn test_version_filters() {
-        let args = CliArgs::parse_from(&["flist", "--minv", "1.0.0.0", "--maxv", "2.0.0.0"]);
+        let args = CliArgs::parse_from(["flist", "--minv", "1.0.0.0", "--maxv", "2.0.0.0"]);
         assert_eq!(args.min_version, Some("1.0.0.0".to_string()));
         assert_eq!(args.max_version, Some("2.0.0.0".to_string()));
     }
 
     #[test]
     fn test_directory_and_output() {
-        let args = CliArgs::parse_from(&["flist", "-d", "C:\\test", "-o", "output.txt"]);
+        let args = CliArgs::parse_from(["flist", "-d", "C:\\test", "-o", "output.txt"]);
         assert_eq!(args.directory, Some("C:\\test".to_string()));
         assert_eq!(args.output_file, Some("output.txt".to_string()));
     }
 
     #[test]
     fn test_normalize_auto_enables_version() {
-        let mut args = CliArgs::parse_from(&["flist", "--minv", "1.0.0.0"]);
+        let mut args = CliArgs::parse_from(["flist", "--minv", "1.0.0.0"]);
         assert!(!args.include_file_version); // Not set initially
         args.normalize();
         assert!(args.include_file_version); // Auto-enabled
     }
 
+    #[test]
+    fn test_at_least_version_of() {
+        let mut args = CliArgs::parse_from(["flist", "*.dll", "--at-least-version-of", "ref.dll"]);
+        assert_eq!(args.at_least_version_of, Some("ref.dll".to_string()));
+        args.normalize();
+        assert!(args.include_file_version);
+    }
+
     #[test]
     fn test_all_options_combined() {
-        let args = CliArgs::parse_from(&[
+        let args = CliArgs::parse_from([
             "flist",
             "*.exe",
             "-i",
@@ -157,10 +1568,10 @@ mod tests {
             "-o",
             "results.txt",
         ]);
-        assert_eq!(args.pattern, "*.exe");
+        assert_eq!(args.pattern, vec!["*.exe"]);
         assert!(args.include_file_version);
         assert!(args.sort_by_path);
-        assert!(args.quiet);
+        assert_eq!(args.verbosity(), VERBOSITY_QUIET);
         assert_eq!(args.min_version, Some("1.0.0.0".to_string()));
         assert_eq!(args.max_version, Some("2.0.0.0".to_string()));
         assert_eq!(args.directory, Some("C:\\Windows".to_string()));
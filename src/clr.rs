@@ -0,0 +1,376 @@
+//! Reading the CLI metadata of managed .NET assemblies.
+//!
+//! This module provides [`assembly_version`], which finds the `AssemblyVersion`
+//! in the metadata of an assembly, as located by the COM descriptor (CLI
+//! header) of its PE image. The layout follows ECMA-335, partition II: a
+//! metadata root listing the streams, and in the `#~` stream the row counts of
+//! the metadata tables followed by the tables themselves. Rows have no size
+//! field, so the tables before the `Assembly` table are skipped by computing
+//! their row sizes from the column types.
+
+use crate::file_version::FileVersion;
+
+/// Signature of the metadata root, `BSJB`.
+const METADATA_SIGNATURE: u32 = 0x424A_5342;
+
+/// Number of the `Assembly` table.
+const ASSEMBLY_TABLE: usize = 0x20;
+
+/// Flags in the `HeapSizes` field of the tables stream.
+const WIDE_STRING_INDEX: u8 = 0x01;
+const WIDE_GUID_INDEX: u8 = 0x02;
+const WIDE_BLOB_INDEX: u8 = 0x04;
+/// Set in uncompressed `#-` streams that have four extra bytes after the row counts.
+const EXTRA_DATA: u8 = 0x20;
+
+/// A column of a metadata table, as far as its size is concerned.
+#[derive(Debug, Clone, Copy)]
+enum ColumnType {
+    /// A constant of the given size in bytes
+    Fixed(usize),
+    /// An index into the `#Strings` heap
+    StringIndex,
+    /// An index into the `#GUID` heap
+    GuidIndex,
+    /// An index into the `#Blob` heap
+    BlobIndex,
+    /// An index into a table
+    TableIndex(usize),
+    /// An index into one of several tables, with the tag bits that select it
+    Coded(u32, &'static [usize]),
+}
+
+use ColumnType::{BlobIndex, Coded, Fixed, GuidIndex, StringIndex, TableIndex};
+
+const TYPE_DEF_OR_REF: ColumnType = Coded(2, &[0x02, 0x01, 0x1B]);
+const HAS_CONSTANT: ColumnType = Coded(2, &[0x04, 0x08, 0x17]);
+const HAS_CUSTOM_ATTRIBUTE: ColumnType = Coded(
+    5,
+    &[
+        0x06, 0x04, 0x01, 0x02, 0x08, 0x09, 0x0A, 0x00, 0x0E, 0x17, 0x14, 0x11, 0x1A, 0x1B, 0x20,
+        0x23, 0x26, 0x27, 0x28, 0x2A, 0x2C, 0x2B,
+    ],
+);
+const HAS_FIELD_MARSHAL: ColumnType = Coded(1, &[0x04, 0x08]);
+const HAS_DECL_SECURITY: ColumnType = Coded(2, &[0x02, 0x06, 0x20]);
+const MEMBER_REF_PARENT: ColumnType = Coded(3, &[0x02, 0x01, 0x1A, 0x06, 0x1B]);
+const HAS_SEMANTICS: ColumnType = Coded(1, &[0x14, 0x17]);
+const METHOD_DEF_OR_REF: ColumnType = Coded(1, &[0x06, 0x0A]);
+const MEMBER_FORWARDED: ColumnType = Coded(1, &[0x04, 0x06]);
+const CUSTOM_ATTRIBUTE_TYPE: ColumnType = Coded(3, &[0x06, 0x0A]);
+const RESOLUTION_SCOPE: ColumnType = Coded(2, &[0x00, 0x1A, 0x23, 0x01]);
+
+/// Columns of the tables in front of the `Assembly` table, by table number.
+const TABLE_COLUMNS: [&[ColumnType]; ASSEMBLY_TABLE] = [
+    // 0x00 Module
+    &[Fixed(2), StringIndex, GuidIndex, GuidIndex, GuidIndex],
+    // 0x01 TypeRef
+    &[RESOLUTION_SCOPE, StringIndex, StringIndex],
+    // 0x02 TypeDef
+    &[
+        Fixed(4),
+        StringIndex,
+        StringIndex,
+        TYPE_DEF_OR_REF,
+        TableIndex(0x04),
+        TableIndex(0x06),
+    ],
+    // 0x03 FieldPtr
+    &[TableIndex(0x04)],
+    // 0x04 Field
+    &[Fixed(2), StringIndex, BlobIndex],
+    // 0x05 MethodPtr
+    &[TableIndex(0x06)],
+    // 0x06 MethodDef
+    &[
+        Fixed(4),
+        Fixed(2),
+        Fixed(2),
+        StringIndex,
+        BlobIndex,
+        TableIndex(0x08),
+    ],
+    // 0x07 ParamPtr
+    &[TableIndex(0x08)],
+    // 0x08 Param
+    &[Fixed(2), Fixed(2), StringIndex],
+    // 0x09 InterfaceImpl
+    &[TableIndex(0x02), TYPE_DEF_OR_REF],
+    // 0x0A MemberRef
+    &[MEMBER_REF_PARENT, StringIndex, BlobIndex],
+    // 0x0B Constant
+    &[Fixed(2), HAS_CONSTANT, BlobIndex],
+    // 0x0C CustomAttribute
+    &[HAS_CUSTOM_ATTRIBUTE, CUSTOM_ATTRIBUTE_TYPE, BlobIndex],
+    // 0x0D FieldMarshal
+    &[HAS_FIELD_MARSHAL, BlobIndex],
+    // 0x0E DeclSecurity
+    &[Fixed(2), HAS_DECL_SECURITY, BlobIndex],
+    // 0x0F ClassLayout
+    &[Fixed(2), Fixed(4), TableIndex(0x02)],
+    // 0x10 FieldLayout
+    &[Fixed(4), TableIndex(0x04)],
+    // 0x11 StandAloneSig
+    &[BlobIndex],
+    // 0x12 EventMap
+    &[TableIndex(0x02), TableIndex(0x14)],
+    // 0x13 EventPtr
+    &[TableIndex(0x14)],
+    // 0x14 Event
+    &[Fixed(2), StringIndex, TYPE_DEF_OR_REF],
+    // 0x15 PropertyMap
+    &[TableIndex(0x02), TableIndex(0x17)],
+    // 0x16 PropertyPtr
+    &[TableIndex(0x17)],
+    // 0x17 Property
+    &[Fixed(2), StringIndex, BlobIndex],
+    // 0x18 MethodSemantics
+    &[Fixed(2), TableIndex(0x06), HAS_SEMANTICS],
+    // 0x19 MethodImpl
+    &[TableIndex(0x02), METHOD_DEF_OR_REF, METHOD_DEF_OR_REF],
+    // 0x1A ModuleRef
+    &[StringIndex],
+    // 0x1B TypeSpec
+    &[BlobIndex],
+    // 0x1C ImplMap
+    &[Fixed(2), MEMBER_FORWARDED, StringIndex, TableIndex(0x1A)],
+    // 0x1D FieldRVA
+    &[Fixed(4), TableIndex(0x04)],
+    // 0x1E EncLog
+    &[Fixed(4), Fixed(4)],
+    // 0x1F EncMap
+    &[Fixed(4)],
+];
+
+/// Reads the `AssemblyVersion` from the metadata of a managed assembly.
+///
+/// # Arguments
+///
+/// * `metadata` - The metadata, starting at the metadata root that the CLI
+///   header of the PE image points to
+///
+/// # Returns
+///
+/// The version of the first row of the `Assembly` table, or `None` if the
+/// metadata is malformed or has no `Assembly` table, as in a netmodule.
+///
+/// # Examples
+///
+/// ```
+/// use flist::clr::assembly_version;
+///
+/// assert_eq!(assembly_version(b"not metadata"), None);
+/// ```
+pub fn assembly_version(metadata: &[u8]) -> Option<FileVersion> {
+    let tables = tables_stream(metadata)?;
+    let heap_sizes = *tables.get(6)?;
+    let present = u64_at(tables, 8)?;
+
+    let mut rows = [0usize; 64];
+    let mut offset = 24;
+    for (table, count) in rows.iter_mut().enumerate() {
+        if present & (1 << table) != 0 {
+            *count = u32_at(tables, offset)? as usize;
+            offset += 4;
+        }
+    }
+    if heap_sizes & EXTRA_DATA != 0 {
+        offset += 4;
+    }
+    if rows[ASSEMBLY_TABLE] == 0 {
+        return None;
+    }
+
+    for (table, columns) in TABLE_COLUMNS.iter().enumerate() {
+        let row_size: usize = columns
+            .iter()
+            .map(|&column| column_size(column, heap_sizes, &rows))
+            .sum();
+        offset = offset.checked_add(rows[table].checked_mul(row_size)?)?;
+    }
+
+    // HashAlgId, then MajorVersion, MinorVersion, BuildNumber and RevisionNumber
+    let part = |index: usize| u16_at(tables, offset + 4 + 2 * index).map(u32::from);
+    Some(FileVersion::new(
+        Some(part(0)?),
+        Some(part(1)?),
+        Some(part(2)?),
+        Some(part(3)?),
+    ))
+}
+
+/// Finds the `#~` tables stream, or the uncompressed `#-` variant, in the
+/// streams listed by the metadata root.
+fn tables_stream(metadata: &[u8]) -> Option<&[u8]> {
+    if u32_at(metadata, 0)? != METADATA_SIGNATURE {
+        return None;
+    }
+
+    // The length of the runtime version string includes its padding
+    let version_length = u32_at(metadata, 12)? as usize;
+    let mut offset = 16usize.checked_add(version_length)?;
+    let streams = u16_at(metadata, offset + 2)?;
+    offset += 4;
+
+    for _ in 0..streams {
+        let stream_offset = u32_at(metadata, offset)? as usize;
+        let stream_size = u32_at(metadata, offset + 4)? as usize;
+        let name_start = offset + 8;
+        let name_length = metadata
+            .get(name_start..)?
+            .iter()
+            .position(|&byte| byte == 0)?;
+        let name = &metadata[name_start..name_start + name_length];
+        if name == b"#~" || name == b"#-" {
+            return metadata.get(stream_offset..stream_offset.checked_add(stream_size)?);
+        }
+        // The name and its terminating zero are padded to four bytes
+        offset = name_start + (name_length + 4) / 4 * 4;
+    }
+    None
+}
+
+/// Returns the size in bytes of a column, which depends on the sizes of the
+/// heaps and tables it indexes.
+fn column_size(column: ColumnType, heap_sizes: u8, rows: &[usize; 64]) -> usize {
+    let heap_index = |flag: u8| if heap_sizes & flag != 0 { 4 } else { 2 };
+    match column {
+        Fixed(size) => size,
+        StringIndex => heap_index(WIDE_STRING_INDEX),
+        GuidIndex => heap_index(WIDE_GUID_INDEX),
+        BlobIndex => heap_index(WIDE_BLOB_INDEX),
+        TableIndex(table) => {
+            if rows[table] < 1 << 16 {
+                2
+            } else {
+                4
+            }
+        }
+        Coded(tag_bits, tables) => {
+            let largest = tables.iter().map(|&table| rows[table]).max().unwrap_or(0);
+            if largest < 1 << (16 - tag_bits) { 2 } else { 4 }
+        }
+    }
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> Option<u16> {
+    let bytes = bytes.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_le_bytes(bytes.try_into().ok()?))
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Option<u32> {
+    let bytes = bytes.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+fn u64_at(bytes: &[u8], offset: usize) -> Option<u64> {
+    let bytes = bytes.get(offset..offset.checked_add(8)?)?;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Builds metadata with a `#~` stream holding one row in each of the given
+    /// tables, with all indexes two bytes wide, and an `Assembly` row with the
+    /// given version if set.
+    pub(crate) fn test_metadata(tables: &[usize], version: Option<[u16; 4]>) -> Vec<u8> {
+        let mut present: u64 = tables.iter().map(|&table| 1 << table).sum();
+        if version.is_some() {
+            present |= 1 << ASSEMBLY_TABLE;
+        }
+
+        let mut stream = vec![0u8; 24];
+        stream[4] = 2; // MajorVersion
+        stream[8..16].copy_from_slice(&present.to_le_bytes());
+        for _ in 0..present.count_ones() {
+            stream.extend(1u32.to_le_bytes());
+        }
+        let rows: [usize; 64] = std::array::from_fn(|table| (present >> table & 1) as usize);
+        for &table in tables {
+            let size: usize = TABLE_COLUMNS[table]
+                .iter()
+                .map(|&column| column_size(column, 0, &rows))
+                .sum();
+            // Filler that would be read as a version if the size were wrong
+            stream.extend(std::iter::repeat_n(0xEE, size));
+        }
+        if let Some(version) = version {
+            stream.extend(0x8004u32.to_le_bytes()); // HashAlgId: SHA-1
+            for part in version {
+                stream.extend(part.to_le_bytes());
+            }
+            stream.extend([0u8; 4 + 2 + 2 + 2]); // Flags, PublicKey, Name, Culture
+        }
+
+        let mut metadata = Vec::new();
+        metadata.extend(METADATA_SIGNATURE.to_le_bytes());
+        metadata.extend([1, 0, 1, 0, 0, 0, 0, 0]); // Version 1.1, reserved
+        metadata.extend(12u32.to_le_bytes());
+        metadata.extend(b"v4.0.30319\0\0");
+        metadata.extend([0, 0, 2, 0]); // Flags, two streams
+        // A stream before the tables, to exercise the name padding
+        let streams_end = metadata.len() + 20 + 12;
+        metadata.extend((streams_end as u32).to_le_bytes());
+        metadata.extend(4u32.to_le_bytes());
+        metadata.extend(b"#Strings\0\0\0\0");
+        metadata.extend(((streams_end + 4) as u32).to_le_bytes());
+        metadata.extend((stream.len() as u32).to_le_bytes());
+        metadata.extend(b"#~\0\0");
+        assert_eq!(metadata.len(), streams_end);
+        metadata.extend([0u8; 4]);
+        metadata.extend(stream);
+        metadata
+    }
+
+    #[test]
+    fn test_assembly_version() {
+        let metadata = test_metadata(&[0x00], Some([1, 2, 3, 4]));
+        assert_eq!(
+            assembly_version(&metadata),
+            Some("1.2.3.4".parse().unwrap())
+        );
+
+        // Rows of the tables in front are skipped by their computed size
+        let tables: Vec<usize> = (0..ASSEMBLY_TABLE).collect();
+        let metadata = test_metadata(&tables, Some([10, 0, 65535, 7]));
+        assert_eq!(
+            assembly_version(&metadata),
+            Some("10.0.65535.7".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_assembly_version_missing() {
+        // A netmodule has metadata but no Assembly table
+        assert_eq!(assembly_version(&test_metadata(&[0x00], None)), None);
+
+        let mut metadata = test_metadata(&[0x00], Some([1, 2, 3, 4]));
+        metadata[0] = b'X';
+        assert_eq!(assembly_version(&metadata), None);
+
+        // Truncated metadata is not an error
+        let metadata = test_metadata(&[0x00, 0x02], Some([1, 2, 3, 4]));
+        for length in [0, 20, metadata.len() - 1] {
+            assert_eq!(assembly_version(&metadata[..length]), None);
+        }
+    }
+
+    #[test]
+    fn test_column_sizes() {
+        let mut rows = [0usize; 64];
+        assert_eq!(column_size(StringIndex, 0, &rows), 2);
+        assert_eq!(column_size(StringIndex, WIDE_STRING_INDEX, &rows), 4);
+        assert_eq!(column_size(BlobIndex, WIDE_STRING_INDEX, &rows), 2);
+
+        // Coded indexes widen once a table outgrows the bits left by the tag
+        rows[0x02] = (1 << 14) - 1;
+        assert_eq!(column_size(TYPE_DEF_OR_REF, 0, &rows), 2);
+        rows[0x02] = 1 << 14;
+        assert_eq!(column_size(TYPE_DEF_OR_REF, 0, &rows), 4);
+        assert_eq!(column_size(TableIndex(0x02), 0, &rows), 2);
+        rows[0x02] = 1 << 16;
+        assert_eq!(column_size(TableIndex(0x02), 0, &rows), 4);
+    }
+}
@@ -4,10 +4,25 @@
 //! matching a pattern, collecting file information with optional version extraction,
 //! filtering by version constraints, and sorting results.
 
-use crate::file_version::FileVersion;
-use crate::version_reader::read_file_version;
-use glob::Pattern;
-use std::path::{Path, PathBuf};
+use crate::file_version::{FileVersion, SemanticVersion, VersionPattern};
+use crate::open_limiter::OpenFileLimiter;
+use crate::progress::ScanProgress;
+use crate::streams::alternate_streams;
+use crate::version_reader::{
+    DEFAULT_READ_BUFFER_SIZE, PeArch, VersionCache, VersionStrings, read_all_version_fields,
+    read_assembly_version, read_file_version, read_msi_product_version, read_pe_arch,
+    read_pe_signed, read_pe_timestamp, read_version_strings, verify_pe_checksum_with_buffer,
+};
+use clap::ValueEnum;
+use glob::{MatchOptions, Pattern};
+use rayon::prelude::*;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fs::FileType;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 use walkdir::WalkDir;
 
 /// Information about a file including its path and optional version.
@@ -21,12 +36,276 @@ use walkdir::WalkDir;
 /// let info = FileInfo {
 ///     path: PathBuf::from("test.dll"),
 ///     version: None,
+///     ..Default::default()
 /// };
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct FileInfo {
     pub path: PathBuf,
     pub version: Option<FileVersion>,
+    /// Product version, `None` if not read or the file has no version information
+    pub product_version: Option<FileVersion>,
+    /// `AssemblyVersion` of a managed .NET assembly, `None` if not read or not
+    /// a managed assembly
+    pub assembly_version: Option<FileVersion>,
+    /// `ProductVersion` string parsed as a semantic version such as `1.2.3-beta`,
+    /// `None` if not read or the string is not a semantic version
+    pub semantic_version: Option<SemanticVersion>,
+    /// Company, product name and other version strings, `None` if not read or
+    /// the file has no version string table
+    pub version_strings: Option<VersionStrings>,
+    /// Whether the PE checksum matches the file contents, `None` if not verified,
+    /// not a PE file, or no checksum is set
+    pub checksum_ok: Option<bool>,
+    /// Names of the file's alternate data streams, only listed on Windows
+    pub streams: Vec<String>,
+    /// Whether the entry is a directory, only detected with
+    /// [`CollectOptions::include_dirs`]
+    pub is_dir: bool,
+    /// Creation time, `None` if not read or not supported by the platform or filesystem
+    pub created: Option<SystemTime>,
+    /// Size in bytes, `None` if not read or the metadata could not be read
+    pub size: Option<u64>,
+    /// Last modification time, `None` if not read or the metadata could not be read
+    pub modified: Option<SystemTime>,
+    /// Link time from the PE header, `None` if not read or not a PE file. May be
+    /// zero or a hash for reproducible builds, see [`read_pe_timestamp`]
+    pub pe_timestamp: Option<SystemTime>,
+    /// Target architecture from the PE header, `None` if not read or not a PE file
+    pub arch: Option<PeArch>,
+    /// Whether the PE file has an embedded signature (certificate table), `None`
+    /// if not read or not a PE file. The signature is not validated.
+    pub signed: Option<bool>,
+    /// BLAKE3 hash of the contents as hex, `None` if not computed or the file
+    /// could not be read
+    pub hash: Option<String>,
+    /// Search directory the file was found in, set when several directories
+    /// are searched in one run
+    pub root: Option<PathBuf>,
+}
+
+/// Options controlling which information [`collect_file_info_with_options`] gathers.
+///
+/// # Examples
+///
+/// ```
+/// use flist::file_lister::CollectOptions;
+///
+/// let options = CollectOptions {
+///     include_version: true,
+///     ..Default::default()
+/// };
+/// assert!(!options.verify_checksum);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CollectOptions {
+    /// Whether to extract version information from files
+    pub include_version: bool,
+    /// Whether to extract the product version from files
+    pub include_product_version: bool,
+    /// Whether to read the `AssemblyVersion` of managed .NET assemblies
+    pub include_assembly_version: bool,
+    /// Whether to read the `ProductVersion` string as a semantic version
+    pub read_semantic_version: bool,
+    /// Whether to read version strings such as the company name from files
+    pub read_strings: bool,
+    /// Language of the version strings to prefer, `None` for the first language
+    pub strings_language: Option<u16>,
+    /// Whether to verify the checksum stored in PE files
+    pub verify_checksum: bool,
+    /// Maximum number of files open at the same time, `None` for no limit
+    pub max_open_files: Option<usize>,
+    /// Whether to list alternate data streams (Windows only)
+    pub list_streams: bool,
+    /// Size of the buffer for reading file contents that are not memory mapped,
+    /// `None` for [`DEFAULT_READ_BUFFER_SIZE`]
+    pub read_buffer_size: Option<usize>,
+    /// Whether the paths may include directories. Directories are marked with
+    /// [`FileInfo::is_dir`] and no information is read from them.
+    pub include_dirs: bool,
+    /// Whether to read the creation time of files
+    pub read_created: bool,
+    /// Whether to read the size of files
+    pub read_size: bool,
+    /// Whether to read the last modification time of files
+    pub read_modified: bool,
+    /// Whether to read the link time from the PE header of files
+    pub read_pe_timestamp: bool,
+    /// Whether to read the target architecture from the PE header of files
+    pub read_arch: bool,
+    /// Whether to check PE files for an embedded signature
+    pub read_signed: bool,
+    /// Whether to hash the contents of files, to find duplicates
+    pub compute_hash: bool,
+    /// Number of threads reading files in parallel, `None` for one per core
+    pub threads: Option<usize>,
+    /// Counters to record collected files in, for progress reporting
+    pub progress: Option<Arc<ScanProgress>>,
+    /// Cache of file versions, so hard links of a file are parsed only once
+    pub version_cache: Option<Arc<VersionCache>>,
+}
+
+/// Matches file paths against search patterns.
+///
+/// This is the matching used during enumeration: the file name of a path is
+/// matched against glob patterns, case-sensitively unless
+/// [`ignore_case`](NameMatcher::ignore_case) is set, and a path matches if any
+/// pattern matches. With [`full_path`](NameMatcher::full_path) the path
+/// relative to a root is matched instead.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+/// use flist::file_lister::NameMatcher;
+///
+/// let matcher = NameMatcher::new(&["*.dll", "app?.exe"]).unwrap();
+/// assert!(matcher.matches_path(Path::new("bin/core.dll")));
+/// assert!(matcher.matches_path(Path::new("app1.exe")));
+/// assert!(!matcher.matches_path(Path::new("readme.txt")));
+/// ```
+#[derive(Debug, Clone)]
+pub struct NameMatcher {
+    patterns: Vec<Pattern>,
+    case_sensitive: bool,
+    root: Option<PathBuf>,
+}
+
+impl NameMatcher {
+    /// Creates a matcher for the given glob patterns.
+    ///
+    /// Braces are expanded first, see [`expand_pattern`].
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns` - Glob patterns to match file names against
+    ///
+    /// # Returns
+    ///
+    /// The matcher, or an error if one of the patterns is invalid.
+    pub fn new(patterns: &[&str]) -> Result<Self, anyhow::Error> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| expand_pattern(pattern))
+            .collect::<Result<Vec<_>, _>>()?
+            .concat();
+
+        Ok(Self {
+            patterns,
+            case_sensitive: true,
+            root: None,
+        })
+    }
+
+    /// Sets whether letter case is ignored when matching.
+    ///
+    /// # Arguments
+    ///
+    /// * `ignore_case` - `true` to let `*.DLL` match `core.dll`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::Path;
+    /// use flist::file_lister::NameMatcher;
+    ///
+    /// let matcher = NameMatcher::new(&["*.DLL"]).unwrap().ignore_case(true);
+    /// assert!(matcher.matches_path(Path::new("core.dll")));
+    /// ```
+    pub fn ignore_case(mut self, ignore_case: bool) -> Self {
+        self.case_sensitive = !ignore_case;
+        self
+    }
+
+    /// Matches patterns against the path relative to `root` instead of the file name.
+    ///
+    /// Path separators are normalized to `/`, so patterns work the same on all
+    /// platforms. `*` and `?` do not match `/`, while a `**` component matches
+    /// any number of directories, including none. With
+    /// [`ignore_case`](NameMatcher::ignore_case), case is ignored in the whole
+    /// relative path. Paths outside `root` are matched as they are.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The directory paths are made relative to, usually the search directory
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::Path;
+    /// use flist::file_lister::NameMatcher;
+    ///
+    /// let matcher = NameMatcher::new(&["src/**/*.rs"]).unwrap().full_path(Path::new("/repo"));
+    /// assert!(matcher.matches_path(Path::new("/repo/src/main.rs")));
+    /// assert!(matcher.matches_path(Path::new("/repo/src/cli/args.rs")));
+    /// assert!(!matcher.matches_path(Path::new("/repo/tests/src/main.rs")));
+    /// ```
+    pub fn full_path(mut self, root: &Path) -> Self {
+        self.root = Some(root.to_path_buf());
+        self
+    }
+
+    /// Returns the part of `path` that patterns are matched against.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of a file
+    ///
+    /// # Returns
+    ///
+    /// The file name, or with [`full_path`](NameMatcher::full_path) the relative
+    /// path separated by `/`. `None` if the path has no file name or it is not
+    /// valid Unicode.
+    pub fn match_subject<'a>(&self, path: &'a Path) -> Option<Cow<'a, str>> {
+        let Some(root) = &self.root else {
+            return path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map(Cow::from);
+        };
+
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        let parts = relative
+            .components()
+            .map(|component| component.as_os_str().to_str())
+            .collect::<Option<Vec<_>>>()?;
+        match parts.as_slice() {
+            [] => None,
+            [name] => Some(Cow::from(*name)),
+            _ => Some(Cow::from(parts.join("/"))),
+        }
+    }
+
+    /// Returns whether `path` matches any of the patterns.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of a file
+    pub fn matches_path(&self, path: &Path) -> bool {
+        let options = MatchOptions {
+            case_sensitive: self.case_sensitive,
+            require_literal_separator: self.root.is_some(),
+            ..MatchOptions::new()
+        };
+        self.match_subject(path)
+            .map(|name| self.patterns.iter().any(|p| p.matches_with(&name, options)))
+            .unwrap_or(false)
+    }
+
+    /// Describes how patterns are matched, e.g. `glob on file name, case-sensitive`.
+    pub fn describe_mode(&self) -> String {
+        let subject = if self.root.is_some() {
+            "relative path"
+        } else {
+            "file name"
+        };
+        let case = if self.case_sensitive {
+            "case-sensitive"
+        } else {
+            "case-insensitive"
+        };
+        format!("glob on {}, {}", subject, case)
+    }
 }
 
 /// Recursively enumerates files matching the search pattern.
@@ -56,227 +335,4321 @@ pub struct FileInfo {
 /// }
 /// ```
 pub fn enumerate_files(directory: &Path, pattern: &str) -> Result<Vec<PathBuf>, anyhow::Error> {
-    let glob_pattern = Pattern::new(pattern)?;
+    enumerate_files_with_patterns(directory, &[pattern])
+}
 
-    let files: Vec<PathBuf> = WalkDir::new(directory)
-        .into_iter()
-        .filter_map(|e| e.ok()) // Skip entries with errors (permission denied, etc.)
-        .filter(|e| e.file_type().is_file()) // Only files, not directories
-        .filter(|e| {
-            // Match file name against glob pattern
-            e.file_name()
-                .to_str()
-                .map(|name| glob_pattern.matches(name))
-                .unwrap_or(false)
-        })
-        .map(|e| e.path().to_path_buf())
-        .collect();
+/// Recursively enumerates files matching any of several search patterns.
+///
+/// Works like [`enumerate_files`], but a file is included if its name matches
+/// at least one of the glob patterns. Each file is reported only once, even if
+/// it matches several patterns.
+///
+/// # Arguments
+///
+/// * `directory` - Root directory to start searching from
+/// * `patterns` - Glob patterns to match file names against
+///
+/// # Returns
+///
+/// A vector of paths to files matching any pattern, or an error if one of the
+/// patterns is invalid.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use flist::file_lister::enumerate_files_with_patterns;
+///
+/// let files = enumerate_files_with_patterns(Path::new("."), &["*.dll", "*.exe"]).unwrap();
+/// println!("Found {} binaries", files.len());
+/// ```
+pub fn enumerate_files_with_patterns(
+    directory: &Path,
+    patterns: &[&str],
+) -> Result<Vec<PathBuf>, anyhow::Error> {
+    enumerate_files_with_options(directory, patterns, &EnumerateOptions::default())
+}
 
-    Ok(files)
+/// Options controlling which entries [`enumerate_files_with_options`] returns.
+///
+/// # Examples
+///
+/// ```
+/// use flist::file_lister::EnumerateOptions;
+///
+/// let options = EnumerateOptions { include_dirs: true, ..Default::default() };
+/// assert!(options.progress.is_none());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EnumerateOptions {
+    /// Whether directories whose name matches a pattern are returned as well.
+    /// The search directory itself is never returned.
+    pub include_dirs: bool,
+    /// How many directory levels to descend. `Some(1)` returns only entries
+    /// directly inside the search directory, as does `Some(0)` since the search
+    /// directory itself is never returned. `None` searches the whole tree.
+    pub max_depth: Option<usize>,
+    /// Glob patterns of names to skip. A matching directory is not searched,
+    /// so everything below it is skipped as well.
+    pub exclude: Vec<String>,
+    /// Whether letter case is ignored when matching patterns and excludes
+    pub ignore_case: bool,
+    /// Whether to skip entries ignored by `.gitignore` and `.ignore` files,
+    /// including those in subdirectories, and by the global git excludes
+    pub use_gitignore: bool,
+    /// Whether patterns are matched against the path relative to the search
+    /// directory instead of the file name, see [`NameMatcher::full_path`].
+    /// Excludes are always matched against names.
+    pub full_path: bool,
+    /// Whether to add the patterns of a [`FLISTIGNORE_FILE`] in the search
+    /// directory to [`EnumerateOptions::exclude`]
+    pub use_flistignore: bool,
+    /// Whether to follow symbolic links to files and directories. A link to a
+    /// directory that contains it is not followed, see [`WalkError::is_loop`].
+    pub follow_symlinks: bool,
+    /// Counters to record found entries in, for progress reporting
+    pub progress: Option<Arc<ScanProgress>>,
+    /// Where to record entries that could not be read, such as directories
+    /// without permission. Without it, such entries are skipped silently.
+    pub errors: Option<Arc<WalkErrors>>,
 }
 
-/// Collects file information with optional version reading.
+/// An entry that could not be read while searching a directory tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalkError {
+    /// The entry that could not be read, if known
+    pub path: Option<PathBuf>,
+    /// Kind of the I/O error, `None` for other errors such as a symlink loop
+    pub kind: Option<std::io::ErrorKind>,
+    /// Description of the error
+    pub message: String,
+    /// Whether the entry is a symbolic link to one of its own ancestors, which
+    /// was skipped so the walk terminates
+    pub is_loop: bool,
+}
+
+impl WalkError {
+    fn from_walkdir(error: &walkdir::Error) -> Self {
+        let io_error = error.io_error();
+        let message = match (io_error, error.loop_ancestor()) {
+            (Some(io_error), _) => io_error.to_string(),
+            (None, Some(ancestor)) => Self::loop_message(ancestor),
+            (None, None) => error.to_string(),
+        };
+        Self {
+            path: error.path().map(Path::to_path_buf),
+            kind: io_error.map(std::io::Error::kind),
+            message,
+            is_loop: error.loop_ancestor().is_some(),
+        }
+    }
+
+    fn from_ignore(error: &ignore::Error) -> Self {
+        // The path is wrapped around the underlying error
+        fn path_of(error: &ignore::Error) -> Option<&Path> {
+            match error {
+                ignore::Error::WithPath { path, .. } => Some(path),
+                ignore::Error::WithDepth { err, .. }
+                | ignore::Error::WithLineNumber { err, .. } => path_of(err),
+                ignore::Error::Loop { child, .. } => Some(child),
+                _ => None,
+            }
+        }
+        fn loop_ancestor(error: &ignore::Error) -> Option<&Path> {
+            match error {
+                ignore::Error::Loop { ancestor, .. } => Some(ancestor),
+                ignore::Error::WithPath { err, .. }
+                | ignore::Error::WithDepth { err, .. }
+                | ignore::Error::WithLineNumber { err, .. } => loop_ancestor(err),
+                _ => None,
+            }
+        }
+
+        let io_error = error.io_error();
+        let message = match (io_error, loop_ancestor(error)) {
+            (Some(io_error), _) => io_error.to_string(),
+            (None, Some(ancestor)) => Self::loop_message(ancestor),
+            (None, None) => error.to_string(),
+        };
+        Self {
+            path: path_of(error).map(Path::to_path_buf),
+            kind: io_error.map(std::io::Error::kind),
+            message,
+            is_loop: loop_ancestor(error).is_some(),
+        }
+    }
+
+    fn loop_message(ancestor: &Path) -> String {
+        format!(
+            "symbolic link loop to ancestor {}, skipped",
+            ancestor.display()
+        )
+    }
+}
+
+impl std::fmt::Display for WalkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{}: {}", path.display(), self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Errors recorded while searching, shared with the walk through
+/// [`EnumerateOptions::errors`].
 ///
-/// Takes a list of file paths and creates `FileInfo` structures, optionally
-/// extracting version information from each file.
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+/// use std::sync::Arc;
+/// use flist::file_lister::{enumerate_files_with_options, EnumerateOptions, WalkErrors};
+///
+/// let errors = Arc::new(WalkErrors::default());
+/// let options = EnumerateOptions { errors: Some(Arc::clone(&errors)), ..Default::default() };
+/// let files = enumerate_files_with_options(Path::new("no/such/dir"), &["*"], &options).unwrap();
+/// assert!(files.is_empty());
+/// assert_eq!(errors.take().len(), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct WalkErrors {
+    errors: Mutex<Vec<WalkError>>,
+}
+
+impl WalkErrors {
+    /// Records an error.
+    pub fn push(&self, error: WalkError) {
+        self.lock().push(error);
+    }
+
+    /// Removes and returns the errors recorded so far, in the order they occurred.
+    pub fn take(&self) -> Vec<WalkError> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    /// Returns the number of errors recorded so far.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns whether no errors were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<WalkError>> {
+        // The list stays consistent even if a thread panicked while holding the lock
+        self.errors
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Recursively enumerates entries matching any of several search patterns,
+/// using the given options.
+///
+/// Entries that cannot be read are skipped; use
+/// [`enumerate_files_with_errors`] to get them as well.
 ///
 /// # Arguments
 ///
-/// * `files` - Vector of file paths to process
-/// * `include_version` - Whether to extract version information from files
+/// * `directory` - Root directory to start searching from
+/// * `patterns` - Glob patterns to match names against
+/// * `options` - Which kinds of entries to return
 ///
 /// # Returns
 ///
-/// A vector of `FileInfo` structures containing paths and optional versions.
+/// A vector of matching paths, or an error if one of the patterns is invalid.
 ///
 /// # Examples
 ///
 /// ```no_run
-/// use std::path::PathBuf;
-/// use flist::file_lister::collect_file_info;
+/// use std::path::Path;
+/// use flist::file_lister::{enumerate_files_with_options, EnumerateOptions};
 ///
-/// let files = vec![PathBuf::from("test.dll")];
-/// let info = collect_file_info(files, false);
+/// let options = EnumerateOptions { include_dirs: true, ..Default::default() };
+/// let entries = enumerate_files_with_options(Path::new("."), &["*cache*"], &options).unwrap();
+/// println!("Found {} cache files and directories", entries.len());
 /// ```
-pub fn collect_file_info(files: Vec<PathBuf>, include_version: bool) -> Vec<FileInfo> {
-    files
-        .into_iter()
-        .map(|path| {
-            let version = if include_version {
-                // Try to read version, but don't fail if it's not available
-                read_file_version(&path).ok().flatten()
-            } else {
-                None
-            };
-            FileInfo { path, version }
-        })
-        .collect()
+pub fn enumerate_files_with_options(
+    directory: &Path,
+    patterns: &[&str],
+    options: &EnumerateOptions,
+) -> Result<Vec<PathBuf>, anyhow::Error> {
+    Ok(enumerate_files_iter_with_options(directory, patterns, options)?.collect())
 }
 
-/// Filters files by version constraints.
+/// Recursively enumerates entries like [`enumerate_files_with_options`], and
+/// returns the entries that could not be read as well.
 ///
-/// Keeps only files whose versions fall within the specified range.
-/// Files without version information are excluded.
+/// A directory without read permission, for example, is reported as an error
+/// while the matches found elsewhere are still returned.
 ///
 /// # Arguments
 ///
-/// * `files` - Vector of file information to filter
-/// * `min_version` - Minimum version (inclusive), or None for no minimum
-/// * `max_version` - Maximum version (inclusive), or None for no maximum
+/// * `directory` - Root directory to start searching from
+/// * `patterns` - Glob patterns to match names against
+/// * `options` - Which kinds of entries to return; [`EnumerateOptions::errors`]
+///   is not used, the errors are returned instead
 ///
 /// # Returns
 ///
-/// A filtered vector containing only files matching the version constraints.
+/// The matching paths and the errors of the walk, or an error if one of the
+/// patterns is invalid.
 ///
 /// # Examples
 ///
-/// ```
-/// use std::path::PathBuf;
-/// use std::str::FromStr;
-/// use flist::file_lister::{FileInfo, filter_by_version};
-/// use flist::file_version::FileVersion;
+/// ```no_run
+/// use std::path::Path;
+/// use flist::file_lister::{enumerate_files_with_errors, EnumerateOptions};
 ///
-/// let files = vec![
-///     FileInfo {
-///         path: PathBuf::from("test.dll"),
-///         version: Some(FileVersion::from_str("1.5.0.0").unwrap()),
-///     },
-/// ];
-/// let min = Some(FileVersion::from_str("1.0.0.0").unwrap());
-/// let max = Some(FileVersion::from_str("2.0.0.0").unwrap());
-/// let filtered = filter_by_version(files, min, max);
-/// assert_eq!(filtered.len(), 1);
+/// let (files, errors) =
+///     enumerate_files_with_errors(Path::new("C:\\"), &["*.dll"], &EnumerateOptions::default())
+///         .unwrap();
+/// println!("Found {} files, could not read {} entries", files.len(), errors.len());
 /// ```
-pub fn filter_by_version(
-    files: Vec<FileInfo>,
-    min_version: Option<FileVersion>,
-    max_version: Option<FileVersion>,
-) -> Vec<FileInfo> {
-    files
-        .into_iter()
-        .filter(|file_info| {
-            if let Some(version) = file_info.version {
-                let min_ok = min_version.is_none_or(|min| version >= min);
-                let max_ok = max_version.is_none_or(|max| version <= max);
-                min_ok && max_ok
-            } else {
-                // Files without version info are excluded when filtering by version
-                false
-            }
-        })
-        .collect()
+pub fn enumerate_files_with_errors(
+    directory: &Path,
+    patterns: &[&str],
+    options: &EnumerateOptions,
+) -> Result<(Vec<PathBuf>, Vec<WalkError>), anyhow::Error> {
+    let errors = Arc::new(WalkErrors::default());
+    let options = EnumerateOptions {
+        errors: Some(Arc::clone(&errors)),
+        ..options.clone()
+    };
+    let files = enumerate_files_with_options(directory, patterns, &options)?;
+
+    Ok((files, errors.take()))
 }
 
-/// Sorts files by path in ascending order.
+/// Lazily enumerates files matching the search pattern.
+///
+/// Works like [`enumerate_files`], but yields each file as the walk finds it
+/// instead of collecting all paths first, so memory use does not grow with the
+/// size of the tree and the first results are available immediately.
 ///
 /// # Arguments
 ///
-/// * `files` - Vector of file information to sort
+/// * `directory` - Root directory to start searching from
+/// * `pattern` - Glob pattern to match file names against
 ///
 /// # Returns
 ///
-/// The same vector sorted by file path.
+/// An iterator over the matching file paths in the order the filesystem returns
+/// them, or an error if the pattern is invalid.
 ///
 /// # Examples
 ///
-/// ```
-/// use std::path::PathBuf;
-/// use flist::file_lister::{FileInfo, sort_by_path};
+/// ```no_run
+/// use std::path::Path;
+/// use flist::file_lister::enumerate_files_iter;
 ///
-/// let mut files = vec![
-///     FileInfo { path: PathBuf::from("z.dll"), version: None },
-///     FileInfo { path: PathBuf::from("a.dll"), version: None },
-/// ];
-/// let sorted = sort_by_path(files);
-/// assert_eq!(sorted[0].path, PathBuf::from("a.dll"));
+/// for file in enumerate_files_iter(Path::new("D:\\"), "*.dll").unwrap() {
+///     println!("{}", file.display());
+/// }
 /// ```
-pub fn sort_by_path(mut files: Vec<FileInfo>) -> Vec<FileInfo> {
-    files.sort_by(|a, b| a.path.cmp(&b.path));
-    files
+pub fn enumerate_files_iter(
+    directory: &Path,
+    pattern: &str,
+) -> Result<impl Iterator<Item = PathBuf> + use<>, anyhow::Error> {
+    enumerate_files_iter_with_options(directory, &[pattern], &EnumerateOptions::default())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
-
-    #[test]
-    fn test_enumerate_files_with_pattern() {
-        // Create a temporary directory with test files
-        let temp_dir = std::env::temp_dir().join("flist_test_enum");
-        let _ = fs::remove_dir_all(&temp_dir); // Clean up if exists
-        fs::create_dir_all(&temp_dir).unwrap();
-
-        // Create test files
-        fs::File::create(temp_dir.join("test1.txt")).unwrap();
-        fs::File::create(temp_dir.join("test2.txt")).unwrap();
-        fs::File::create(temp_dir.join("test.dll")).unwrap();
-        fs::File::create(temp_dir.join("other.exe")).unwrap();
+/// Lazily enumerates entries matching any of several search patterns, using the
+/// given options.
+///
+/// This is the lazy form of [`enumerate_files_with_options`].
+///
+/// # Arguments
+///
+/// * `directory` - Root directory to start searching from
+/// * `patterns` - Glob patterns to match names against
+/// * `options` - Which kinds of entries to return
+///
+/// # Returns
+///
+/// An iterator over the matching paths, or an error if one of the patterns is
+/// invalid.
+pub fn enumerate_files_iter_with_options(
+    directory: &Path,
+    patterns: &[&str],
+    options: &EnumerateOptions,
+) -> Result<impl Iterator<Item = PathBuf> + use<>, anyhow::Error> {
+    let mut matcher = NameMatcher::new(patterns)?.ignore_case(options.ignore_case);
+    if options.full_path {
+        matcher = matcher.full_path(directory);
+    }
+    let flistignore = if options.use_flistignore {
+        read_flistignore(directory)?
+    } else {
+        Vec::new()
+    };
+    let exclude: Vec<&str> = options
+        .exclude
+        .iter()
+        .chain(&flistignore)
+        .map(String::as_str)
+        .collect();
+    let exclude = NameMatcher::new(&exclude)?.ignore_case(options.ignore_case);
 
-        // Test pattern matching
-        let files = enumerate_files(&temp_dir, "*.txt").unwrap();
-        assert_eq!(files.len(), 2);
+    let entries: Box<dyn Iterator<Item = (PathBuf, FileType)>> = if options.use_gitignore {
+        Box::new(walk_respecting_ignore_files(directory, options, exclude))
+    } else {
+        Box::new(walk_all(directory, options, exclude))
+    };
 
-        let files = enumerate_files(&temp_dir, "*.dll").unwrap();
-        assert_eq!(files.len(), 1);
+    let include_dirs = options.include_dirs;
+    let progress = options.progress.clone();
+    let seen = options.progress.clone();
+    let files = entries
+        .inspect(move |_| {
+            if let Some(progress) = &seen {
+                progress.add_seen();
+            }
+        })
+        .filter(move |(_, file_type)| {
+            // Only files, and directories if requested
+            file_type.is_file() || (include_dirs && file_type.is_dir())
+        })
+        .filter(move |(path, _)| matcher.matches_path(path))
+        .inspect(move |_| {
+            if let Some(progress) = &progress {
+                progress.add_matched(1);
+            }
+        })
+        .map(|(path, _)| path);
 
-        let files = enumerate_files(&temp_dir, "*").unwrap();
-        assert_eq!(files.len(), 4);
+    Ok(files)
+}
 
-        // Clean up
-        fs::remove_dir_all(&temp_dir).unwrap();
+/// Walks every entry below `directory`, except excluded ones.
+fn walk_all(
+    directory: &Path,
+    options: &EnumerateOptions,
+    exclude: NameMatcher,
+) -> impl Iterator<Item = (PathBuf, FileType)> + use<> {
+    let mut walker = WalkDir::new(directory)
+        .min_depth(1) // Never the search directory itself
+        .follow_links(options.follow_symlinks); // Detects loops to ancestors
+    if let Some(max_depth) = options.max_depth {
+        walker = walker.max_depth(max_depth);
     }
 
-    #[test]
-    fn test_collect_file_info_without_version() {
-        let paths = vec![PathBuf::from("test1.txt"), PathBuf::from("test2.txt")];
-
-        let file_infos = collect_file_info(paths, false);
-        assert_eq!(file_infos.len(), 2);
-        assert!(file_infos[0].version.is_none());
-        assert!(file_infos[1].version.is_none());
-    }
+    let errors = options.errors.clone();
+    walker
+        .into_iter()
+        // Prune excluded directories instead of filtering their contents
+        .filter_entry(move |e| e.depth() == 0 || !exclude.matches_path(e.path()))
+        // Skip entries with errors (permission denied, etc.), recording them if requested
+        .filter_map(move |e| {
+            e.map_err(|error| {
+                if let Some(errors) = &errors {
+                    errors.push(WalkError::from_walkdir(&error));
+                }
+            })
+            .ok()
+        })
+        .map(|e| (e.path().to_path_buf(), e.file_type()))
+}
 
-    #[test]
-    fn test_filter_by_version() {
-        let files = vec![
-            FileInfo {
-                path: PathBuf::from("file1.dll"),
-                version: Some("1.0.0.0".parse().unwrap()),
-            },
-            FileInfo {
-                path: PathBuf::from("file2.dll"),
-                version: Some("2.0.0.0".parse().unwrap()),
-            },
-            FileInfo {
-                path: PathBuf::from("file3.dll"),
-                version: Some("3.0.0.0".parse().unwrap()),
-            },
-            FileInfo {
-                path: PathBuf::from("file4.dll"),
-                version: None,
-            },
-        ];
+/// Walks the entries below `directory` that are not ignored by `.gitignore`,
+/// `.ignore` or the global git excludes, except excluded ones.
+fn walk_respecting_ignore_files(
+    directory: &Path,
+    options: &EnumerateOptions,
+    exclude: NameMatcher,
+) -> impl Iterator<Item = (PathBuf, FileType)> + use<> {
+    let errors = options.errors.clone();
+    ignore::WalkBuilder::new(directory)
+        // Like the plain walk, depth 0 means the search directory's own entries
+        .max_depth(options.max_depth.map(|depth| depth.max(1)))
+        // Only skip what the ignore files say, not hidden files
+        .hidden(false)
+        // Detects loops to ancestors when following links
+        .follow_links(options.follow_symlinks)
+        // Honor .gitignore files outside of git repositories as well
+        .require_git(false)
+        // Prune excluded directories instead of filtering their contents
+        .filter_entry(move |e| e.depth() == 0 || !exclude.matches_path(e.path()))
+        .build()
+        // Skip entries with errors (permission denied, etc.), recording them if requested
+        .filter_map(move |e| {
+            e.map_err(|error| {
+                if let Some(errors) = &errors {
+                    errors.push(WalkError::from_ignore(&error));
+                }
+            })
+            .ok()
+        })
+        .filter(|e| e.depth() > 0) // Never the search directory itself
+        .filter_map(|e| Some((e.path().to_path_buf(), e.file_type()?)))
+}
 
-        // Filter with min version
-        let filtered = filter_by_version(files.clone(), Some("2.0.0.0".parse().unwrap()), None);
-        assert_eq!(filtered.len(), 2); // 2.0.0.0 and 3.0.0.0
+/// Enumerates files matching a search pattern, descending at most `max_depth`
+/// directory levels.
+///
+/// # Arguments
+///
+/// * `directory` - Root directory to start searching from
+/// * `pattern` - Glob pattern to match file names against
+/// * `max_depth` - Levels to descend, `Some(1)` for only the files directly
+///   inside `directory`, `None` for the whole tree
+///
+/// # Returns
+///
+/// A vector of matching file paths, or an error if the pattern is invalid.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use flist::file_lister::enumerate_files_with_depth;
+///
+/// // Only DLLs in the share itself, not in its subdirectories
+/// let files = enumerate_files_with_depth(Path::new(r"\\server\share"), "*.dll", Some(1)).unwrap();
+/// println!("Found {} files", files.len());
+/// ```
+pub fn enumerate_files_with_depth(
+    directory: &Path,
+    pattern: &str,
+    max_depth: Option<usize>,
+) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let options = EnumerateOptions {
+        max_depth,
+        ..Default::default()
+    };
+    enumerate_files_with_options(directory, &[pattern], &options)
+}
+
+/// Enumerates files matching a search pattern, skipping excluded names.
+///
+/// An entry is skipped if its name or the name of any directory between it and
+/// `directory` matches one of the exclude patterns.
+///
+/// # Arguments
+///
+/// * `directory` - Root directory to start searching from
+/// * `pattern` - Glob pattern to match file names against
+/// * `excludes` - Glob patterns of file and directory names to skip
+///
+/// # Returns
+///
+/// A vector of matching file paths, or an error if the pattern or one of the
+/// exclude patterns is invalid.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use flist::file_lister::enumerate_files_filtered;
+///
+/// let files =
+///     enumerate_files_filtered(Path::new("."), "*.js", &["node_modules", ".git"]).unwrap();
+/// println!("Found {} files", files.len());
+/// ```
+pub fn enumerate_files_filtered(
+    directory: &Path,
+    pattern: &str,
+    excludes: &[&str],
+) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let options = EnumerateOptions {
+        exclude: excludes.iter().map(|e| e.to_string()).collect(),
+        ..Default::default()
+    };
+    enumerate_files_with_options(directory, &[pattern], &options)
+}
+
+/// Compiles a glob pattern after expanding braces.
+///
+/// `{a,b}` is replaced by one pattern per comma-separated alternative, so
+/// `*.{dll,exe,sys}` becomes `*.dll`, `*.exe` and `*.sys`. Braces can be nested
+/// and alternatives can be empty: `file{,.bak}` is `file` and `file.bak`.
+/// Braces inside a character class such as `[{}]` are taken literally.
+///
+/// # Arguments
+///
+/// * `pattern` - The pattern to expand and compile
+///
+/// # Returns
+///
+/// The compiled patterns, or an error if a brace is not matched or a pattern
+/// is not a valid glob.
+///
+/// # Examples
+///
+/// ```
+/// use flist::file_lister::expand_pattern;
+///
+/// let patterns = expand_pattern("*.{dll,exe}").unwrap();
+/// let patterns: Vec<&str> = patterns.iter().map(|p| p.as_str()).collect();
+/// assert_eq!(patterns, ["*.dll", "*.exe"]);
+/// assert!(expand_pattern("*.{dll").is_err());
+/// ```
+pub fn expand_pattern(pattern: &str) -> Result<Vec<Pattern>, anyhow::Error> {
+    expand_braces(pattern)?
+        .iter()
+        .map(|expanded| Ok(Pattern::new(expanded)?))
+        .collect()
+}
+
+/// Expands the first top-level brace group of `pattern`, then the rest recursively.
+fn expand_braces(pattern: &str) -> Result<Vec<String>, anyhow::Error> {
+    let syntax_error = |position: usize, message: &str| {
+        anyhow::anyhow!(
+            "Pattern syntax error near position {}: {}",
+            position,
+            message
+        )
+    };
+
+    // Find the first brace group and the top-level commas in it
+    let mut open = None;
+    let mut commas = Vec::new();
+    let mut depth = 0;
+    let mut in_class = false;
+    for (index, c) in pattern.char_indices() {
+        match c {
+            '[' if !in_class => in_class = true,
+            ']' if in_class => in_class = false,
+            _ if in_class => {}
+            '{' => {
+                if depth == 0 {
+                    open = Some(index);
+                }
+                depth += 1;
+            }
+            '}' if depth == 0 => return Err(syntax_error(index, "unmatched '}'")),
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    let open = open.unwrap_or_default();
+                    let prefix = &pattern[..open];
+                    let suffix = &pattern[index + 1..];
+                    let bounds: Vec<usize> = std::iter::once(open)
+                        .chain(commas.iter().copied())
+                        .chain(std::iter::once(index))
+                        .collect();
+
+                    let mut expanded = Vec::new();
+                    for pair in bounds.windows(2) {
+                        let alternative = &pattern[pair[0] + 1..pair[1]];
+                        let combined = format!("{}{}{}", prefix, alternative, suffix);
+                        expanded.extend(expand_braces(&combined)?);
+                    }
+                    return Ok(expanded);
+                }
+            }
+            ',' if depth == 1 => commas.push(index),
+            _ => {}
+        }
+    }
+
+    match open {
+        Some(open) => Err(syntax_error(open, "unmatched '{'")),
+        None => Ok(vec![pattern.to_string()]),
+    }
+}
+
+/// Reads search patterns from a file.
+///
+/// The file contains one glob pattern per line. Leading and trailing whitespace
+/// is trimmed, and blank lines as well as lines starting with `#` are ignored.
+///
+/// # Arguments
+///
+/// * `path` - Path to the patterns file
+///
+/// # Returns
+///
+/// The patterns in file order, or an error if the file cannot be read.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use flist::file_lister::read_patterns_file;
+///
+/// let patterns = read_patterns_file(Path::new("patterns.txt")).unwrap();
+/// println!("Loaded {} patterns", patterns.len());
+/// ```
+pub fn read_patterns_file(path: &Path) -> Result<Vec<String>, anyhow::Error> {
+    let content = std::fs::read_to_string(path)?;
+
+    Ok(list_entries(&content))
+}
+
+/// Reads the directories to search from a file, or from stdin if `path` is `-`.
+///
+/// The list contains one directory per line. Leading and trailing whitespace is
+/// trimmed, and blank lines as well as lines starting with `#` are ignored.
+///
+/// # Arguments
+///
+/// * `path` - Path to the directory list, or `-` for stdin
+///
+/// # Returns
+///
+/// The directories in list order, or an error if the list cannot be read.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use flist::file_lister::read_directories_file;
+///
+/// let directories = read_directories_file(Path::new("dirs.txt")).unwrap();
+/// println!("Searching {} directories", directories.len());
+/// ```
+pub fn read_directories_file(path: &Path) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let content = if path == Path::new("-") {
+        std::io::read_to_string(std::io::stdin())?
+    } else {
+        std::fs::read_to_string(path)?
+    };
+
+    Ok(list_entries(&content)
+        .into_iter()
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Reads a list of file paths, one per line, such as the output of
+/// `git diff --name-only` or `find`.
+///
+/// Unlike the hand-written lists of [`read_directories_file`], the lines are
+/// taken literally: only line endings, including `\r`, are removed and blank
+/// lines are skipped, so names with spaces or a leading `#` are kept.
+///
+/// # Arguments
+///
+/// * `reader` - Source of the list, e.g. stdin
+///
+/// # Returns
+///
+/// The paths in list order, or an error if the list cannot be read or is not
+/// valid UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::read_path_list;
+///
+/// let paths = read_path_list("src/main.rs\r\n\nbin/my app.exe\n".as_bytes()).unwrap();
+/// assert_eq!(paths, vec![PathBuf::from("src/main.rs"), PathBuf::from("bin/my app.exe")]);
+/// ```
+pub fn read_path_list<R: std::io::Read>(reader: R) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let content = std::io::read_to_string(reader)?;
+
+    Ok(content
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Name of the file listing exclude patterns in a search directory.
+pub const FLISTIGNORE_FILE: &str = ".flistignore";
+
+/// Reads the exclude patterns of the [`FLISTIGNORE_FILE`] in a search directory.
+///
+/// The file has the format of a patterns file, see [`read_patterns_file`]. Its
+/// patterns are matched like `--exclude` patterns, against names.
+///
+/// # Arguments
+///
+/// * `directory` - The search directory
+///
+/// # Returns
+///
+/// The patterns in file order, none if the directory has no such file, or an
+/// error if the file cannot be read or contains an invalid pattern.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use flist::file_lister::read_flistignore;
+///
+/// let excludes = read_flistignore(Path::new("build")).unwrap();
+/// println!("Skipping {:?}", excludes);
+/// ```
+pub fn read_flistignore(directory: &Path) -> Result<Vec<String>, anyhow::Error> {
+    let path = directory.join(FLISTIGNORE_FILE);
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+
+    let patterns = read_patterns_file(&path)
+        .map_err(|e| anyhow::anyhow!("Failed to read '{}': {}", path.display(), e))?;
+    for pattern in &patterns {
+        NameMatcher::new(&[pattern.as_str()]).map_err(|e| {
+            anyhow::anyhow!(
+                "Invalid pattern '{}' in '{}': {}",
+                pattern,
+                path.display(),
+                e
+            )
+        })?;
+    }
+    Ok(patterns)
+}
+
+/// Splits a list file into its trimmed lines, without blank lines and `#` comments.
+fn list_entries(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect()
+}
+
+/// Returns the pattern as a file path if it names an existing file directly.
+///
+/// A pattern without glob metacharacters (`*`, `?`, `[`, `]`) that is a path,
+/// absolute or containing a path separator, and points to an existing file is
+/// treated as a direct lookup of that single file, so no directory enumeration
+/// is needed. A bare name such as `foo.dll` is always a pattern, so it is still
+/// searched for in subdirectories even if the current directory has a file of
+/// that name. Any other pattern returns `None` and should be matched against
+/// file names as usual.
+///
+/// # Arguments
+///
+/// * `pattern` - The search pattern or file path given on the command line
+///
+/// # Returns
+///
+/// `Some(path)` if the pattern is a plain path to an existing file, otherwise `None`.
+///
+/// # Examples
+///
+/// ```
+/// use flist::file_lister::direct_file_path;
+///
+/// assert!(direct_file_path("*.dll").is_none());
+/// assert!(direct_file_path("does-not-exist.dll").is_none());
+/// assert!(direct_file_path("Cargo.toml").is_none());
+/// assert!(direct_file_path("./Cargo.toml").is_some());
+/// ```
+pub fn direct_file_path(pattern: &str) -> Option<PathBuf> {
+    if pattern.contains(['*', '?', '[', ']']) {
+        return None;
+    }
+
+    let path = PathBuf::from(pattern);
+    let is_path = path.is_absolute() || pattern.contains(['/', std::path::MAIN_SEPARATOR]);
+    if is_path && path.is_file() {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+/// Removes paths that an exclude pattern applies to.
+///
+/// Given files are matched like enumerated ones: a path is removed if its file
+/// name or the name of one of its directories matches an exclude pattern, as if
+/// the directory had been pruned during a search.
+///
+/// # Arguments
+///
+/// * `paths` - Paths of files given directly, not found by a search
+/// * `excludes` - Glob patterns of file and directory names to skip
+/// * `ignore_case` - Whether letter case is ignored when matching
+///
+/// # Returns
+///
+/// The paths that no exclude pattern applies to, or an error if one of the
+/// patterns is invalid.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::filter_excluded_paths;
+///
+/// let paths = vec![PathBuf::from("bin/app.dll"), PathBuf::from("obj/app.dll")];
+/// let kept = filter_excluded_paths(paths, &["obj"], false).unwrap();
+/// assert_eq!(kept, vec![PathBuf::from("bin/app.dll")]);
+/// ```
+pub fn filter_excluded_paths(
+    paths: Vec<PathBuf>,
+    excludes: &[&str],
+    ignore_case: bool,
+) -> Result<Vec<PathBuf>, anyhow::Error> {
+    if excludes.is_empty() {
+        return Ok(paths);
+    }
+    let exclude = NameMatcher::new(excludes)?.ignore_case(ignore_case);
+    Ok(paths
+        .into_iter()
+        .filter(|path| {
+            !path
+                .ancestors()
+                .filter(|ancestor| {
+                    matches!(
+                        ancestor.components().next_back(),
+                        Some(Component::Normal(_))
+                    )
+                })
+                .any(|ancestor| exclude.matches_path(ancestor))
+        })
+        .collect())
+}
+
+/// Collects file information with optional version reading.
+///
+/// Takes a list of file paths and creates `FileInfo` structures, optionally
+/// extracting version information from each file.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file paths to process
+/// * `include_version` - Whether to extract version information from files
+/// * `cache` - Versions read before, e.g. by an earlier call; files found in the
+///   cache are not parsed again, new versions are added to it
+///
+/// # Returns
+///
+/// A vector of `FileInfo` structures containing paths and optional versions.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::PathBuf;
+/// use std::sync::Arc;
+/// use flist::file_lister::collect_file_info;
+/// use flist::version_reader::VersionCache;
+///
+/// let files = vec![PathBuf::from("test.dll")];
+/// let info = collect_file_info(files, false, None);
+///
+/// let cache = Arc::new(VersionCache::new());
+/// let info = collect_file_info(vec![PathBuf::from("a/core.dll")], true, Some(Arc::clone(&cache)));
+/// let info = collect_file_info(vec![PathBuf::from("b/core.dll")], true, Some(cache));
+/// ```
+pub fn collect_file_info(
+    files: Vec<PathBuf>,
+    include_version: bool,
+    cache: Option<Arc<VersionCache>>,
+) -> Vec<FileInfo> {
+    collect_file_info_with_options(
+        files,
+        &CollectOptions {
+            include_version,
+            version_cache: cache,
+            ..Default::default()
+        },
+    )
+}
+
+/// Collects file information using the given options.
+///
+/// Files that no longer exist when they are read are dropped, see
+/// [`collect_file_info_report`].
+///
+/// # Arguments
+///
+/// * `files` - Vector of file paths to process
+/// * `options` - Which information to gather for each file
+///
+/// # Returns
+///
+/// A vector of `FileInfo` structures with the requested information.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::PathBuf;
+/// use flist::file_lister::{collect_file_info_with_options, CollectOptions};
+///
+/// let files = vec![PathBuf::from("test.dll")];
+/// let options = CollectOptions {
+///     include_version: true,
+///     verify_checksum: true,
+///     ..Default::default()
+/// };
+/// let info = collect_file_info_with_options(files, &options);
+/// ```
+pub fn collect_file_info_with_options(
+    files: Vec<PathBuf>,
+    options: &CollectOptions,
+) -> Vec<FileInfo> {
+    collect_file_info_report(files, options).files
+}
+
+/// Result of [`collect_file_info_report`].
+#[derive(Debug, Clone, Default)]
+pub struct CollectReport {
+    /// Information about the files that were collected
+    pub files: Vec<FileInfo>,
+    /// Files that were enumerated but deleted before they could be read
+    pub vanished: Vec<PathBuf>,
+}
+
+/// Collects file information using the given options and reports files that
+/// vanished during the scan.
+///
+/// On a busy system a file can be deleted between enumeration and reading.
+/// When any information is read from the files, a file that no longer exists
+/// is dropped and listed in [`CollectReport::vanished`] instead. Other errors,
+/// such as denied access, keep the file in the results without the information
+/// that could not be read.
+///
+/// Files are read in parallel on [`CollectOptions::threads`] threads. The
+/// collected files keep the order of `files`.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file paths to process
+/// * `options` - Which information to gather for each file
+///
+/// # Returns
+///
+/// The collected files and the paths of vanished files.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::{collect_file_info_report, CollectOptions};
+///
+/// let options = CollectOptions { include_version: true, ..Default::default() };
+/// let report = collect_file_info_report(vec![PathBuf::from("deleted.dll")], &options);
+/// assert!(report.files.is_empty());
+/// assert_eq!(report.vanished, vec![PathBuf::from("deleted.dll")]);
+/// ```
+pub fn collect_file_info_report(files: Vec<PathBuf>, options: &CollectOptions) -> CollectReport {
+    let limiter = options.max_open_files.map(OpenFileLimiter::new);
+    let reads_files = options.include_version
+        || options.include_product_version
+        || options.include_assembly_version
+        || options.read_semantic_version
+        || options.read_strings
+        || options.verify_checksum
+        || options.list_streams
+        || options.read_created
+        || options.read_size
+        || options.read_modified
+        || options.read_pe_timestamp
+        || options.read_arch
+        || options.read_signed
+        || options.compute_hash;
+
+    // Files are read independently, the indexed collect keeps the input order
+    let collect = || -> Vec<Result<FileInfo, PathBuf>> {
+        files
+            .into_par_iter()
+            .map(|path| {
+                // Hold a permit while the file is open for reading
+                let _permit = limiter.as_ref().map(|limiter| limiter.acquire());
+                collect_one(path, options, reads_files)
+            })
+            .collect()
+    };
+    let pool = options
+        .threads
+        .map(|threads| rayon::ThreadPoolBuilder::new().num_threads(threads).build());
+    let collected = match pool {
+        Some(Ok(pool)) => pool.install(collect),
+        // Without a dedicated pool, use the global one with a thread per core
+        _ => collect(),
+    };
+
+    let mut report = CollectReport::default();
+    for result in collected {
+        match result {
+            Ok(file_info) => report.files.push(file_info),
+            Err(path) => report.vanished.push(path),
+        }
+    }
+
+    report
+}
+
+/// Collects the information about a single file, or returns its path as the
+/// error if it vanished before it could be read.
+fn collect_one(
+    path: PathBuf,
+    options: &CollectOptions,
+    reads_files: bool,
+) -> Result<FileInfo, PathBuf> {
+    // The readers treat unreadable files as "no information", so check
+    // whether the file is gone before reading it
+    if reads_files && vanished(&path) {
+        return Err(path);
+    }
+
+    if options.include_dirs && path.is_dir() {
+        return Ok(FileInfo {
+            path,
+            is_dir: true,
+            ..Default::default()
+        });
+    }
+
+    // Try to read versions, but don't fail if they are not available
+    let read_version = || {
+        match &options.version_cache {
+            Some(cache) => cache.get_or_read(&path),
+            None => read_file_version(&path),
+        }
+        .ok()
+        .flatten()
+    };
+    let (version, product_version) = if options.include_product_version {
+        // Both versions come from the same version resource, parse it once
+        let info = match &options.version_cache {
+            Some(cache) => cache.get_or_read_all(&path),
+            None => read_all_version_fields(&path),
+        };
+        match info.ok().flatten() {
+            Some(info) => (
+                info.file_version.filter(|_| options.include_version),
+                info.product_version,
+            ),
+            // An MSI package's ProductVersion is its only version
+            None => match read_msi_product_version(&path).ok().flatten() {
+                Some(product_version) => (
+                    Some(product_version).filter(|_| options.include_version),
+                    Some(product_version),
+                ),
+                // Not a PE file: the version of an ELF soname or Mach-O dylib
+                None => (options.include_version.then(read_version).flatten(), None),
+            },
+        }
+    } else if options.include_version {
+        (read_version(), None)
+    } else {
+        (None, None)
+    };
+    let assembly_version = if options.include_assembly_version {
+        read_assembly_version(&path).ok().flatten()
+    } else {
+        None
+    };
+    // The semantic version is parsed from the version strings, read them once
+    let version_strings = if options.read_strings || options.read_semantic_version {
+        read_version_strings(&path, options.strings_language)
+            .ok()
+            .flatten()
+    } else {
+        None
+    };
+    let semantic_version = version_strings
+        .as_ref()
+        .filter(|_| options.read_semantic_version)
+        .and_then(VersionStrings::semantic_version);
+    let version_strings = version_strings.filter(|_| options.read_strings);
+    let checksum_ok = if options.verify_checksum {
+        let buffer_size = options.read_buffer_size.unwrap_or(DEFAULT_READ_BUFFER_SIZE);
+        verify_pe_checksum_with_buffer(&path, buffer_size)
+            .ok()
+            .flatten()
+    } else {
+        None
+    };
+    let pe_timestamp = if options.read_pe_timestamp {
+        read_pe_timestamp(&path).ok().flatten()
+    } else {
+        None
+    };
+    let arch = if options.read_arch {
+        read_pe_arch(&path).ok().flatten()
+    } else {
+        None
+    };
+    let signed = if options.read_signed {
+        read_pe_signed(&path).ok().flatten()
+    } else {
+        None
+    };
+    let hash = if options.compute_hash {
+        let buffer_size = options.read_buffer_size.unwrap_or(DEFAULT_READ_BUFFER_SIZE);
+        content_hash(&path, buffer_size).ok()
+    } else {
+        None
+    };
+    let streams = if options.list_streams {
+        alternate_streams(&path).unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    let metadata = if options.read_created || options.read_size || options.read_modified {
+        std::fs::metadata(&path).ok()
+    } else {
+        None
+    };
+    let created = metadata
+        .as_ref()
+        .filter(|_| options.read_created)
+        .and_then(|metadata| metadata.created().ok());
+    let size = metadata
+        .as_ref()
+        .filter(|_| options.read_size)
+        .map(|metadata| metadata.len());
+    let modified = metadata
+        .as_ref()
+        .filter(|_| options.read_modified)
+        .and_then(|metadata| metadata.modified().ok());
+    if let Some(progress) = &options.progress {
+        progress.add_processed(version.is_some() || product_version.is_some());
+    }
+    Ok(FileInfo {
+        path,
+        version,
+        product_version,
+        assembly_version,
+        semantic_version,
+        version_strings,
+        checksum_ok,
+        streams,
+        is_dir: false,
+        created,
+        size,
+        modified,
+        pe_timestamp,
+        arch,
+        signed,
+        hash,
+        root: None,
+    })
+}
+
+/// Computes the BLAKE3 hash of a file's contents.
+///
+/// The file is read in chunks of `buffer_size` bytes, so large files are not
+/// held in memory.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file to hash
+/// * `buffer_size` - Size of the read buffer in bytes, at least 1
+///
+/// # Returns
+///
+/// The hash as 64 lowercase hex digits, or an error if the file cannot be read.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use flist::file_lister::content_hash;
+///
+/// let a = content_hash(Path::new("a.dll"), 64 * 1024).unwrap();
+/// let b = content_hash(Path::new("b.dll"), 64 * 1024).unwrap();
+/// println!("identical: {}", a == b);
+/// ```
+pub fn content_hash(path: &Path, buffer_size: usize) -> Result<String, std::io::Error> {
+    use std::io::Read;
+
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    let mut buffer = vec![0u8; buffer_size.max(1)];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// Replaces the path of each file by its canonical, absolute form.
+///
+/// Symbolic links and `.` and `..` components are resolved with
+/// [`std::fs::canonicalize`]. A path that cannot be resolved, for example
+/// because the file was deleted since it was found, is made absolute with
+/// [`std::path::absolute`] instead and returned together with the error.
+///
+/// # Arguments
+///
+/// * `files` - The files whose paths are replaced
+///
+/// # Returns
+///
+/// The original paths that could not be resolved, with the reason.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::PathBuf;
+/// use flist::file_lister::{FileInfo, canonicalize_paths};
+///
+/// let mut files = vec![FileInfo { path: PathBuf::from("./bin/../app.dll"), ..Default::default() }];
+/// for (path, error) in canonicalize_paths(&mut files) {
+///     eprintln!("{}: {}", path.display(), error);
+/// }
+/// println!("{}", files[0].path.display());
+/// ```
+pub fn canonicalize_paths(files: &mut [FileInfo]) -> Vec<(PathBuf, std::io::Error)> {
+    let mut failed = Vec::new();
+    for file_info in files {
+        match std::fs::canonicalize(&file_info.path) {
+            Ok(canonical) => file_info.path = canonical,
+            Err(e) => {
+                if let Ok(absolute) = std::path::absolute(&file_info.path) {
+                    failed.push((std::mem::replace(&mut file_info.path, absolute), e));
+                } else {
+                    failed.push((file_info.path.clone(), e));
+                }
+            }
+        }
+    }
+    failed
+}
+
+/// Returns whether `path` no longer exists, as opposed to being inaccessible.
+fn vanished(path: &Path) -> bool {
+    matches!(
+        std::fs::symlink_metadata(path),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound
+    )
+}
+
+/// Filters files by version constraints.
+///
+/// Keeps only files whose versions fall within the specified range.
+/// Files without version information are excluded.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to filter
+/// * `min_version` - Minimum version (inclusive), or None for no minimum
+/// * `max_version` - Maximum version (inclusive), or None for no maximum
+///
+/// # Returns
+///
+/// A filtered vector containing only files matching the version constraints.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use std::str::FromStr;
+/// use flist::file_lister::{FileInfo, filter_by_version};
+/// use flist::file_version::FileVersion;
+///
+/// let files = vec![
+///     FileInfo {
+///         path: PathBuf::from("test.dll"),
+///         version: Some(FileVersion::from_str("1.5.0.0").unwrap()),
+///         ..Default::default()
+///     },
+/// ];
+/// let min = Some(FileVersion::from_str("1.0.0.0").unwrap());
+/// let max = Some(FileVersion::from_str("2.0.0.0").unwrap());
+/// let filtered = filter_by_version(files, min, max);
+/// assert_eq!(filtered.len(), 1);
+/// ```
+pub fn filter_by_version(
+    files: Vec<FileInfo>,
+    min_version: Option<FileVersion>,
+    max_version: Option<FileVersion>,
+) -> Vec<FileInfo> {
+    filter_by_version_of(files, min_version, max_version, VersionField::File)
+}
+
+/// Filters files by the version in the given field.
+///
+/// Works like [`filter_by_version`], but compares the file or the product
+/// version depending on `field`.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to filter
+/// * `min_version` - Minimum version (inclusive), or None for no minimum
+/// * `max_version` - Maximum version (inclusive), or None for no maximum
+/// * `field` - Which version to compare
+///
+/// # Returns
+///
+/// A filtered vector containing only files matching the version constraints.
+pub fn filter_by_version_of(
+    files: Vec<FileInfo>,
+    min_version: Option<FileVersion>,
+    max_version: Option<FileVersion>,
+    field: VersionField,
+) -> Vec<FileInfo> {
+    files
+        .into_iter()
+        .filter(|file_info| {
+            if let Some(version) = file_info.version_of(field) {
+                let min_ok = min_version.is_none_or(|min| version >= min);
+                let max_ok = max_version.is_none_or(|max| version <= max);
+                min_ok && max_ok
+            } else {
+                // Files without version info are excluded when filtering by version
+                false
+            }
+        })
+        .collect()
+}
+
+/// Filters files to those whose version matches a wildcard pattern.
+///
+/// Files without version information are excluded.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to filter
+/// * `pattern` - The pattern versions must match, e.g. `1.2.*`
+///
+/// # Returns
+///
+/// The files whose version matches the pattern.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::{FileInfo, filter_by_version_pattern};
+///
+/// let files = vec![
+///     FileInfo { path: PathBuf::from("a.dll"), version: Some("1.2.9.1".parse().unwrap()), ..Default::default() },
+///     FileInfo { path: PathBuf::from("b.dll"), version: Some("1.3.0.0".parse().unwrap()), ..Default::default() },
+/// ];
+/// let filtered = filter_by_version_pattern(files, &"1.2.*".parse().unwrap());
+/// assert_eq!(filtered.len(), 1);
+/// assert_eq!(filtered[0].path, PathBuf::from("a.dll"));
+/// ```
+pub fn filter_by_version_pattern(files: Vec<FileInfo>, pattern: &VersionPattern) -> Vec<FileInfo> {
+    files
+        .into_iter()
+        .filter(|file_info| {
+            file_info
+                .version
+                .is_some_and(|version| pattern.matches(&version))
+        })
+        .collect()
+}
+
+/// Filters files by creation time.
+///
+/// Both bounds are inclusive. Files without a creation time, because it was not
+/// read or is not supported by the platform or filesystem, are excluded when a
+/// bound is given.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to filter
+/// * `created_after` - Earliest creation time, `None` for no lower bound
+/// * `created_before` - Latest creation time, `None` for no upper bound
+///
+/// # Returns
+///
+/// The files created within the bounds.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use flist::file_lister::{FileInfo, filter_by_created};
+///
+/// let files = vec![
+///     FileInfo { path: PathBuf::from("old.dll"), created: Some(UNIX_EPOCH), ..Default::default() },
+///     FileInfo { path: PathBuf::from("new.dll"), created: Some(UNIX_EPOCH + Duration::from_secs(60)), ..Default::default() },
+/// ];
+/// let filtered = filter_by_created(files, Some(UNIX_EPOCH + Duration::from_secs(1)), None);
+/// assert_eq!(filtered[0].path, PathBuf::from("new.dll"));
+/// ```
+pub fn filter_by_created(
+    files: Vec<FileInfo>,
+    created_after: Option<SystemTime>,
+    created_before: Option<SystemTime>,
+) -> Vec<FileInfo> {
+    files
+        .into_iter()
+        .filter(|file_info| match file_info.created {
+            Some(created) => {
+                created_after.is_none_or(|after| created >= after)
+                    && created_before.is_none_or(|before| created <= before)
+            }
+            None => false,
+        })
+        .collect()
+}
+
+/// Filters files by last modification time.
+///
+/// Keeps only files modified within the bounds, both inclusive. Files without a
+/// known modification time, such as files whose metadata could not be read,
+/// are excluded.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to filter
+/// * `modified_after` - Earliest modification time, `None` for no lower bound
+/// * `modified_before` - Latest modification time, `None` for no upper bound
+///
+/// # Returns
+///
+/// The files modified within the bounds.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use flist::file_lister::{FileInfo, filter_by_mtime};
+///
+/// let files = vec![
+///     FileInfo { path: PathBuf::from("old.dll"), modified: Some(UNIX_EPOCH), ..Default::default() },
+///     FileInfo { path: PathBuf::from("new.dll"), modified: Some(UNIX_EPOCH + Duration::from_secs(60)), ..Default::default() },
+/// ];
+/// let filtered = filter_by_mtime(files, Some(UNIX_EPOCH + Duration::from_secs(1)), None);
+/// assert_eq!(filtered[0].path, PathBuf::from("new.dll"));
+/// ```
+pub fn filter_by_mtime(
+    files: Vec<FileInfo>,
+    modified_after: Option<SystemTime>,
+    modified_before: Option<SystemTime>,
+) -> Vec<FileInfo> {
+    files
+        .into_iter()
+        .filter(|file_info| match file_info.modified {
+            Some(modified) => {
+                modified_after.is_none_or(|after| modified >= after)
+                    && modified_before.is_none_or(|before| modified <= before)
+            }
+            None => false,
+        })
+        .collect()
+}
+
+/// Filters files by size.
+///
+/// Keeps only files whose size falls within the bounds. Files without a known
+/// size, such as directories or files whose metadata could not be read, are
+/// excluded.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to filter
+/// * `min_size` - Minimum size in bytes (inclusive), `None` for no minimum
+/// * `max_size` - Maximum size in bytes (inclusive), `None` for no maximum
+///
+/// # Returns
+///
+/// The files within the size bounds.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::{FileInfo, filter_by_size};
+///
+/// let files = vec![
+///     FileInfo { path: PathBuf::from("small.dll"), size: Some(4096), ..Default::default() },
+///     FileInfo { path: PathBuf::from("large.dll"), size: Some(20 << 20), ..Default::default() },
+/// ];
+/// let filtered = filter_by_size(files, Some(10 << 20), None);
+/// assert_eq!(filtered[0].path, PathBuf::from("large.dll"));
+/// ```
+pub fn filter_by_size(
+    files: Vec<FileInfo>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+) -> Vec<FileInfo> {
+    files
+        .into_iter()
+        .filter(|file_info| match file_info.size {
+            Some(size) => {
+                min_size.is_none_or(|min| size >= min) && max_size.is_none_or(|max| size <= max)
+            }
+            None => false,
+        })
+        .collect()
+}
+
+/// Sums the sizes of files.
+///
+/// Files whose size was not read count as zero.
+///
+/// # Arguments
+///
+/// * `files` - The files to sum
+///
+/// # Returns
+///
+/// The total size in bytes.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::{FileInfo, total_size};
+///
+/// let files = vec![
+///     FileInfo { path: PathBuf::from("a.dll"), size: Some(4096), ..Default::default() },
+///     FileInfo { path: PathBuf::from("b.dll"), size: Some(1024), ..Default::default() },
+///     FileInfo { path: PathBuf::from("c.dll"), size: None, ..Default::default() },
+/// ];
+/// assert_eq!(total_size(&files), 5120);
+/// ```
+pub fn total_size(files: &[FileInfo]) -> u64 {
+    files.iter().filter_map(|file_info| file_info.size).sum()
+}
+
+/// Removes files whose version matches any of the given patterns.
+///
+/// This is the inverse of a version match: it finds the outdated files among
+/// mostly updated ones. Files without version information never match a pattern
+/// and are kept.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to filter
+/// * `patterns` - Version patterns to exclude; a file is removed if it matches any
+///
+/// # Returns
+///
+/// The files whose version matches none of the patterns.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::{FileInfo, filter_excluded_versions};
+///
+/// let files = vec![
+///     FileInfo { path: PathBuf::from("new.dll"), version: Some("1.2.3.4".parse().unwrap()), ..Default::default() },
+///     FileInfo { path: PathBuf::from("old.dll"), version: Some("1.1.0.0".parse().unwrap()), ..Default::default() },
+/// ];
+/// let filtered = filter_excluded_versions(files, &["1.2.*".parse().unwrap()]);
+/// assert_eq!(filtered.len(), 1);
+/// assert_eq!(filtered[0].path, PathBuf::from("old.dll"));
+/// ```
+pub fn filter_excluded_versions(
+    files: Vec<FileInfo>,
+    patterns: &[VersionPattern],
+) -> Vec<FileInfo> {
+    filter_excluded_versions_of(files, patterns, VersionField::File)
+}
+
+/// Removes files whose version in the given field matches any of the patterns.
+///
+/// Works like [`filter_excluded_versions`], but matches the file or the product
+/// version depending on `field`.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to filter
+/// * `patterns` - Version patterns to exclude
+/// * `field` - Which version to match
+///
+/// # Returns
+///
+/// The files whose version matches none of the patterns.
+pub fn filter_excluded_versions_of(
+    files: Vec<FileInfo>,
+    patterns: &[VersionPattern],
+    field: VersionField,
+) -> Vec<FileInfo> {
+    files
+        .into_iter()
+        .filter(|file_info| match &file_info.version_of(field) {
+            Some(version) => !patterns.iter().any(|pattern| pattern.matches(version)),
+            None => true,
+        })
+        .collect()
+}
+
+/// Filters files to those whose version equals `version`.
+///
+/// Parts omitted at the end of `version` are wildcards, so `1.2` keeps every
+/// `1.2.x.y` version while `1.2.3.4` keeps only that exact version. A `*` part
+/// matches any value as well, see [`VersionPattern`]. Parts that are not set in
+/// a file's version compare as zero. Files without version information are
+/// excluded.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to filter
+/// * `version` - The version to keep, possibly partial
+///
+/// # Returns
+///
+/// The files whose version matches `version`.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::{FileInfo, filter_by_exact_version};
+///
+/// let files = vec![
+///     FileInfo { path: PathBuf::from("a.dll"), version: Some("1.2.3.4".parse().unwrap()), ..Default::default() },
+///     FileInfo { path: PathBuf::from("b.dll"), version: Some("1.2.9.0".parse().unwrap()), ..Default::default() },
+///     FileInfo { path: PathBuf::from("c.dll"), version: Some("1.3.0.0".parse().unwrap()), ..Default::default() },
+/// ];
+/// assert_eq!(filter_by_exact_version(files.clone(), &"1.2.3.4".parse().unwrap()).len(), 1);
+/// assert_eq!(filter_by_exact_version(files, &"1.2".parse().unwrap()).len(), 2);
+/// ```
+pub fn filter_by_exact_version(files: Vec<FileInfo>, version: &VersionPattern) -> Vec<FileInfo> {
+    filter_by_exact_version_of(files, version, VersionField::File)
+}
+
+/// Filters files to those whose version in the given field equals `version`.
+///
+/// Works like [`filter_by_exact_version`], but matches the file or the product
+/// version depending on `field`.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to filter
+/// * `version` - The version to keep, possibly partial
+/// * `field` - Which version to match
+///
+/// # Returns
+///
+/// The files whose version matches `version`.
+pub fn filter_by_exact_version_of(
+    files: Vec<FileInfo>,
+    version: &VersionPattern,
+    field: VersionField,
+) -> Vec<FileInfo> {
+    files
+        .into_iter()
+        .filter(|file_info| {
+            file_info
+                .version_of(field)
+                .is_some_and(|v| version.matches(&v))
+        })
+        .collect()
+}
+
+/// Filters files to those older than a reference version.
+///
+/// Keeps only files whose version is strictly lower than `reference`. This is
+/// used to find files that should be at least as new as a reference file but
+/// are not. Files without version information are excluded.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to filter
+/// * `reference` - Version every file is expected to reach
+///
+/// # Returns
+///
+/// A filtered vector containing only files older than the reference version.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use std::str::FromStr;
+/// use flist::file_lister::{FileInfo, filter_older_than};
+/// use flist::file_version::FileVersion;
+///
+/// let files = vec![
+///     FileInfo {
+///         path: PathBuf::from("old.dll"),
+///         version: Some(FileVersion::from_str("1.0.0.0").unwrap()),
+///         ..Default::default()
+///     },
+///     FileInfo {
+///         path: PathBuf::from("new.dll"),
+///         version: Some(FileVersion::from_str("2.0.0.0").unwrap()),
+///         ..Default::default()
+///     },
+/// ];
+/// let offenders = filter_older_than(files, FileVersion::from_str("2.0.0.0").unwrap());
+/// assert_eq!(offenders.len(), 1);
+/// assert_eq!(offenders[0].path, PathBuf::from("old.dll"));
+/// ```
+pub fn filter_older_than(files: Vec<FileInfo>, reference: FileVersion) -> Vec<FileInfo> {
+    filter_older_than_of(files, reference, VersionField::File)
+}
+
+/// Filters files to those whose version in the given field is older than a
+/// reference version.
+///
+/// Works like [`filter_older_than`], but compares the file or the product
+/// version depending on `field`.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to filter
+/// * `reference` - The version files are compared against
+/// * `field` - Which version to compare
+///
+/// # Returns
+///
+/// The files with a version strictly lower than `reference`.
+pub fn filter_older_than_of(
+    files: Vec<FileInfo>,
+    reference: FileVersion,
+    field: VersionField,
+) -> Vec<FileInfo> {
+    files
+        .into_iter()
+        .filter(|file_info| {
+            file_info
+                .version_of(field)
+                .is_some_and(|version| version < reference)
+        })
+        .collect()
+}
+
+/// Reads the version of the reference file for `--at-least-version-of`.
+///
+/// # Arguments
+///
+/// * `path` - Path to the reference file
+/// * `field` - Which version to read
+///
+/// # Returns
+///
+/// The version to pass to [`filter_older_than_of`], or an error if the file
+/// cannot be read or has no version information.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use flist::file_lister::{VersionField, read_reference_version};
+///
+/// let reference = read_reference_version(Path::new("app.dll"), VersionField::File).unwrap();
+/// println!("Files older than {}", reference);
+/// ```
+pub fn read_reference_version(
+    path: &Path,
+    field: VersionField,
+) -> Result<FileVersion, anyhow::Error> {
+    match field {
+        VersionField::File => read_file_version(path),
+        VersionField::Product => {
+            read_all_version_fields(path).map(|info| info.and_then(|info| info.product_version))
+        }
+    }
+    .map_err(|e| anyhow::anyhow!("Failed to read reference file '{}': {}", path.display(), e))?
+    .ok_or_else(|| {
+        anyhow::anyhow!(
+            "Reference file '{}' has no version information",
+            path.display()
+        )
+    })
+}
+
+/// Filters files to those whose PE checksum does not match.
+///
+/// Keeps only files with `checksum_ok == Some(false)`. Files that were not
+/// verified, are not PE files, or have no checksum set are excluded.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to filter
+///
+/// # Returns
+///
+/// A filtered vector containing only files with a checksum mismatch.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::{FileInfo, filter_bad_checksum};
+///
+/// let files = vec![
+///     FileInfo { path: PathBuf::from("ok.dll"), checksum_ok: Some(true), ..Default::default() },
+///     FileInfo { path: PathBuf::from("bad.dll"), checksum_ok: Some(false), ..Default::default() },
+/// ];
+/// let bad = filter_bad_checksum(files);
+/// assert_eq!(bad.len(), 1);
+/// ```
+pub fn filter_bad_checksum(files: Vec<FileInfo>) -> Vec<FileInfo> {
+    files
+        .into_iter()
+        .filter(|file_info| file_info.checksum_ok == Some(false))
+        .collect()
+}
+
+/// Filters files whose file version and product version disagree.
+///
+/// Keeps only files where both versions are known and differ, which often
+/// points to a mislabeled build. Files missing either version are excluded.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to filter
+///
+/// # Returns
+///
+/// A filtered vector containing only files with mismatched versions.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::{FileInfo, filter_mismatched_versions};
+///
+/// let files = vec![
+///     FileInfo {
+///         path: PathBuf::from("app.dll"),
+///         version: Some("1.2.3.4".parse().unwrap()),
+///         product_version: Some("1.2.0.0".parse().unwrap()),
+///         ..Default::default()
+///     },
+/// ];
+/// assert_eq!(filter_mismatched_versions(files).len(), 1);
+/// ```
+pub fn filter_mismatched_versions(files: Vec<FileInfo>) -> Vec<FileInfo> {
+    files
+        .into_iter()
+        .filter(
+            |file_info| match (file_info.version, file_info.product_version) {
+                (Some(version), Some(product_version)) => version != product_version,
+                _ => false,
+            },
+        )
+        .collect()
+}
+
+/// Filters files by whether they have version information.
+///
+/// With `has_version`, keeps the files whose file version was read, such as PE
+/// files with a version resource. Otherwise keeps the files without one, for
+/// example binaries that still need a version resource before signing.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to filter
+/// * `has_version` - Whether to keep the files with (`true`) or without
+///   (`false`) a file version
+///
+/// # Returns
+///
+/// A filtered vector containing only the files with or without a version.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::{FileInfo, filter_by_has_version};
+///
+/// let files = vec![
+///     FileInfo { path: PathBuf::from("app.dll"), version: Some("1.0".parse().unwrap()), ..Default::default() },
+///     FileInfo { path: PathBuf::from("tool.exe"), version: None, ..Default::default() },
+/// ];
+/// let unversioned = filter_by_has_version(files, false);
+/// assert_eq!(unversioned[0].path, PathBuf::from("tool.exe"));
+/// ```
+pub fn filter_by_has_version(files: Vec<FileInfo>, has_version: bool) -> Vec<FileInfo> {
+    files
+        .into_iter()
+        .filter(|file_info| file_info.version.is_some() == has_version)
+        .collect()
+}
+
+/// Filters files by the target architecture from their PE header.
+///
+/// Keeps only files with `arch == Some(arch)`. Files whose architecture was not
+/// read or that are not PE files are excluded.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to filter
+/// * `arch` - The architecture to keep
+///
+/// # Returns
+///
+/// A filtered vector containing only files built for `arch`.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::{FileInfo, filter_by_arch};
+/// use flist::version_reader::PeArch;
+///
+/// let files = vec![
+///     FileInfo { path: PathBuf::from("old.dll"), arch: Some(PeArch::X86), ..Default::default() },
+///     FileInfo { path: PathBuf::from("new.dll"), arch: Some(PeArch::X64), ..Default::default() },
+/// ];
+/// let old = filter_by_arch(files, PeArch::X86);
+/// assert_eq!(old.len(), 1);
+/// ```
+pub fn filter_by_arch(files: Vec<FileInfo>, arch: PeArch) -> Vec<FileInfo> {
+    files
+        .into_iter()
+        .filter(|file_info| file_info.arch == Some(arch))
+        .collect()
+}
+
+/// Filters files by their semantic version.
+///
+/// Compares [`FileInfo::semantic_version`] by semver precedence, so a
+/// prerelease is lower than its release: `1.2.3-beta` is below a minimum of
+/// `1.2.3`. Files without a semantic version are excluded.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to filter
+/// * `min_version` - Minimum version (inclusive), or None for no minimum
+/// * `max_version` - Maximum version (inclusive), or None for no maximum
+///
+/// # Returns
+///
+/// A filtered vector containing only files matching the version constraints.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::{FileInfo, filter_by_semantic_version};
+///
+/// let files = vec![
+///     FileInfo {
+///         path: PathBuf::from("beta.dll"),
+///         semantic_version: Some("1.2.3-beta".parse().unwrap()),
+///         ..Default::default()
+///     },
+///     FileInfo {
+///         path: PathBuf::from("release.dll"),
+///         semantic_version: Some("1.2.3".parse().unwrap()),
+///         ..Default::default()
+///     },
+/// ];
+/// let min = "1.2.3".parse().unwrap();
+/// let releases = filter_by_semantic_version(files, Some(&min), None);
+/// assert_eq!(releases.len(), 1);
+/// ```
+pub fn filter_by_semantic_version(
+    files: Vec<FileInfo>,
+    min_version: Option<&SemanticVersion>,
+    max_version: Option<&SemanticVersion>,
+) -> Vec<FileInfo> {
+    files
+        .into_iter()
+        .filter(|file_info| {
+            file_info.semantic_version.as_ref().is_some_and(|version| {
+                min_version.is_none_or(|min| version >= min)
+                    && max_version.is_none_or(|max| version <= max)
+            })
+        })
+        .collect()
+}
+
+/// Filters files to PE files without an embedded signature.
+///
+/// Keeps only files with `signed == Some(false)`. Files that were not checked
+/// or are not PE files are excluded.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::{FileInfo, filter_unsigned};
+///
+/// let files = vec![
+///     FileInfo { path: PathBuf::from("signed.dll"), signed: Some(true), ..Default::default() },
+///     FileInfo { path: PathBuf::from("unsigned.dll"), signed: Some(false), ..Default::default() },
+///     FileInfo { path: PathBuf::from("readme.txt"), signed: None, ..Default::default() },
+/// ];
+/// let unsigned = filter_unsigned(files);
+/// assert_eq!(unsigned.len(), 1);
+/// ```
+pub fn filter_unsigned(files: Vec<FileInfo>) -> Vec<FileInfo> {
+    files
+        .into_iter()
+        .filter(|file_info| file_info.signed == Some(false))
+        .collect()
+}
+
+/// Keeps only paths whose extension is in a set of extensions.
+///
+/// Extensions are compared case-insensitively and may be given with or without
+/// a leading dot. Paths without an extension are excluded. Filtering paths
+/// before file information is collected avoids reading files that would be
+/// dropped anyway.
+///
+/// # Arguments
+///
+/// * `paths` - Vector of paths to filter
+/// * `extensions` - The extensions to keep, e.g. `["dll", "exe"]`
+///
+/// # Returns
+///
+/// A filtered vector containing only paths with one of the extensions.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::filter_by_extensions;
+///
+/// let paths = vec![
+///     PathBuf::from("app.EXE"),
+///     PathBuf::from("core.dll"),
+///     PathBuf::from("readme.txt"),
+///     PathBuf::from("LICENSE"),
+/// ];
+/// let binaries = filter_by_extensions(paths, &["exe".to_string(), ".dll".to_string()]);
+/// assert_eq!(binaries, vec![PathBuf::from("app.EXE"), PathBuf::from("core.dll")]);
+/// ```
+pub fn filter_by_extensions(paths: Vec<PathBuf>, extensions: &[String]) -> Vec<PathBuf> {
+    paths
+        .into_iter()
+        .filter(|path| has_extension(path, extensions))
+        .collect()
+}
+
+/// Returns whether the extension of `path` is one of `extensions`, ignoring case.
+///
+/// This is the check of [`filter_by_extensions`] for a single path, e.g. for
+/// paths checked one by one as they are found.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+/// use flist::file_lister::has_extension;
+///
+/// assert!(has_extension(Path::new("Core.DLL"), &["dll".to_string()]));
+/// assert!(!has_extension(Path::new("LICENSE"), &["dll".to_string()]));
+/// ```
+pub fn has_extension(path: &Path, extensions: &[String]) -> bool {
+    let Some(extension) = path.extension().and_then(|e| e.to_str()) else {
+        return false;
+    };
+    extensions.iter().any(|wanted| {
+        wanted
+            .trim_start_matches('.')
+            .eq_ignore_ascii_case(extension)
+    })
+}
+
+/// A file whose version is lower than that of a file with the same name that
+/// comes before it in path order.
+#[derive(Debug, Clone)]
+pub struct VersionRegression {
+    /// The earlier file with the higher version
+    pub earlier: FileInfo,
+    /// The later file with the lower version
+    pub later: FileInfo,
+}
+
+/// Finds version regressions between files with the same name.
+///
+/// Files are grouped by file name and each group is ordered by path. Within a
+/// group, every file whose version is lower than the highest version seen on an
+/// earlier path is reported together with that earlier file. This catches a
+/// stale copy of a DLL placed after a newer one in load order. Files without
+/// version information are ignored.
+///
+/// # Arguments
+///
+/// * `files` - Slice of file information to check
+///
+/// # Returns
+///
+/// The regressions found, ordered by file name and path.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::{FileInfo, find_version_regressions};
+///
+/// let files = vec![
+///     FileInfo {
+///         path: PathBuf::from("a/core.dll"),
+///         version: Some("2.0.0.0".parse().unwrap()),
+///         ..Default::default()
+///     },
+///     FileInfo {
+///         path: PathBuf::from("b/core.dll"),
+///         version: Some("1.0.0.0".parse().unwrap()),
+///         ..Default::default()
+///     },
+/// ];
+/// let regressions = find_version_regressions(&files);
+/// assert_eq!(regressions.len(), 1);
+/// assert_eq!(regressions[0].later.path, PathBuf::from("b/core.dll"));
+/// ```
+pub fn find_version_regressions(files: &[FileInfo]) -> Vec<VersionRegression> {
+    let mut groups: std::collections::BTreeMap<&std::ffi::OsStr, Vec<&FileInfo>> =
+        std::collections::BTreeMap::new();
+    for file_info in files.iter().filter(|f| f.version.is_some()) {
+        if let Some(name) = file_info.path.file_name() {
+            groups.entry(name).or_default().push(file_info);
+        }
+    }
+
+    let mut regressions = Vec::new();
+    for group in groups.values_mut() {
+        group.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let mut highest: Option<&FileInfo> = None;
+        for &file_info in group.iter() {
+            match highest {
+                Some(earlier) if file_info.version < earlier.version => {
+                    regressions.push(VersionRegression {
+                        earlier: earlier.clone(),
+                        later: file_info.clone(),
+                    });
+                }
+                Some(earlier) if file_info.version <= earlier.version => {}
+                _ => highest = Some(file_info),
+            }
+        }
+    }
+
+    regressions
+}
+
+/// Keeps only the file with the highest version among files with the same name.
+///
+/// Files are grouped by file name across directories. Of each group, the file
+/// with the highest version is kept; of several files with that version, the
+/// one with the lowest path. Files without version information are always kept,
+/// as they cannot be compared. The kept files stay in their original order.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to reduce
+///
+/// # Returns
+///
+/// The files that have the highest version of their name, plus files without
+/// version information.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::{FileInfo, keep_highest_version};
+///
+/// let file = |path: &str, version: &str| FileInfo {
+///     path: PathBuf::from(path),
+///     version: Some(version.parse().unwrap()),
+///     ..Default::default()
+/// };
+/// let files = vec![file("a/core.dll", "1.0"), file("b/core.dll", "2.0")];
+/// let highest = keep_highest_version(files);
+/// assert_eq!(highest.len(), 1);
+/// assert_eq!(highest[0].path, PathBuf::from("b/core.dll"));
+/// ```
+pub fn keep_highest_version(files: Vec<FileInfo>) -> Vec<FileInfo> {
+    let mut best: HashMap<&std::ffi::OsStr, &FileInfo> = HashMap::new();
+    for file_info in files.iter().filter(|f| f.version.is_some()) {
+        let Some(name) = file_info.path.file_name() else {
+            continue;
+        };
+        best.entry(name)
+            .and_modify(|current| {
+                let higher = file_info
+                    .version
+                    .cmp(&current.version)
+                    .then_with(|| current.path.cmp(&file_info.path));
+                if higher.is_gt() {
+                    *current = file_info;
+                }
+            })
+            .or_insert(file_info);
+    }
+    let kept: HashSet<&Path> = best.values().map(|f| f.path.as_path()).collect();
+
+    let keep: Vec<bool> = files
+        .iter()
+        .map(|f| {
+            f.version.is_none() || f.path.file_name().is_none() || kept.contains(f.path.as_path())
+        })
+        .collect();
+    files
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(file_info, keep)| keep.then_some(file_info))
+        .collect()
+}
+
+/// Which version of a file the version filters use.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum VersionField {
+    /// File version (`dwFileVersion`)
+    #[default]
+    File,
+    /// Product version (`dwProductVersion`)
+    Product,
+}
+
+impl FileInfo {
+    /// Returns the version in the given field.
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - Which version to return
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flist::file_lister::{FileInfo, VersionField};
+    ///
+    /// let info = FileInfo { product_version: Some("2.0".parse().unwrap()), ..Default::default() };
+    /// assert_eq!(info.version_of(VersionField::File), None);
+    /// assert_eq!(info.version_of(VersionField::Product), Some("2.0".parse().unwrap()));
+    /// ```
+    pub fn version_of(&self, field: VersionField) -> Option<FileVersion> {
+        match field {
+            VersionField::File => self.version,
+            VersionField::Product => self.product_version,
+        }
+    }
+}
+
+/// A key that results can be sorted by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum SortKey {
+    /// Full file path
+    #[default]
+    Path,
+    /// File name without the directory
+    Name,
+    /// File version, files without version information last
+    Version,
+    /// File extension ignoring case, files without extension first
+    #[value(name = "ext")]
+    Extension,
+}
+
+impl SortKey {
+    /// Compares two files by this key alone.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - First file
+    /// * `b` - Second file
+    ///
+    /// # Returns
+    ///
+    /// The ordering of `a` relative to `b`.
+    pub fn compare(&self, a: &FileInfo, b: &FileInfo) -> Ordering {
+        self.compare_with_order(a, b, PathOrder::Lexicographic)
+    }
+
+    /// Compares two files by this key alone, comparing paths and names in the
+    /// given order.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - First file
+    /// * `b` - Second file
+    /// * `order` - How paths and names are compared
+    ///
+    /// # Returns
+    ///
+    /// The ordering of `a` relative to `b`.
+    pub fn compare_with_order(&self, a: &FileInfo, b: &FileInfo, order: PathOrder) -> Ordering {
+        match self {
+            SortKey::Path => order.compare_paths(&a.path, &b.path),
+            SortKey::Name => match (a.path.file_name(), b.path.file_name()) {
+                (Some(a), Some(b)) => order.compare_paths(Path::new(a), Path::new(b)),
+                (a, b) => a.cmp(&b),
+            },
+            SortKey::Version => match (&a.version, &b.version) {
+                (Some(a), Some(b)) => a.cmp(b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+            SortKey::Extension => {
+                let extension = |file: &FileInfo| {
+                    file.path
+                        .extension()
+                        .map(|extension| extension.to_string_lossy().to_lowercase())
+                };
+                extension(a).cmp(&extension(b))
+            }
+        }
+    }
+}
+
+/// The direction of a sort.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortDirection {
+    /// Smallest first
+    #[default]
+    Ascending,
+    /// Largest first
+    Descending,
+}
+
+impl SortDirection {
+    /// Applies the direction to an ascending ordering.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::cmp::Ordering;
+    /// use flist::file_lister::SortDirection;
+    ///
+    /// assert_eq!(SortDirection::Descending.apply(Ordering::Less), Ordering::Greater);
+    /// ```
+    pub fn apply(self, ordering: Ordering) -> Ordering {
+        match self {
+            SortDirection::Ascending => ordering,
+            SortDirection::Descending => ordering.reverse(),
+        }
+    }
+}
+
+/// How paths and file names are compared when sorting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PathOrder {
+    /// Character by character, so `file10.dll` comes before `file2.dll`
+    #[default]
+    Lexicographic,
+    /// Runs of digits compared by their numeric value, so `file2.dll` comes
+    /// before `file10.dll`; see [`natural_cmp`]
+    Natural,
+}
+
+impl PathOrder {
+    /// Compares two paths component by component in this order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::cmp::Ordering;
+    /// use std::path::Path;
+    /// use flist::file_lister::PathOrder;
+    ///
+    /// let (a, b) = (Path::new("v2/app.dll"), Path::new("v10/app.dll"));
+    /// assert_eq!(PathOrder::Lexicographic.compare_paths(a, b), Ordering::Greater);
+    /// assert_eq!(PathOrder::Natural.compare_paths(a, b), Ordering::Less);
+    /// ```
+    pub fn compare_paths(self, a: &Path, b: &Path) -> Ordering {
+        match self {
+            PathOrder::Lexicographic => a.cmp(b),
+            PathOrder::Natural => {
+                let mut a_components = a.components();
+                let mut b_components = b.components();
+                loop {
+                    match (a_components.next(), b_components.next()) {
+                        (Some(a), Some(b)) => {
+                            let ordering = natural_cmp(
+                                &a.as_os_str().to_string_lossy(),
+                                &b.as_os_str().to_string_lossy(),
+                            );
+                            if ordering.is_ne() {
+                                return ordering;
+                            }
+                        }
+                        (Some(_), None) => return Ordering::Greater,
+                        (None, Some(_)) => return Ordering::Less,
+                        // Equal apart from the encoding, decide by the raw paths
+                        (None, None) => return a.cmp(b),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Compares two strings in natural order.
+///
+/// The strings are split into runs of ASCII digits and runs of other
+/// characters. Runs of digits are compared by their numeric value, of any
+/// length, all other runs character by character. Strings that only differ in
+/// leading zeros, such as `a01` and `a1`, are ordered character by character so
+/// the order stays total.
+///
+/// # Arguments
+///
+/// * `a` - First string
+/// * `b` - Second string
+///
+/// # Returns
+///
+/// The ordering of `a` relative to `b`.
+///
+/// # Examples
+///
+/// ```
+/// use std::cmp::Ordering;
+/// use flist::file_lister::natural_cmp;
+///
+/// assert_eq!(natural_cmp("file2.dll", "file10.dll"), Ordering::Less);
+/// assert_eq!(natural_cmp("file10", "file9b"), Ordering::Greater);
+/// ```
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_runs = digit_runs(a);
+    let mut b_runs = digit_runs(b);
+    loop {
+        let (a_run, b_run) = match (a_runs.next(), b_runs.next()) {
+            (Some(a_run), Some(b_run)) => (a_run, b_run),
+            (Some(_), None) => return Ordering::Greater,
+            (None, Some(_)) => return Ordering::Less,
+            (None, None) => return a.cmp(b),
+        };
+
+        let digits = |run: &str| run.starts_with(|c: char| c.is_ascii_digit());
+        let ordering = if digits(a_run) && digits(b_run) {
+            // Without leading zeros, a longer number is larger
+            let a_number = a_run.trim_start_matches('0');
+            let b_number = b_run.trim_start_matches('0');
+            a_number
+                .len()
+                .cmp(&b_number.len())
+                .then_with(|| a_number.cmp(b_number))
+        } else {
+            a_run.cmp(b_run)
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+}
+
+/// Splits a string into alternating runs of ASCII digits and other characters.
+fn digit_runs(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        let digit = rest.chars().next()?.is_ascii_digit();
+        let end = rest
+            .find(|c: char| c.is_ascii_digit() != digit)
+            .unwrap_or(rest.len());
+        let (run, tail) = rest.split_at(end);
+        rest = tail;
+        Some(run)
+    })
+}
+
+/// Sorts files by several keys in ascending order.
+///
+/// Files are ordered by the first key, files that are equal by the first key
+/// by the second key, and so on. Unless [`SortKey::Path`] is one of the keys,
+/// files equal by all keys are ordered by path, so the order never depends on
+/// the order the files were found in.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to sort
+/// * `keys` - Sort keys, most significant first
+///
+/// # Returns
+///
+/// The same vector sorted by the keys.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::{FileInfo, SortKey, sort_by_keys};
+///
+/// let files = vec![
+///     FileInfo { path: PathBuf::from("b.dll"), version: Some("1.0".parse().unwrap()), ..Default::default() },
+///     FileInfo { path: PathBuf::from("c.dll"), version: Some("0.9".parse().unwrap()), ..Default::default() },
+///     FileInfo { path: PathBuf::from("a.dll"), version: Some("1.0".parse().unwrap()), ..Default::default() },
+/// ];
+/// let sorted = sort_by_keys(files, &[SortKey::Version, SortKey::Path]);
+/// assert_eq!(sorted[0].path, PathBuf::from("c.dll"));
+/// assert_eq!(sorted[1].path, PathBuf::from("a.dll"));
+/// ```
+pub fn sort_by_keys(files: Vec<FileInfo>, keys: &[SortKey]) -> Vec<FileInfo> {
+    sort_by_keys_with_direction(files, keys, SortDirection::Ascending)
+}
+
+/// Sorts files by several keys in the given direction.
+///
+/// Like [`sort_by_keys`], but [`SortDirection::Descending`] reverses the
+/// comparison of every key, so files without version information come first
+/// when sorting by version. The path used to order files equal by all keys
+/// is reversed as well.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to sort
+/// * `keys` - Sort keys, most significant first
+/// * `direction` - Whether to sort ascending or descending
+///
+/// # Returns
+///
+/// The same vector sorted by the keys.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::{FileInfo, SortDirection, SortKey, sort_by_keys_with_direction};
+///
+/// let files = vec![
+///     FileInfo { path: PathBuf::from("a.dll"), ..Default::default() },
+///     FileInfo { path: PathBuf::from("z.dll"), ..Default::default() },
+/// ];
+/// let sorted = sort_by_keys_with_direction(files, &[SortKey::Path], SortDirection::Descending);
+/// assert_eq!(sorted[0].path, PathBuf::from("z.dll"));
+/// ```
+pub fn sort_by_keys_with_direction(
+    files: Vec<FileInfo>,
+    keys: &[SortKey],
+    direction: SortDirection,
+) -> Vec<FileInfo> {
+    sort_by_keys_with_order(files, keys, direction, PathOrder::Lexicographic)
+}
+
+/// Sorts files by several keys in the given direction, comparing paths and
+/// names in the given order.
+///
+/// Like [`sort_by_keys_with_direction`], but with [`PathOrder::Natural`],
+/// numbers in paths and names are compared by value.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to sort
+/// * `keys` - Sort keys, most significant first
+/// * `direction` - Whether to sort ascending or descending
+/// * `order` - How paths and names are compared
+///
+/// # Returns
+///
+/// The same vector sorted by the keys.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::{FileInfo, PathOrder, SortDirection, SortKey, sort_by_keys_with_order};
+///
+/// let files = vec![
+///     FileInfo { path: PathBuf::from("file10.dll"), ..Default::default() },
+///     FileInfo { path: PathBuf::from("file2.dll"), ..Default::default() },
+/// ];
+/// let sorted = sort_by_keys_with_order(files, &[SortKey::Path], SortDirection::Ascending, PathOrder::Natural);
+/// assert_eq!(sorted[0].path, PathBuf::from("file2.dll"));
+/// ```
+pub fn sort_by_keys_with_order(
+    mut files: Vec<FileInfo>,
+    keys: &[SortKey],
+    direction: SortDirection,
+    order: PathOrder,
+) -> Vec<FileInfo> {
+    // The path breaks ties, so the result is reproducible
+    let tiebreaker = (!keys.contains(&SortKey::Path)).then_some(&SortKey::Path);
+    files.sort_by(|a, b| {
+        keys.iter()
+            .chain(tiebreaker)
+            .map(|key| direction.apply(key.compare_with_order(a, b, order)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+    files
+}
+
+/// Sorts files by path in ascending order.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to sort
+///
+/// # Returns
+///
+/// The same vector sorted by file path.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::{FileInfo, sort_by_path};
+///
+/// let mut files = vec![
+///     FileInfo { path: PathBuf::from("z.dll"), version: None, ..Default::default() },
+///     FileInfo { path: PathBuf::from("a.dll"), version: None, ..Default::default() },
+/// ];
+/// let sorted = sort_by_path(files);
+/// assert_eq!(sorted[0].path, PathBuf::from("a.dll"));
+/// ```
+pub fn sort_by_path(files: Vec<FileInfo>) -> Vec<FileInfo> {
+    sort_by_keys(files, &[SortKey::Path])
+}
+
+/// Sorts files by version in ascending order, oldest first.
+///
+/// Files without version information are grouped last. Files of equal version,
+/// and files without version information, are ordered by path, see
+/// [`sort_by_keys`].
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information to sort
+///
+/// # Returns
+///
+/// The same vector sorted by version.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::{FileInfo, sort_by_version};
+///
+/// let files = vec![
+///     FileInfo { path: PathBuf::from("a.dll"), version: None, ..Default::default() },
+///     FileInfo { path: PathBuf::from("b.dll"), version: Some("2.0".parse().unwrap()), ..Default::default() },
+///     FileInfo { path: PathBuf::from("c.dll"), version: Some("1.5".parse().unwrap()), ..Default::default() },
+/// ];
+/// let sorted = sort_by_version(files);
+/// assert_eq!(sorted[0].path, PathBuf::from("c.dll"));
+/// assert_eq!(sorted[2].path, PathBuf::from("a.dll"));
+/// ```
+pub fn sort_by_version(files: Vec<FileInfo>) -> Vec<FileInfo> {
+    sort_by_keys(files, &[SortKey::Version])
+}
+
+/// Skips files up to and including a given path.
+///
+/// Keeps only files whose path sorts strictly after `after`, using the same
+/// ordering as [`sort_by_path`]. Together with a path-sorted listing this allows
+/// resuming a scan after the last path that was processed. The boundary path
+/// itself is excluded.
+///
+/// `after` is compared with the paths as they were found, e.g. `./sub/a.dll`
+/// when searching `.`, see [`resolve_after_path`] for a path relative to the
+/// search directory. A leading `./` is ignored on both sides, so `sub/a.dll`
+/// and `./sub/a.dll` are the same boundary.
+///
+/// # Arguments
+///
+/// * `files` - Vector of file information, typically sorted by path
+/// * `after` - The last path that was already processed
+///
+/// # Returns
+///
+/// A vector containing only the files after the given path.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::{Path, PathBuf};
+/// use flist::file_lister::{FileInfo, skip_through_path};
+///
+/// let files = vec![
+///     FileInfo { path: PathBuf::from("a.dll"), version: None, ..Default::default() },
+///     FileInfo { path: PathBuf::from("b.dll"), version: None, ..Default::default() },
+///     FileInfo { path: PathBuf::from("c.dll"), version: None, ..Default::default() },
+/// ];
+/// let remaining = skip_through_path(files, Path::new("./b.dll"));
+/// assert_eq!(remaining.len(), 1);
+/// assert_eq!(remaining[0].path, PathBuf::from("c.dll"));
+/// ```
+pub fn skip_through_path(files: Vec<FileInfo>, after: &Path) -> Vec<FileInfo> {
+    // Paths found in the current directory start with `./`, the user's may not
+    let without_cur_dir = |path: &Path| path.strip_prefix(".").unwrap_or(path).to_path_buf();
+    let after = without_cur_dir(after);
+    files
+        .into_iter()
+        .filter(|file_info| without_cur_dir(&file_info.path) > after)
+        .collect()
+}
+
+/// Resolves a `--after-path` boundary against the searched directory.
+///
+/// The found paths start with the search directory, which is absolute when no
+/// directory is given. A relative `after` that does not already start with the
+/// directory is taken relative to it, so `sub/a.dll` names the same file
+/// whether the listing shows `./sub/a.dll` or `/home/me/sub/a.dll`.
+///
+/// # Arguments
+///
+/// * `after` - The boundary path as given by the user
+/// * `directory` - The directory that was searched
+///
+/// # Returns
+///
+/// The path to pass to [`skip_through_path`].
+///
+/// # Examples
+///
+/// ```
+/// use std::path::{Path, PathBuf};
+/// use flist::file_lister::resolve_after_path;
+///
+/// let resolved = resolve_after_path(Path::new("sub/a.dll"), Path::new("/data"));
+/// assert_eq!(resolved, PathBuf::from("/data/sub/a.dll"));
+/// let resolved = resolve_after_path(Path::new("sub/a.dll"), Path::new("."));
+/// assert_eq!(resolved, PathBuf::from("sub/a.dll"));
+/// ```
+pub fn resolve_after_path(after: &Path, directory: &Path) -> PathBuf {
+    let after = after.strip_prefix(".").unwrap_or(after);
+    let directory = directory.strip_prefix(".").unwrap_or(directory);
+    if after.is_absolute() || after.starts_with(directory) {
+        after.to_path_buf()
+    } else {
+        directory.join(after)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_enumerate_files_with_pattern() {
+        // Create a temporary directory with test files
+        let temp_dir = std::env::temp_dir().join("flist_test_enum");
+        let _ = fs::remove_dir_all(&temp_dir); // Clean up if exists
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        // Create test files
+        fs::File::create(temp_dir.join("test1.txt")).unwrap();
+        fs::File::create(temp_dir.join("test2.txt")).unwrap();
+        fs::File::create(temp_dir.join("test.dll")).unwrap();
+        fs::File::create(temp_dir.join("other.exe")).unwrap();
+
+        // Test pattern matching
+        let files = enumerate_files(&temp_dir, "*.txt").unwrap();
+        assert_eq!(files.len(), 2);
+
+        let files = enumerate_files(&temp_dir, "*.dll").unwrap();
+        assert_eq!(files.len(), 1);
+
+        let files = enumerate_files(&temp_dir, "*").unwrap();
+        assert_eq!(files.len(), 4);
+
+        // Clean up
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_enumerate_files_with_patterns_from_file() {
+        let temp_dir = std::env::temp_dir().join("flist_test_pattern_from");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("tree").join("sub")).unwrap();
+
+        let tree = temp_dir.join("tree");
+        fs::File::create(tree.join("app.exe")).unwrap();
+        fs::File::create(tree.join("core.dll")).unwrap();
+        fs::File::create(tree.join("readme.txt")).unwrap();
+        fs::File::create(tree.join("sub").join("kernel.sys")).unwrap();
+        fs::File::create(tree.join("sub").join("core.pdb")).unwrap();
+
+        let patterns_file = temp_dir.join("patterns.txt");
+        fs::write(
+            &patterns_file,
+            "# Binaries to audit\n*.dll\n\n  *.sys  \n# *.txt\ncore.*\n",
+        )
+        .unwrap();
+
+        let patterns = read_patterns_file(&patterns_file).unwrap();
+        assert_eq!(patterns, vec!["*.dll", "*.sys", "core.*"]);
+
+        let dirs_file = temp_dir.join("dirs.txt");
+        fs::write(
+            &dirs_file,
+            "# Roots
+tree
+
+  tree/sub  
+",
+        )
+        .unwrap();
+        assert_eq!(
+            read_directories_file(&dirs_file).unwrap(),
+            vec![PathBuf::from("tree"), PathBuf::from("tree/sub")]
+        );
+        assert!(read_directories_file(&temp_dir.join("missing.txt")).is_err());
+
+        // Path lists from other tools are taken literally
+        let list = "tree/app.exe\r\n\n# not a comment.dll\n  padded.txt\n";
+        assert_eq!(
+            read_path_list(list.as_bytes()).unwrap(),
+            vec![
+                PathBuf::from("tree/app.exe"),
+                PathBuf::from("# not a comment.dll"),
+                PathBuf::from("  padded.txt"),
+            ]
+        );
+
+        // core.dll matches two patterns but is only reported once
+        let patterns: Vec<&str> = patterns.iter().map(String::as_str).collect();
+        let mut files = enumerate_files_with_patterns(&tree, &patterns).unwrap();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                tree.join("core.dll"),
+                tree.join("sub").join("core.pdb"),
+                tree.join("sub").join("kernel.sys"),
+            ]
+        );
+
+        // Inline patterns combine with the file's patterns
+        let files = enumerate_files_with_patterns(&tree, &["*.exe", "*.dll"]).unwrap();
+        assert_eq!(files.len(), 2);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_direct_file_path() {
+        let temp_dir = std::env::temp_dir().join("flist_test_direct");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        let file = temp_dir.join("foo.dll");
+        fs::File::create(&file).unwrap();
+        let file_str = file.to_str().unwrap();
+
+        // Existing file without glob characters is a direct lookup
+        assert_eq!(direct_file_path(file_str), Some(file.clone()));
+
+        // A bare name is a pattern, even if a file of that name exists in the
+        // current directory (the crate root while testing)
+        assert_eq!(direct_file_path("Cargo.toml"), None);
+        assert_eq!(
+            direct_file_path("./Cargo.toml"),
+            Some(PathBuf::from("./Cargo.toml"))
+        );
+
+        // Glob patterns and directories fall back to enumeration
+        assert_eq!(direct_file_path("*.dll"), None);
+        assert_eq!(direct_file_path(&file_str.replace("foo", "fo?")), None);
+        assert_eq!(direct_file_path(temp_dir.to_str().unwrap()), None);
+
+        // Nonexistent paths are treated as patterns
+        assert_eq!(
+            direct_file_path(temp_dir.join("missing.dll").to_str().unwrap()),
+            None
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_filter_excluded_paths() {
+        let paths = vec![
+            PathBuf::from("./app.dll"),
+            PathBuf::from("./x.dll"),
+            PathBuf::from("obj/Debug/app.dll"),
+            PathBuf::from("/opt/app/lib.DLL"),
+        ];
+
+        let kept = filter_excluded_paths(paths.clone(), &["x.dll", "obj"], false).unwrap();
+        assert_eq!(kept, vec![paths[0].clone(), paths[3].clone()]);
+        let kept = filter_excluded_paths(paths.clone(), &["*.dll"], true).unwrap();
+        assert!(kept.is_empty());
+        assert_eq!(
+            filter_excluded_paths(paths.clone(), &[], false).unwrap(),
+            paths
+        );
+        assert!(filter_excluded_paths(paths, &["[invalid"], false).is_err());
+    }
+
+    #[test]
+    fn test_collect_file_info_without_version() {
+        let paths = vec![PathBuf::from("test1.txt"), PathBuf::from("test2.txt")];
+
+        let file_infos = collect_file_info(paths, false, None);
+        assert_eq!(file_infos.len(), 2);
+        assert!(file_infos[0].version.is_none());
+        assert!(file_infos[1].version.is_none());
+    }
+
+    #[test]
+    fn test_filter_by_version() {
+        let files = vec![
+            FileInfo {
+                path: PathBuf::from("file1.dll"),
+                version: Some("1.0.0.0".parse().unwrap()),
+                ..Default::default()
+            },
+            FileInfo {
+                path: PathBuf::from("file2.dll"),
+                version: Some("2.0.0.0".parse().unwrap()),
+                ..Default::default()
+            },
+            FileInfo {
+                path: PathBuf::from("file3.dll"),
+                version: Some("3.0.0.0".parse().unwrap()),
+                ..Default::default()
+            },
+            FileInfo {
+                path: PathBuf::from("file4.dll"),
+                version: None,
+                ..Default::default()
+            },
+        ];
+
+        // Filter with min version
+        let filtered = filter_by_version(files.clone(), Some("2.0.0.0".parse().unwrap()), None);
+        assert_eq!(filtered.len(), 2); // 2.0.0.0 and 3.0.0.0
+
+        // Filter with max version
+        let filtered = filter_by_version(files.clone(), None, Some("2.0.0.0".parse().unwrap()));
+        assert_eq!(filtered.len(), 2); // 1.0.0.0 and 2.0.0.0
+
+        // Filter with both min and max
+        let filtered = filter_by_version(
+            files.clone(),
+            Some("1.5.0.0".parse().unwrap()),
+            Some("2.5.0.0".parse().unwrap()),
+        );
+        assert_eq!(filtered.len(), 1); // Only 2.0.0.0
+
+        // Files without version are excluded
+        let filtered = filter_by_version(files.clone(), Some("0.0.0.0".parse().unwrap()), None);
+        assert_eq!(filtered.len(), 3); // file4.dll is excluded
+    }
+
+    #[test]
+    fn test_filter_older_than() {
+        let files = vec![
+            FileInfo {
+                path: PathBuf::from("a.dll"),
+                version: Some("1.0.0.0".parse().unwrap()),
+                ..Default::default()
+            },
+            FileInfo {
+                path: PathBuf::from("b.dll"),
+                version: Some("1.9.9.9".parse().unwrap()),
+                ..Default::default()
+            },
+            FileInfo {
+                path: PathBuf::from("c.dll"),
+                version: Some("2.0.0.0".parse().unwrap()),
+                ..Default::default()
+            },
+            FileInfo {
+                path: PathBuf::from("d.dll"),
+                version: Some("3.0.0.0".parse().unwrap()),
+                ..Default::default()
+            },
+            FileInfo {
+                path: PathBuf::from("e.dll"),
+                version: None,
+                ..Default::default()
+            },
+        ];
+
+        let offenders = filter_older_than(files, "2.0.0.0".parse().unwrap());
+        let paths: Vec<_> = offenders.iter().map(|f| f.path.clone()).collect();
+        // Equal versions are not offenders, versionless files are excluded
+        assert_eq!(paths, vec![PathBuf::from("a.dll"), PathBuf::from("b.dll")]);
+    }
+
+    #[test]
+    fn test_filter_bad_checksum() {
+        let files = vec![
+            FileInfo {
+                path: PathBuf::from("ok.dll"),
+                checksum_ok: Some(true),
+                ..Default::default()
+            },
+            FileInfo {
+                path: PathBuf::from("tampered.dll"),
+                checksum_ok: Some(false),
+                ..Default::default()
+            },
+            FileInfo {
+                path: PathBuf::from("not_set.dll"),
+                checksum_ok: None,
+                ..Default::default()
+            },
+        ];
+
+        let bad = filter_bad_checksum(files);
+        assert_eq!(bad.len(), 1);
+        assert_eq!(bad[0].path, PathBuf::from("tampered.dll"));
+    }
+
+    #[test]
+    fn test_collect_content_hash() {
+        let temp_dir = std::env::temp_dir().join("flist_test_content_hash");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        std::fs::write(temp_dir.join("a.dll"), b"same contents").unwrap();
+        std::fs::write(temp_dir.join("b.dll"), b"same contents").unwrap();
+        std::fs::write(temp_dir.join("c.dll"), b"other contents").unwrap();
+
+        let paths = ["a.dll", "b.dll", "c.dll", "missing.dll"]
+            .map(|name| temp_dir.join(name))
+            .to_vec();
+        let options = CollectOptions {
+            compute_hash: true,
+            read_buffer_size: Some(3),
+            ..Default::default()
+        };
+        let report = collect_file_info_report(paths, &options);
+        let hashes: Vec<_> = report.files.iter().map(|f| f.hash.clone()).collect();
+        assert_eq!(hashes.len(), 3);
+        assert_eq!(hashes[0], hashes[1]);
+        assert_ne!(hashes[0], hashes[2]);
+        assert_eq!(hashes[0].as_ref().unwrap().len(), 64);
+        assert_eq!(
+            hashes[0].as_deref(),
+            Some(blake3::hash(b"same contents").to_hex().as_str())
+        );
+
+        // Hashes are only computed on request
+        let report = collect_file_info_report(vec![temp_dir.join("a.dll")], &Default::default());
+        assert_eq!(report.files[0].hash, None);
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_filter_mismatched_versions() {
+        let file = |name: &str, version: Option<&str>, product_version: Option<&str>| FileInfo {
+            path: PathBuf::from(name),
+            version: version.map(|v| v.parse().unwrap()),
+            product_version: product_version.map(|v| v.parse().unwrap()),
+            ..Default::default()
+        };
+        let files = vec![
+            file("same.dll", Some("1.2.3.4"), Some("1.2.3.4")),
+            file("mislabeled.dll", Some("1.2.3.4"), Some("1.2.3.5")),
+            // Omitted parts are zero, so these agree
+            file("short.dll", Some("2.0.0.0"), Some("2")),
+            file("no_product.dll", Some("1.0.0.0"), None),
+            file("no_file.dll", None, Some("1.0.0.0")),
+            file("none.txt", None, None),
+        ];
+
+        let mismatched = filter_mismatched_versions(files);
+        assert_eq!(mismatched.len(), 1);
+        assert_eq!(mismatched[0].path, PathBuf::from("mislabeled.dll"));
+    }
+
+    #[test]
+    fn test_filter_by_has_version() {
+        let file = |name: &str, version: Option<&str>| FileInfo {
+            path: PathBuf::from(name),
+            version: version.map(|v| v.parse().unwrap()),
+            ..Default::default()
+        };
+        let files = vec![
+            file("app.dll", Some("1.2.3.4")),
+            file("tool.exe", None),
+            file("zero.dll", Some("0.0.0.0")),
+            file("readme.txt", None),
+        ];
+        let paths =
+            |files: Vec<FileInfo>| -> Vec<PathBuf> { files.into_iter().map(|f| f.path).collect() };
+
+        assert_eq!(
+            paths(filter_by_has_version(files.clone(), true)),
+            vec![PathBuf::from("app.dll"), PathBuf::from("zero.dll")]
+        );
+        assert_eq!(
+            paths(filter_by_has_version(files, false)),
+            vec![PathBuf::from("tool.exe"), PathBuf::from("readme.txt")]
+        );
+    }
+
+    #[test]
+    fn test_filter_by_extensions() {
+        let paths = vec![
+            PathBuf::from("bin").join("app.exe"),
+            PathBuf::from("bin").join("Core.DLL"),
+            PathBuf::from("bin").join("kernel.sys"),
+            PathBuf::from("bin").join("archive.tar.gz"),
+            PathBuf::from("bin").join("Makefile"),
+            PathBuf::from("bin").join(".dll"),
+        ];
+        let extensions = vec!["dll".to_string(), "SYS".to_string(), ".gz".to_string()];
+        assert_eq!(
+            filter_by_extensions(paths.clone(), &extensions),
+            vec![
+                PathBuf::from("bin").join("Core.DLL"),
+                PathBuf::from("bin").join("kernel.sys"),
+                PathBuf::from("bin").join("archive.tar.gz"),
+            ]
+        );
+        assert!(filter_by_extensions(paths, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_filter_by_arch() {
+        let file = |name: &str, arch: Option<PeArch>| FileInfo {
+            path: PathBuf::from(name),
+            arch,
+            ..Default::default()
+        };
+        let files = vec![
+            file("x86.dll", Some(PeArch::X86)),
+            file("x64.dll", Some(PeArch::X64)),
+            file("arm64.dll", Some(PeArch::Arm64)),
+            file("other.dll", Some(PeArch::Other(0x0200))),
+            file("readme.txt", None),
+        ];
+
+        let x86 = filter_by_arch(files.clone(), PeArch::X86);
+        assert_eq!(x86.len(), 1);
+        assert_eq!(x86[0].path, PathBuf::from("x86.dll"));
+        assert_eq!(filter_by_arch(files.clone(), PeArch::Arm64).len(), 1);
+        assert!(filter_by_arch(files, PeArch::Arm).is_empty());
+    }
+
+    #[test]
+    fn test_filter_by_semantic_version() {
+        let file = |name: &str, version: Option<&str>| FileInfo {
+            path: PathBuf::from(name),
+            semantic_version: version.map(|version| version.parse().unwrap()),
+            ..Default::default()
+        };
+        let files = vec![
+            file("alpha.dll", Some("1.2.3-alpha")),
+            file("beta.dll", Some("1.2.3-beta.2")),
+            file("release.dll", Some("1.2.3")),
+            file("next.dll", Some("1.3.0-rc.1")),
+            file("noversion.dll", None),
+        ];
+        let names = |files: Vec<FileInfo>| {
+            files
+                .into_iter()
+                .map(|file| file.path.display().to_string())
+                .collect::<Vec<_>>()
+        };
+        let version = |spec: &str| spec.parse::<SemanticVersion>().unwrap();
+
+        assert_eq!(
+            names(filter_by_semantic_version(
+                files.clone(),
+                Some(&version("1.2.3-beta")),
+                None
+            )),
+            vec!["beta.dll", "release.dll", "next.dll"]
+        );
+        assert_eq!(
+            names(filter_by_semantic_version(
+                files.clone(),
+                None,
+                Some(&version("1.2.3-rc"))
+            )),
+            vec!["alpha.dll", "beta.dll"]
+        );
+        assert_eq!(
+            names(filter_by_semantic_version(
+                files.clone(),
+                Some(&version("1.2.3")),
+                Some(&version("1.3.0"))
+            )),
+            vec!["release.dll", "next.dll"]
+        );
+        assert_eq!(filter_by_semantic_version(files, None, None).len(), 4);
+    }
+
+    #[test]
+    fn test_filter_unsigned() {
+        let file = |name: &str, signed: Option<bool>| FileInfo {
+            path: PathBuf::from(name),
+            signed,
+            ..Default::default()
+        };
+        let files = vec![
+            file("signed.dll", Some(true)),
+            file("unsigned.dll", Some(false)),
+            file("readme.txt", None),
+        ];
+
+        let unsigned = filter_unsigned(files);
+        assert_eq!(unsigned.len(), 1);
+        assert_eq!(unsigned[0].path, PathBuf::from("unsigned.dll"));
+    }
+
+    #[test]
+    fn test_enumerate_and_collect_matching_dirs() {
+        let temp_dir = std::env::temp_dir().join("flist_test_match_dirs");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("app_cache").join("nested_cache")).unwrap();
+        fs::create_dir_all(temp_dir.join("other")).unwrap();
+        fs::write(temp_dir.join("other").join("cache.db"), "db").unwrap();
+        fs::write(temp_dir.join("app_cache").join("data.bin"), "bin").unwrap();
+
+        // Directories are only listed on request
+        let files = enumerate_files(&temp_dir, "*cache*").unwrap();
+        assert_eq!(files, vec![temp_dir.join("other").join("cache.db")]);
+
+        let options = EnumerateOptions {
+            include_dirs: true,
+            ..Default::default()
+        };
+        let mut entries = enumerate_files_with_options(&temp_dir, &["*cache*"], &options).unwrap();
+        entries.sort();
+        assert_eq!(
+            entries,
+            vec![
+                temp_dir.join("app_cache"),
+                temp_dir.join("app_cache").join("nested_cache"),
+                temp_dir.join("other").join("cache.db"),
+            ]
+        );
+
+        // The search directory itself never matches
+        let entries = enumerate_files_with_options(&temp_dir, &["flist_test_*"], &options).unwrap();
+        assert!(entries.is_empty());
+
+        // Directories are marked and not read
+        let options = CollectOptions {
+            include_version: true,
+            include_dirs: true,
+            ..Default::default()
+        };
+        let infos = collect_file_info_with_options(
+            vec![
+                temp_dir.join("app_cache"),
+                temp_dir.join("other").join("cache.db"),
+            ],
+            &options,
+        );
+        assert!(infos[0].is_dir);
+        assert_eq!(infos[0].version, None);
+        assert!(!infos[1].is_dir);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_collect_in_parallel_keeps_order() {
+        let temp_dir = std::env::temp_dir().join("flist_test_parallel");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        let mut files: Vec<PathBuf> = (0..200)
+            .map(|i| temp_dir.join(format!("file{:03}.dll", i)))
+            .collect();
+        for file in &files {
+            fs::write(file, "not a PE file").unwrap();
+        }
+        files.reverse();
+
+        for threads in [None, Some(1), Some(4)] {
+            let options = CollectOptions {
+                include_version: true,
+                max_open_files: Some(2),
+                threads,
+                ..Default::default()
+            };
+            let report = collect_file_info_report(files.clone(), &options);
+            let paths: Vec<PathBuf> = report.files.into_iter().map(|f| f.path).collect();
+            assert_eq!(paths, files);
+        }
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_collect_drops_files_vanished_during_scan() {
+        let temp_dir = std::env::temp_dir().join("flist_test_vanished");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        fs::write(temp_dir.join("kept.dll"), "kept").unwrap();
+        fs::write(temp_dir.join("gone.dll"), "gone").unwrap();
+
+        // Both files exist at enumeration time, one is deleted before reading
+        let mut files = enumerate_files(&temp_dir, "*.dll").unwrap();
+        files.sort();
+        assert_eq!(files.len(), 2);
+        fs::remove_file(temp_dir.join("gone.dll")).unwrap();
+
+        let options = CollectOptions {
+            include_version: true,
+            ..Default::default()
+        };
+        let report = collect_file_info_report(files.clone(), &options);
+        assert_eq!(report.files.len(), 1);
+        assert_eq!(report.files[0].path, temp_dir.join("kept.dll"));
+        assert_eq!(report.vanished, vec![temp_dir.join("gone.dll")]);
+
+        // Without reading the files, nothing is checked
+        let report = collect_file_info_report(files, &CollectOptions::default());
+        assert_eq!(report.files.len(), 2);
+        assert!(report.vanished.is_empty());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_enumerate_and_collect_record_progress() {
+        let temp_dir = std::env::temp_dir().join("flist_test_progress");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        for name in ["a.dll", "b.dll", "c.txt"] {
+            fs::write(temp_dir.join(name), name).unwrap();
+        }
+
+        let progress = Arc::new(ScanProgress::default());
+        let enumerate_options = EnumerateOptions {
+            progress: Some(Arc::clone(&progress)),
+            ..Default::default()
+        };
+        let files =
+            enumerate_files_with_options(&temp_dir, &["*.dll"], &enumerate_options).unwrap();
+        assert_eq!(
+            progress.message(),
+            "processed 0 of 2 files (0 versions read)..."
+        );
+
+        let collect_options = CollectOptions {
+            include_version: true,
+            progress: Some(Arc::clone(&progress)),
+            ..Default::default()
+        };
+        collect_file_info_with_options(files, &collect_options);
+        assert_eq!(
+            progress.message(),
+            "processed 2 of 2 files (0 versions read)..."
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_name_matcher_glob_on_file_name() {
+        let matcher = NameMatcher::new(&["*.dll"]).unwrap();
+
+        // Only the file name is matched, the directory is ignored
+        assert!(matcher.matches_path(Path::new("core.dll")));
+        assert!(matcher.matches_path(Path::new("some/dir.dll/core.dll")));
+        assert!(!matcher.matches_path(Path::new("lib.dll/readme.txt")));
+        assert_eq!(
+            matcher.match_subject(Path::new("a/b/c.dll")).as_deref(),
+            Some("c.dll")
+        );
+
+        // Glob syntax
+        let matcher = NameMatcher::new(&["app?.exe", "[ab]*.txt"]).unwrap();
+        assert!(matcher.matches_path(Path::new("app1.exe")));
+        assert!(!matcher.matches_path(Path::new("app12.exe")));
+        assert!(matcher.matches_path(Path::new("b-notes.txt")));
+        assert!(!matcher.matches_path(Path::new("c-notes.txt")));
+    }
+
+    #[test]
+    fn test_name_matcher_is_case_sensitive() {
+        let matcher = NameMatcher::new(&["*.dll"]).unwrap();
+        assert!(!matcher.matches_path(Path::new("CORE.DLL")));
+        assert_eq!(matcher.describe_mode(), "glob on file name, case-sensitive");
+    }
+
+    #[test]
+    fn test_name_matcher_ignore_case() {
+        let matcher = NameMatcher::new(&["*.TXT"]).unwrap();
+        assert!(!matcher.matches_path(Path::new("file.txt")));
+
+        let matcher = matcher.ignore_case(true);
+        assert!(matcher.matches_path(Path::new("file.txt")));
+        assert!(matcher.matches_path(Path::new("FILE.Txt")));
+        assert!(!matcher.matches_path(Path::new("file.md")));
+        assert_eq!(
+            matcher.describe_mode(),
+            "glob on file name, case-insensitive"
+        );
+    }
+
+    #[test]
+    fn test_expand_braces() {
+        let expand = |pattern: &str| -> Vec<String> {
+            expand_pattern(pattern)
+                .unwrap()
+                .iter()
+                .map(|p| p.as_str().to_string())
+                .collect()
+        };
+
+        assert_eq!(expand("*.dll"), ["*.dll"]);
+        assert_eq!(expand("*.{dll,exe,sys}"), ["*.dll", "*.exe", "*.sys"]);
+        assert_eq!(expand("file{,.bak}"), ["file", "file.bak"]);
+        assert_eq!(expand("a{}b"), ["ab"]);
+        assert_eq!(expand("{a,b}{1,2}"), ["a1", "a2", "b1", "b2"]);
+        assert_eq!(
+            expand("lib{core{,32},ui}.dll"),
+            ["libcore.dll", "libcore32.dll", "libui.dll"]
+        );
+
+        // Braces in character classes are literal
+        assert_eq!(expand("[{]*"), ["[{]*"]);
+        assert_eq!(expand("x[}]{a,b}"), ["x[}]a", "x[}]b"]);
+
+        // Unbalanced braces are reported like other syntax errors
+        let error = expand_pattern("*.{dll,exe").unwrap_err().to_string();
+        assert_eq!(error, "Pattern syntax error near position 2: unmatched '{'");
+        assert!(expand_pattern("*.dll}").is_err());
+        assert!(expand_pattern("{a,[b}").is_err());
+    }
+
+    #[test]
+    fn test_name_matcher_expands_braces() {
+        let matcher = NameMatcher::new(&["*.{dll,exe}"]).unwrap();
+        assert!(matcher.matches_path(Path::new("core.dll")));
+        assert!(matcher.matches_path(Path::new("app.exe")));
+        assert!(!matcher.matches_path(Path::new("readme.txt")));
+        assert!(NameMatcher::new(&["*.{dll"]).is_err());
+    }
+
+    #[test]
+    fn test_name_matcher_full_path() {
+        let root = Path::new("repo");
+        let matcher = NameMatcher::new(&["src/**/*.rs", "*.toml"])
+            .unwrap()
+            .full_path(root);
+        assert_eq!(
+            matcher.describe_mode(),
+            "glob on relative path, case-sensitive"
+        );
+
+        // Separators are normalized, and the subject is relative to the root
+        let path = root.join("src").join("cli").join("args.rs");
+        assert_eq!(
+            matcher.match_subject(&path).as_deref(),
+            Some("src/cli/args.rs")
+        );
+        assert!(matcher.matches_path(&path));
+        assert!(matcher.matches_path(&root.join("src").join("main.rs")));
+        assert!(matcher.matches_path(&root.join("Cargo.toml")));
+
+        // * does not cross directories, and patterns are anchored at the root
+        assert!(!matcher.matches_path(&root.join("crates").join("Cargo.toml")));
+        assert!(!matcher.matches_path(&root.join("tests").join("src").join("a.rs")));
+        assert!(!matcher.matches_path(&root.join("src").join("readme.md")));
+        assert_eq!(matcher.match_subject(root), None);
+    }
+
+    #[test]
+    fn test_name_matcher_full_path_ignore_case() {
+        let root = Path::new("Repo");
+        let path = root.join("Src").join("CLI").join("Args.RS");
+        let matcher = NameMatcher::new(&["src/**/*.rs"]).unwrap().full_path(root);
+        assert!(!matcher.matches_path(&path));
+
+        // Case is ignored in the directories too, the separators stay literal
+        let matcher = matcher.ignore_case(true);
+        assert_eq!(
+            matcher.describe_mode(),
+            "glob on relative path, case-insensitive"
+        );
+        assert!(matcher.matches_path(&path));
+        assert!(matcher.matches_path(&root.join("SRC").join("Main.rs")));
+        assert!(!matcher.matches_path(&root.join("Tests").join("src").join("a.rs")));
+        let matcher = NameMatcher::new(&["SRC/*.RS"])
+            .unwrap()
+            .full_path(root)
+            .ignore_case(true);
+        assert!(matcher.matches_path(&root.join("src").join("main.rs")));
+        assert!(!matcher.matches_path(&path));
+    }
+
+    #[test]
+    fn test_enumerate_files_with_errors() {
+        let temp_dir = std::env::temp_dir().join("flist_test_walk_errors");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        std::fs::write(temp_dir.join("app.dll"), b"").unwrap();
+
+        // A readable tree has matches and no errors
+        let (files, errors) =
+            enumerate_files_with_errors(&temp_dir, &["*.dll"], &EnumerateOptions::default())
+                .unwrap();
+        assert_eq!(files, vec![temp_dir.join("app.dll")]);
+        assert!(errors.is_empty());
+
+        // A missing directory is reported with its path and error kind, with both walks
+        let missing = temp_dir.join("missing");
+        for use_gitignore in [false, true] {
+            let options = EnumerateOptions {
+                use_gitignore,
+                ..Default::default()
+            };
+            let (files, errors) = enumerate_files_with_errors(&missing, &["*"], &options).unwrap();
+            assert!(files.is_empty());
+            assert_eq!(errors.len(), 1, "use_gitignore: {}", use_gitignore);
+            assert_eq!(errors[0].path.as_deref(), Some(missing.as_path()));
+            assert_eq!(errors[0].kind, Some(std::io::ErrorKind::NotFound));
+            assert!(
+                errors[0]
+                    .to_string()
+                    .starts_with(&missing.display().to_string())
+            );
+        }
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_enumerate_files_follow_symlinks() {
+        let temp_dir = std::env::temp_dir().join("flist_test_follow_symlinks");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        let sub = temp_dir.join("sub");
+        std::fs::create_dir_all(&sub).unwrap();
+        std::fs::write(sub.join("app.dll"), b"").unwrap();
+        // A link back to the search directory would repeat the tree forever
+        std::os::unix::fs::symlink(&temp_dir, sub.join("up")).unwrap();
+        std::os::unix::fs::symlink(sub.join("app.dll"), temp_dir.join("link.dll")).unwrap();
+
+        let (mut files, errors) =
+            enumerate_files_with_errors(&temp_dir, &["*.dll"], &EnumerateOptions::default())
+                .unwrap();
+        files.sort();
+        assert_eq!(files, vec![sub.join("app.dll")]);
+        assert!(errors.is_empty());
+
+        for use_gitignore in [false, true] {
+            let options = EnumerateOptions {
+                follow_symlinks: true,
+                use_gitignore,
+                ..Default::default()
+            };
+            let (mut files, errors) =
+                enumerate_files_with_errors(&temp_dir, &["*.dll"], &options).unwrap();
+            files.sort();
+            assert_eq!(files, vec![temp_dir.join("link.dll"), sub.join("app.dll")]);
+            assert_eq!(errors.len(), 1, "use_gitignore: {}", use_gitignore);
+            assert!(errors[0].is_loop);
+            assert_eq!(errors[0].path.as_deref(), Some(sub.join("up").as_path()));
+        }
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_enumerate_files_full_path() {
+        let temp_dir = std::env::temp_dir().join("flist_test_full_path");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("src").join("cli")).unwrap();
+        fs::create_dir_all(temp_dir.join("target")).unwrap();
+        fs::File::create(temp_dir.join("build.rs")).unwrap();
+        fs::File::create(temp_dir.join("src").join("main.rs")).unwrap();
+        fs::File::create(temp_dir.join("src").join("cli").join("args.rs")).unwrap();
+        fs::File::create(temp_dir.join("target").join("gen.rs")).unwrap();
+
+        let options = EnumerateOptions {
+            full_path: true,
+            ..Default::default()
+        };
+        let mut files =
+            enumerate_files_with_options(&temp_dir, &["src/**/*.rs"], &options).unwrap();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                temp_dir.join("src").join("cli").join("args.rs"),
+                temp_dir.join("src").join("main.rs"),
+            ]
+        );
+
+        // By default only names are matched, so no name contains a separator
+        let files =
+            enumerate_files_with_options(&temp_dir, &["src/**/*.rs"], &EnumerateOptions::default())
+                .unwrap();
+        assert!(files.is_empty());
+
+        // Ignoring case applies to the directories of the relative path as well
+        let options = EnumerateOptions {
+            full_path: true,
+            ignore_case: true,
+            ..Default::default()
+        };
+        let mut files =
+            enumerate_files_with_options(&temp_dir, &["SRC/**/*.RS"], &options).unwrap();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                temp_dir.join("src").join("cli").join("args.rs"),
+                temp_dir.join("src").join("main.rs"),
+            ]
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_enumerate_files_ignore_case() {
+        let temp_dir = std::env::temp_dir().join("flist_test_ignore_case");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("BUILD")).unwrap();
+        fs::File::create(temp_dir.join("file.txt")).unwrap();
+        fs::File::create(temp_dir.join("BUILD").join("out.txt")).unwrap();
+
+        let patterns = ["*.TXT"];
+        let mut options = EnumerateOptions::default();
+        assert!(
+            enumerate_files_with_options(&temp_dir, &patterns, &options)
+                .unwrap()
+                .is_empty()
+        );
+
+        options.ignore_case = true;
+        assert_eq!(
+            enumerate_files_with_options(&temp_dir, &patterns, &options)
+                .unwrap()
+                .len(),
+            2
+        );
+
+        // Excludes ignore case as well
+        options.exclude = vec!["build".to_string()];
+        assert_eq!(
+            enumerate_files_with_options(&temp_dir, &patterns, &options).unwrap(),
+            vec![temp_dir.join("file.txt")]
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_enumerate_files_with_flistignore() {
+        let temp_dir = std::env::temp_dir().join("flist_test_flistignore");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("obj")).unwrap();
+        fs::write(
+            temp_dir.join(FLISTIGNORE_FILE),
+            "# Build output\nobj\n\n  *.pdb \n",
+        )
+        .unwrap();
+        for file in ["app.dll", "app.pdb", "core.dll", "obj/app.dll"] {
+            fs::write(temp_dir.join(file), "").unwrap();
+        }
+
+        assert_eq!(read_flistignore(&temp_dir).unwrap(), vec!["obj", "*.pdb"]);
+        assert!(read_flistignore(&temp_dir.join("obj")).unwrap().is_empty());
+
+        // Not used unless requested
+        let mut options = EnumerateOptions::default();
+        let all = enumerate_files_with_options(&temp_dir, &["*.dll", "*.pdb"], &options).unwrap();
+        assert_eq!(all.len(), 4);
+
+        // Combined with the command line excludes
+        options.use_flistignore = true;
+        options.exclude = vec!["core*".to_string()];
+        let kept = enumerate_files_with_options(&temp_dir, &["*.dll", "*.pdb"], &options).unwrap();
+        assert_eq!(kept, vec![temp_dir.join("app.dll")]);
+
+        fs::write(temp_dir.join(FLISTIGNORE_FILE), "[\n").unwrap();
+        let error = enumerate_files_with_options(&temp_dir, &["*"], &options).unwrap_err();
+        assert!(error.to_string().contains(FLISTIGNORE_FILE));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_enumerate_files_with_gitignore() {
+        let temp_dir = std::env::temp_dir().join("flist_test_gitignore");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("target")).unwrap();
+        fs::create_dir_all(temp_dir.join("src").join("gen")).unwrap();
+
+        fs::write(temp_dir.join(".gitignore"), "target/\n*.log\n").unwrap();
+        fs::write(temp_dir.join("src").join(".gitignore"), "gen/\n").unwrap();
+        fs::write(temp_dir.join(".ignore"), "notes.txt\n").unwrap();
+        for file in [
+            "build.rs",
+            "build.log",
+            "notes.txt",
+            ".hidden.rs",
+            "target/app.rs",
+            "src/main.rs",
+            "src/gen/out.rs",
+        ] {
+            fs::write(temp_dir.join(file), "").unwrap();
+        }
+
+        let mut options = EnumerateOptions::default();
+        let mut all = enumerate_files_with_options(&temp_dir, &["*"], &options).unwrap();
+        all.sort();
+        assert_eq!(all.len(), 10);
+
+        options.use_gitignore = true;
+        let mut kept = enumerate_files_with_options(&temp_dir, &["*"], &options).unwrap();
+        kept.sort();
+        let expected: Vec<PathBuf> = [
+            ".gitignore",
+            ".hidden.rs",
+            ".ignore",
+            "build.rs",
+            "src/.gitignore",
+            "src/main.rs",
+        ]
+        .iter()
+        .map(|file| temp_dir.join(file))
+        .collect();
+        assert_eq!(kept, expected);
+
+        // Depth and excludes work the same way
+        options.max_depth = Some(1);
+        options.exclude = vec![".*".to_string()];
+        let kept = enumerate_files_with_options(&temp_dir, &["*"], &options).unwrap();
+        assert_eq!(kept, vec![temp_dir.join("build.rs")]);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_name_matcher_invalid_pattern() {
+        assert!(NameMatcher::new(&["[unclosed"]).is_err());
+    }
+
+    #[test]
+    fn test_partial_versions_mean_zero_filled() {
+        // The same policy applies across parsing, filtering, comparing and display:
+        // a version part that is not given is zero
+        let file = |path: &str, version: &str| FileInfo {
+            path: PathBuf::from(path),
+            version: Some(version.parse().unwrap()),
+            ..Default::default()
+        };
+        let files = vec![
+            file("a/x.dll", "1.2.0.0"),
+            file("b/x.dll", "1.2"),
+            file("c/x.dll", "1.2.0.1"),
+        ];
+        let v = |version: &str| Some(version.parse::<FileVersion>().unwrap());
 
-        // Filter with max version
-        let filtered = filter_by_version(files.clone(), None, Some("2.0.0.0".parse().unwrap()));
-        assert_eq!(filtered.len(), 2); // 1.0.0.0 and 2.0.0.0
+        // --minv 1.2 and --maxv 1.2 both mean 1.2.0.0
+        let filtered = filter_by_version(files.clone(), v("1.2"), v("1.2"));
+        let paths: Vec<_> = filtered.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("a/x.dll"), PathBuf::from("b/x.dll")]
+        );
 
-        // Filter with both min and max
-        let filtered = filter_by_version(
-            files.clone(),
-            Some("1.5.0.0".parse().unwrap()),
-            Some("2.5.0.0".parse().unwrap()),
+        // 1.2 is not older than 1.2.0.0
+        assert_eq!(
+            filter_older_than(files.clone(), v("1.2.0.1").unwrap()).len(),
+            2
         );
-        assert_eq!(filtered.len(), 1); // Only 2.0.0.0
+        assert!(filter_older_than(files.clone(), v("1.2.0.0").unwrap()).is_empty());
 
-        // Files without version are excluded
-        let filtered = filter_by_version(files.clone(), Some("0.0.0.0".parse().unwrap()), None);
-        assert_eq!(filtered.len(), 3); // file4.dll is excluded
+        // Equal versions are no regression
+        assert!(find_version_regressions(&files[..2]).is_empty());
+
+        // An excluded 1.2.0.0 also removes the file reported as 1.2
+        let remaining = filter_excluded_versions(files.clone(), &["1.2.0.0".parse().unwrap()]);
+        assert_eq!(remaining.len(), 1);
+
+        // Displayed zero-filled
+        assert_eq!(files[1].version.unwrap().to_string(), "1.2.0.0");
+    }
+
+    #[test]
+    fn test_filter_by_created() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let at = |seconds: u64| UNIX_EPOCH + Duration::from_secs(seconds);
+        let file = |path: &str, created: Option<SystemTime>| FileInfo {
+            path: PathBuf::from(path),
+            created,
+            ..Default::default()
+        };
+        let files = vec![
+            file("a", Some(at(100))),
+            file("b", Some(at(200))),
+            file("c", Some(at(300))),
+            file("unknown", None),
+        ];
+        let paths =
+            |files: Vec<FileInfo>| -> Vec<PathBuf> { files.into_iter().map(|f| f.path).collect() };
+
+        // Inclusive on both ends, files without creation time are dropped
+        assert_eq!(
+            paths(filter_by_created(
+                files.clone(),
+                Some(at(200)),
+                Some(at(300))
+            )),
+            vec![PathBuf::from("b"), PathBuf::from("c")]
+        );
+        assert_eq!(
+            paths(filter_by_created(files.clone(), None, Some(at(100)))),
+            vec![PathBuf::from("a")]
+        );
+        assert_eq!(filter_by_created(files, None, None).len(), 3);
+    }
+
+    #[test]
+    fn test_collect_reads_created_time() {
+        let temp_file = std::env::temp_dir().join("flist_test_created.txt");
+        fs::write(&temp_file, "created").unwrap();
+
+        let options = CollectOptions {
+            read_created: true,
+            ..Default::default()
+        };
+        let infos = collect_file_info_with_options(vec![temp_file.clone()], &options);
+        // Not every platform and filesystem records a creation time
+        let expected = fs::metadata(&temp_file).unwrap().created().ok();
+        assert_eq!(infos[0].created, expected);
+
+        let infos =
+            collect_file_info_with_options(vec![temp_file.clone()], &CollectOptions::default());
+        assert_eq!(infos[0].created, None);
+
+        fs::remove_file(&temp_file).unwrap();
+    }
+
+    #[test]
+    fn test_filter_by_mtime() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let temp_dir = std::env::temp_dir().join("flist_test_mtime");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        let at = |seconds: u64| UNIX_EPOCH + Duration::from_secs(seconds);
+        let mut files = Vec::new();
+        for (name, seconds) in [("a", 1_000u64), ("b", 2_000), ("c", 3_000)] {
+            let path = temp_dir.join(name);
+            fs::write(&path, name).unwrap();
+            filetime::set_file_mtime(&path, filetime::FileTime::from_unix_time(seconds as i64, 0))
+                .unwrap();
+            files.push(path);
+        }
+        files.push(temp_dir.join("gone"));
+
+        let options = CollectOptions {
+            read_modified: true,
+            ..Default::default()
+        };
+        let report = collect_file_info_report(files, &options);
+        assert_eq!(report.files[0].modified, Some(at(1_000)));
+        assert_eq!(report.vanished, vec![temp_dir.join("gone")]);
+
+        // Add a file whose metadata could not be read
+        let mut infos = report.files;
+        infos.push(FileInfo {
+            path: temp_dir.join("unknown"),
+            ..Default::default()
+        });
+        let names = |files: Vec<FileInfo>| -> Vec<String> {
+            files
+                .iter()
+                .map(|f| f.path.file_name().unwrap().to_string_lossy().into_owned())
+                .collect()
+        };
+
+        // Both bounds are inclusive
+        assert_eq!(
+            names(filter_by_mtime(infos.clone(), Some(at(2_000)), None)),
+            vec!["b", "c"]
+        );
+        assert_eq!(
+            names(filter_by_mtime(
+                infos.clone(),
+                Some(at(1_000)),
+                Some(at(2_000))
+            )),
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            names(filter_by_mtime(infos.clone(), None, Some(at(2_999)))),
+            vec!["a", "b"]
+        );
+        assert_eq!(filter_by_mtime(infos, None, None).len(), 3);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_wildcard_version_bounds() {
+        let file = |path: &str, version: &str| FileInfo {
+            path: PathBuf::from(path),
+            version: Some(version.parse().unwrap()),
+            ..Default::default()
+        };
+        let files = vec![
+            file("a", "1.1.9.9"),
+            file("b", "1.2.0.0"),
+            file("c", "1.2.65535.65535"),
+            file("d", "1.3.0.0"),
+        ];
+        let paths =
+            |files: Vec<FileInfo>| -> Vec<PathBuf> { files.into_iter().map(|f| f.path).collect() };
+        let pattern: VersionPattern = "1.2.*".parse().unwrap();
+
+        // --minv 1.2.* and --maxv 1.2.* bound the whole 1.2 line
+        assert_eq!(
+            paths(filter_by_version(
+                files.clone(),
+                Some(pattern.lowest()),
+                Some(pattern.highest())
+            )),
+            vec![PathBuf::from("b"), PathBuf::from("c")]
+        );
+        assert_eq!(
+            paths(filter_by_version_pattern(files, &pattern)),
+            vec![PathBuf::from("b"), PathBuf::from("c")]
+        );
+    }
+
+    #[test]
+    fn test_filters_by_product_version() {
+        let file = |path: &str, version: &str, product: Option<&str>| FileInfo {
+            path: PathBuf::from(path),
+            version: Some(version.parse().unwrap()),
+            product_version: product.map(|v| v.parse().unwrap()),
+            ..Default::default()
+        };
+        let files = vec![
+            file("a.dll", "1.0.0.0", Some("5.0.0.0")),
+            file("b.dll", "2.0.0.0", Some("4.0.0.0")),
+            file("c.dll", "3.0.0.0", None),
+        ];
+        let paths =
+            |files: Vec<FileInfo>| -> Vec<PathBuf> { files.into_iter().map(|f| f.path).collect() };
+        let version = |v: &str| Some(v.parse::<FileVersion>().unwrap());
+
+        assert_eq!(
+            paths(filter_by_version_of(
+                files.clone(),
+                version("4.5"),
+                None,
+                VersionField::Product
+            )),
+            vec![PathBuf::from("a.dll")]
+        );
+        assert_eq!(
+            paths(filter_by_version(files.clone(), version("1.5"), None)),
+            vec![PathBuf::from("b.dll"), PathBuf::from("c.dll")]
+        );
+        assert_eq!(
+            paths(filter_excluded_versions_of(
+                files.clone(),
+                &["4.*".parse().unwrap()],
+                VersionField::Product
+            )),
+            vec![PathBuf::from("a.dll"), PathBuf::from("c.dll")]
+        );
+        assert_eq!(
+            paths(filter_older_than_of(
+                files,
+                "5.0".parse().unwrap(),
+                VersionField::Product
+            )),
+            vec![PathBuf::from("b.dll")]
+        );
+    }
+
+    #[test]
+    fn test_total_size() {
+        let file = |size: Option<u64>| FileInfo {
+            path: PathBuf::from("a.dll"),
+            size,
+            ..Default::default()
+        };
+        assert_eq!(total_size(&[]), 0);
+        assert_eq!(total_size(&[file(None)]), 0);
+        assert_eq!(
+            total_size(&[file(Some(u32::MAX as u64)), file(None), file(Some(1))]),
+            u32::MAX as u64 + 1
+        );
+    }
+
+    #[test]
+    fn test_filter_by_size() {
+        let file = |path: &str, size: Option<u64>| FileInfo {
+            path: PathBuf::from(path),
+            size,
+            ..Default::default()
+        };
+        let files = vec![
+            file("a", Some(100)),
+            file("b", Some(200)),
+            file("c", Some(300)),
+            file("unknown", None),
+        ];
+        let paths =
+            |files: Vec<FileInfo>| -> Vec<PathBuf> { files.into_iter().map(|f| f.path).collect() };
+
+        assert_eq!(
+            paths(filter_by_size(files.clone(), Some(200), None)),
+            vec![PathBuf::from("b"), PathBuf::from("c")]
+        );
+        assert_eq!(
+            paths(filter_by_size(files.clone(), Some(150), Some(250))),
+            vec![PathBuf::from("b")]
+        );
+        assert_eq!(
+            paths(filter_by_size(files.clone(), None, Some(100))),
+            vec![PathBuf::from("a")]
+        );
+        // Files of unknown size are dropped whenever the filter runs
+        assert_eq!(filter_by_size(files, None, None).len(), 3);
+    }
+
+    #[cfg(feature = "elf")]
+    #[test]
+    fn test_collect_product_version_keeps_elf_version() {
+        use crate::version_reader::tests::elf_test_image;
+
+        let temp_file = std::env::temp_dir().join("flist_test_product_elf.so.1");
+        fs::write(&temp_file, elf_test_image(Some("libfoo.so.1.2.3"))).unwrap();
+
+        // The soname version is read whether or not the product version is requested
+        for include_product_version in [false, true] {
+            let options = CollectOptions {
+                include_version: true,
+                include_product_version,
+                version_cache: Some(Arc::new(VersionCache::new())),
+                ..Default::default()
+            };
+            let infos = collect_file_info_with_options(vec![temp_file.clone()], &options);
+            assert_eq!(infos[0].version, Some("1.2.3".parse().unwrap()));
+            assert_eq!(infos[0].product_version, None);
+        }
+
+        fs::remove_file(&temp_file).unwrap();
+    }
+
+    #[cfg(feature = "macho")]
+    #[test]
+    fn test_collect_product_version_keeps_macho_version() {
+        use crate::version_reader::tests::macho_test_image;
+
+        let temp_file = std::env::temp_dir().join("flist_test_product_macho.dylib");
+        // x86_64 dylib with current_version 1.2.3
+        fs::write(&temp_file, macho_test_image(0x0100_0007, Some(0x0001_0203))).unwrap();
+
+        for include_product_version in [false, true] {
+            let options = CollectOptions {
+                include_version: true,
+                include_product_version,
+                version_cache: Some(Arc::new(VersionCache::new())),
+                ..Default::default()
+            };
+            let infos = collect_file_info_with_options(vec![temp_file.clone()], &options);
+            assert_eq!(infos[0].version, Some("1.2.3".parse().unwrap()));
+            assert_eq!(infos[0].product_version, None);
+        }
+
+        fs::remove_file(&temp_file).unwrap();
+    }
+
+    #[cfg(feature = "msi")]
+    #[test]
+    fn test_collect_msi_product_version() {
+        use crate::version_reader::tests::msi_test_package;
+
+        let temp_file = std::env::temp_dir().join("flist_test_product_version.msi");
+        fs::write(&temp_file, msi_test_package(Some("10.2.345"))).unwrap();
+        let expected = Some("10.2.345".parse().unwrap());
+
+        let options = CollectOptions {
+            include_version: true,
+            include_product_version: true,
+            ..Default::default()
+        };
+        let infos = collect_file_info_with_options(vec![temp_file.clone()], &options);
+        assert_eq!(infos[0].version, expected);
+        assert_eq!(infos[0].product_version, expected);
+
+        // The product version alone, e.g. for --version-field product
+        let options = CollectOptions {
+            include_product_version: true,
+            ..Default::default()
+        };
+        let infos = collect_file_info_with_options(vec![temp_file.clone()], &options);
+        assert_eq!(infos[0].version, None);
+        assert_eq!(infos[0].product_version, expected);
+
+        fs::remove_file(&temp_file).unwrap();
+    }
+
+    #[test]
+    fn test_collect_reads_size() {
+        let temp_file = std::env::temp_dir().join("flist_test_size.txt");
+        fs::write(&temp_file, "12345").unwrap();
+
+        let options = CollectOptions {
+            read_size: true,
+            ..Default::default()
+        };
+        let infos = collect_file_info_with_options(vec![temp_file.clone()], &options);
+        assert_eq!(infos[0].size, Some(5));
+        assert_eq!(infos[0].created, None);
+
+        let infos =
+            collect_file_info_with_options(vec![temp_file.clone()], &CollectOptions::default());
+        assert_eq!(infos[0].size, None);
+
+        fs::remove_file(&temp_file).unwrap();
+    }
+
+    #[test]
+    fn test_filter_excluded_versions() {
+        let file = |path: &str, version: Option<&str>| FileInfo {
+            path: PathBuf::from(path),
+            version: version.map(|v| v.parse().unwrap()),
+            ..Default::default()
+        };
+        let files = vec![
+            file("a.dll", Some("1.2.3.4")),
+            file("b.dll", Some("1.2.9.0")),
+            file("c.dll", Some("1.1.0.0")),
+            file("d.dll", Some("2.0.0.0")),
+            file("e.txt", None),
+        ];
+
+        // Exact version
+        let filtered = filter_excluded_versions(files.clone(), &["1.2.3.4".parse().unwrap()]);
+        assert_eq!(filtered.len(), 4);
+        assert!(filtered.iter().all(|f| f.path != Path::new("a.dll")));
+
+        // Wildcards, combined as "exclude if matching any"; versionless files are kept
+        let patterns = ["1.2.*".parse().unwrap(), "2".parse().unwrap()];
+        let paths: Vec<_> = filter_excluded_versions(files, &patterns)
+            .into_iter()
+            .map(|f| f.path)
+            .collect();
+        assert_eq!(paths, vec![PathBuf::from("c.dll"), PathBuf::from("e.txt")]);
+    }
+
+    #[test]
+    fn test_canonicalize_paths() {
+        let temp_dir = std::env::temp_dir().join("flist_test_canonicalize");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("sub")).unwrap();
+        fs::write(temp_dir.join("app.dll"), "").unwrap();
+
+        let file = |path: PathBuf| FileInfo {
+            path,
+            ..Default::default()
+        };
+        let mut files = vec![
+            file(temp_dir.join("sub").join("..").join("app.dll")),
+            file(temp_dir.join("sub").join("deleted.dll")),
+        ];
+        let failed = canonicalize_paths(&mut files);
+
+        let canonical_dir = fs::canonicalize(&temp_dir).unwrap();
+        assert_eq!(files[0].path, canonical_dir.join("app.dll"));
+
+        // Unresolvable paths are kept, made absolute, and reported
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].0, temp_dir.join("sub").join("deleted.dll"));
+        assert_eq!(failed[0].1.kind(), std::io::ErrorKind::NotFound);
+        assert_eq!(files[1].path, temp_dir.join("sub").join("deleted.dll"));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_filter_by_exact_version() {
+        let file = |path: &str, version: Option<&str>| FileInfo {
+            path: PathBuf::from(path),
+            version: version.map(|v| v.parse().unwrap()),
+            ..Default::default()
+        };
+        let files = vec![
+            file("a.dll", Some("1.2.3.4")),
+            file("b.dll", Some("1.2.9.0")),
+            file("c.dll", Some("1.2")),
+            file("d.dll", Some("1.20.0.0")),
+            file("e.txt", None),
+        ];
+        let paths =
+            |files: Vec<FileInfo>| -> Vec<PathBuf> { files.into_iter().map(|f| f.path).collect() };
+
+        // Full equality
+        let filtered = filter_by_exact_version(files.clone(), &"1.2.3.4".parse().unwrap());
+        assert_eq!(paths(filtered), vec![PathBuf::from("a.dll")]);
+
+        // Omitted parts are wildcards, versionless files never match
+        let filtered = filter_by_exact_version(files.clone(), &"1.2".parse().unwrap());
+        assert_eq!(
+            paths(filtered),
+            vec![
+                PathBuf::from("a.dll"),
+                PathBuf::from("b.dll"),
+                PathBuf::from("c.dll")
+            ]
+        );
+
+        // Unset parts of a file's version compare as zero
+        let filtered = filter_by_exact_version(files.clone(), &"1.2.0.0".parse().unwrap());
+        assert_eq!(paths(filtered), vec![PathBuf::from("c.dll")]);
+
+        assert!(filter_by_exact_version(files, &"3".parse().unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_keep_highest_version() {
+        let file = |path: &str, version: Option<&str>| FileInfo {
+            path: PathBuf::from(path),
+            version: version.map(|v| v.parse().unwrap()),
+            ..Default::default()
+        };
+        let files = vec![
+            file("a/core.dll", Some("1.0.0.0")),
+            file("b/core.dll", Some("3.0.0.0")),
+            file("c/core.dll", Some("2.5.0.0")),
+            file("c/app.exe", Some("1.2.0.0")),
+            file("b/app.exe", Some("1.2")),
+            file("a/app.exe", Some("1.1.0.0")),
+            file("a/readme.dll", None),
+            file("b/readme.dll", None),
+            file("d/only.dll", Some("0.1.0.0")),
+        ];
+        let paths: Vec<PathBuf> = keep_highest_version(files)
+            .into_iter()
+            .map(|f| f.path)
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("b/core.dll"),
+                // 1.2 equals 1.2.0.0, so the lower path wins the tie
+                PathBuf::from("b/app.exe"),
+                PathBuf::from("a/readme.dll"),
+                PathBuf::from("b/readme.dll"),
+                PathBuf::from("d/only.dll"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_version_regressions() {
+        let file = |path: &str, version: Option<&str>| FileInfo {
+            path: PathBuf::from(path),
+            version: version.map(|v| v.parse().unwrap()),
+            ..Default::default()
+        };
+        let files = vec![
+            file("c/core.dll", Some("1.5.0.0")),
+            file("a/core.dll", Some("1.0.0.0")),
+            file("b/core.dll", Some("2.0.0.0")),
+            file("d/core.dll", Some("2.0.0.0")),
+            file("e/core.dll", None),
+            file("a/ui.dll", Some("3.0.0.0")),
+            file("b/ui.dll", Some("3.1.0.0")),
+        ];
+
+        let regressions = find_version_regressions(&files);
+
+        // c/core.dll is older than b/core.dll which precedes it; ui.dll only increases,
+        // equal versions and versionless files are not regressions
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].earlier.path, PathBuf::from("b/core.dll"));
+        assert_eq!(regressions[0].later.path, PathBuf::from("c/core.dll"));
+    }
+
+    #[test]
+    fn test_find_version_regressions_reports_each_inversion() {
+        let files: Vec<FileInfo> = [("a/x.dll", "3.0"), ("b/x.dll", "2.0"), ("c/x.dll", "1.0")]
+            .iter()
+            .map(|(path, version)| FileInfo {
+                path: PathBuf::from(path),
+                version: Some(version.parse().unwrap()),
+                ..Default::default()
+            })
+            .collect();
+
+        // Both later files are compared to the highest earlier version
+        let regressions = find_version_regressions(&files);
+        assert_eq!(regressions.len(), 2);
+        assert!(
+            regressions
+                .iter()
+                .all(|r| r.earlier.path == Path::new("a/x.dll"))
+        );
     }
 
     #[test]
@@ -285,14 +4658,17 @@ mod tests {
             FileInfo {
                 path: PathBuf::from("c.txt"),
                 version: None,
+                ..Default::default()
             },
             FileInfo {
                 path: PathBuf::from("a.txt"),
                 version: None,
+                ..Default::default()
             },
             FileInfo {
                 path: PathBuf::from("b.txt"),
                 version: None,
+                ..Default::default()
             },
         ];
 
@@ -302,13 +4678,382 @@ mod tests {
         assert_eq!(sorted[2].path, PathBuf::from("c.txt"));
     }
 
+    #[test]
+    fn test_sort_descending() {
+        let files: Vec<FileInfo> = [
+            ("a.dll", Some("2.0")),
+            ("z.dll", None),
+            ("m.dll", Some("1.0")),
+        ]
+        .iter()
+        .map(|(path, version)| FileInfo {
+            path: PathBuf::from(path),
+            version: version.map(|v| v.parse().unwrap()),
+            ..Default::default()
+        })
+        .collect();
+        let paths = |files: &[FileInfo]| -> Vec<String> {
+            files.iter().map(|f| f.path.display().to_string()).collect()
+        };
+
+        let sorted =
+            sort_by_keys_with_direction(files.clone(), &[SortKey::Path], SortDirection::Descending);
+        assert_eq!(paths(&sorted), ["z.dll", "m.dll", "a.dll"]);
+
+        // Every key is reversed, versionless files come first
+        let sorted = sort_by_keys_with_direction(
+            files.clone(),
+            &[SortKey::Version, SortKey::Path],
+            SortDirection::Descending,
+        );
+        assert_eq!(paths(&sorted), ["z.dll", "a.dll", "m.dll"]);
+
+        // Ascending is the default order
+        assert_eq!(
+            paths(&sort_by_keys_with_direction(
+                files.clone(),
+                &[SortKey::Path],
+                SortDirection::default()
+            )),
+            paths(&sort_by_keys(files, &[SortKey::Path]))
+        );
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        assert_eq!(natural_cmp("file2", "file10"), Ordering::Less);
+        assert_eq!(natural_cmp("file10", "file2"), Ordering::Greater);
+        assert_eq!(natural_cmp("file2.dll", "file2.dll"), Ordering::Equal);
+        assert_eq!(natural_cmp("a", "a1"), Ordering::Less);
+        assert_eq!(natural_cmp("1.10", "1.9"), Ordering::Greater);
+        assert_eq!(
+            natural_cmp("x99999999999999999999999", "x100000000000000000000000"),
+            Ordering::Less
+        );
+
+        // Leading zeros do not change the value, but keep the order total
+        assert_eq!(natural_cmp("a007b", "a7c"), Ordering::Less);
+        assert_eq!(natural_cmp("a01", "a1"), "a01".cmp("a1"));
+    }
+
+    #[test]
+    fn test_sort_natural() {
+        let files: Vec<FileInfo> = [
+            "file10.dll",
+            "file2.dll",
+            "v10/a.dll",
+            "v9/b.dll",
+            "file1.dll",
+        ]
+        .iter()
+        .map(|path| FileInfo {
+            path: PathBuf::from(path),
+            ..Default::default()
+        })
+        .collect();
+        let paths = |files: &[FileInfo]| -> Vec<String> {
+            files
+                .iter()
+                .map(|f| f.path.to_string_lossy().replace('\\', "/"))
+                .collect()
+        };
+
+        let sorted = sort_by_keys_with_order(
+            files.clone(),
+            &[SortKey::Path],
+            SortDirection::Ascending,
+            PathOrder::Natural,
+        );
+        assert_eq!(
+            paths(&sorted),
+            [
+                "file1.dll",
+                "file2.dll",
+                "file10.dll",
+                "v9/b.dll",
+                "v10/a.dll"
+            ]
+        );
+
+        // The default stays lexicographic
+        let sorted = sort_by_keys(files.clone(), &[SortKey::Path]);
+        assert_eq!(
+            paths(&sorted),
+            [
+                "file1.dll",
+                "file10.dll",
+                "file2.dll",
+                "v10/a.dll",
+                "v9/b.dll"
+            ]
+        );
+
+        // Names are compared naturally as well
+        let sorted = sort_by_keys_with_order(
+            files,
+            &[SortKey::Name],
+            SortDirection::Descending,
+            PathOrder::Natural,
+        );
+        assert_eq!(
+            paths(&sorted),
+            [
+                "file10.dll",
+                "file2.dll",
+                "file1.dll",
+                "v9/b.dll",
+                "v10/a.dll"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort_by_version() {
+        let files: Vec<FileInfo> = [
+            ("a.dll", None),
+            ("b.dll", Some("10.0")),
+            ("c.dll", Some("2.0")),
+            ("d.dll", None),
+            ("e.dll", Some("2.0.0.0")),
+        ]
+        .iter()
+        .map(|(path, version)| FileInfo {
+            path: PathBuf::from(path),
+            version: version.map(|v| v.parse().unwrap()),
+            ..Default::default()
+        })
+        .collect();
+
+        // Numeric order, equal versions and versionless files by path
+        let sorted = sort_by_version(files);
+        let paths: Vec<_> = sorted.iter().map(|f| f.path.to_str().unwrap()).collect();
+        assert_eq!(paths, ["c.dll", "e.dll", "b.dll", "a.dll", "d.dll"]);
+    }
+
+    #[test]
+    fn test_sort_by_keys_breaks_ties_by_path() {
+        let files: Vec<FileInfo> = [
+            ("./x.dll", None),
+            ("./z/b.dll", Some("2.0")),
+            ("./a/b/z.dll", None),
+            ("./y/b.dll", Some("2.0")),
+        ]
+        .iter()
+        .map(|(path, version)| FileInfo {
+            path: PathBuf::from(path),
+            version: version.map(|v| v.parse().unwrap()),
+            ..Default::default()
+        })
+        .collect();
+        let sorted = |keys: &[SortKey], direction| {
+            sort_by_keys_with_direction(files.clone(), keys, direction)
+                .into_iter()
+                .map(|f| f.path.to_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        // Same-version and versionless files are ordered by path, whatever
+        // the order they were found in
+        assert_eq!(
+            sorted(&[SortKey::Version], SortDirection::Ascending),
+            ["./y/b.dll", "./z/b.dll", "./a/b/z.dll", "./x.dll"]
+        );
+        assert_eq!(
+            sorted(&[SortKey::Version], SortDirection::Descending),
+            ["./x.dll", "./a/b/z.dll", "./z/b.dll", "./y/b.dll"]
+        );
+        // Files with the same name too
+        assert_eq!(
+            sorted(&[SortKey::Name], SortDirection::Ascending),
+            ["./y/b.dll", "./z/b.dll", "./x.dll", "./a/b/z.dll"]
+        );
+    }
+
+    #[test]
+    fn test_sort_by_extension() {
+        let files: Vec<FileInfo> = ["b.dll", "a.EXE", "README", "c.DLL", "a.exe", "d.cfg"]
+            .iter()
+            .map(|path| FileInfo {
+                path: PathBuf::from(path),
+                ..Default::default()
+            })
+            .collect();
+        let sorted: Vec<_> = sort_by_keys(files, &[SortKey::Extension])
+            .into_iter()
+            .map(|f| f.path.to_str().unwrap().to_string())
+            .collect();
+        // Case is ignored, files without extension come first, ties by path
+        assert_eq!(
+            sorted,
+            ["README", "d.cfg", "b.dll", "c.DLL", "a.EXE", "a.exe"]
+        );
+    }
+
+    #[test]
+    fn test_sort_by_version_then_path() {
+        let files: Vec<FileInfo> = [
+            ("z/b.dll", Some("2.0")),
+            ("a/c.dll", None),
+            ("y/a.dll", Some("1.0")),
+            ("x/d.dll", Some("2.0")),
+            ("b/a.dll", Some("1.0")),
+        ]
+        .iter()
+        .map(|(path, version)| FileInfo {
+            path: PathBuf::from(path),
+            version: version.map(|v| v.parse().unwrap()),
+            ..Default::default()
+        })
+        .collect();
+
+        // Grouped by version, then ordered by path; versionless files last
+        let sorted = sort_by_keys(files.clone(), &[SortKey::Version, SortKey::Path]);
+        let paths: Vec<_> = sorted.iter().map(|f| f.path.to_str().unwrap()).collect();
+        assert_eq!(
+            paths,
+            ["b/a.dll", "y/a.dll", "x/d.dll", "z/b.dll", "a/c.dll"]
+        );
+
+        // The key order matters; the path still breaks the remaining ties
+        let sorted = sort_by_keys(files.clone(), &[SortKey::Name, SortKey::Version]);
+        let paths: Vec<_> = sorted.iter().map(|f| f.path.to_str().unwrap()).collect();
+        assert_eq!(
+            paths,
+            ["b/a.dll", "y/a.dll", "z/b.dll", "a/c.dll", "x/d.dll"]
+        );
+
+        // A single path key is the same as sort_by_path
+        let by_keys = sort_by_keys(files.clone(), &[SortKey::Path]);
+        let by_path = sort_by_path(files);
+        assert!(by_keys.iter().zip(&by_path).all(|(a, b)| a.path == b.path));
+    }
+
+    #[test]
+    fn test_skip_through_path() {
+        let files: Vec<FileInfo> = ["dir/a.dll", "dir/b.dll", "dir/c.dll", "dir2/a.dll"]
+            .iter()
+            .map(|p| FileInfo {
+                path: PathBuf::from(p),
+                version: None,
+                ..Default::default()
+            })
+            .collect();
+
+        // The boundary path itself is excluded
+        let remaining = skip_through_path(files.clone(), Path::new("dir/b.dll"));
+        let paths: Vec<_> = remaining.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("dir/c.dll"), PathBuf::from("dir2/a.dll")]
+        );
+
+        // A path between two results resumes at the next one
+        let remaining = skip_through_path(files.clone(), Path::new("dir/bb.dll"));
+        assert_eq!(remaining[0].path, PathBuf::from("dir/c.dll"));
+
+        // A path before everything keeps all, after everything keeps none
+        assert_eq!(skip_through_path(files.clone(), Path::new("a")).len(), 4);
+        assert!(skip_through_path(files.clone(), Path::new("dir2/a.dll")).is_empty());
+
+        // A leading ./ on either side does not matter
+        let found: Vec<FileInfo> = files
+            .iter()
+            .map(|f| FileInfo {
+                path: Path::new(".").join(&f.path),
+                ..f.clone()
+            })
+            .collect();
+        for (files, after) in [
+            (found.clone(), "dir/b.dll"),
+            (found, "./dir/b.dll"),
+            (files, "./dir/b.dll"),
+        ] {
+            let remaining = skip_through_path(files, Path::new(after));
+            assert_eq!(remaining.len(), 2, "{}", after);
+            assert!(remaining[0].path.ends_with("dir/c.dll"), "{}", after);
+        }
+    }
+
+    #[test]
+    fn test_resolve_after_path() {
+        let temp_dir = std::env::temp_dir().join("flist_test_after_path");
+        for (after, directory, expected) in [
+            ("a/y.dll", ".", PathBuf::from("a/y.dll")),
+            ("./a/y.dll", ".", PathBuf::from("a/y.dll")),
+            ("a/y.dll", "sub", PathBuf::from("sub/a/y.dll")),
+            ("sub/a/y.dll", "./sub", PathBuf::from("sub/a/y.dll")),
+            (
+                "a/y.dll",
+                temp_dir.to_str().unwrap(),
+                temp_dir.join("a/y.dll"),
+            ),
+            (
+                temp_dir.join("b.dll").to_str().unwrap(),
+                ".",
+                temp_dir.join("b.dll"),
+            ),
+        ] {
+            assert_eq!(
+                resolve_after_path(Path::new(after), Path::new(directory)),
+                expected,
+                "{} in {}",
+                after,
+                directory
+            );
+        }
+
+        // Found under an absolute search directory, a relative boundary resumes
+        let files: Vec<FileInfo> = ["a/x.dll", "a/y.dll", "a/z.dll"]
+            .iter()
+            .map(|name| FileInfo {
+                path: temp_dir.join(name),
+                ..Default::default()
+            })
+            .collect();
+        let after = resolve_after_path(Path::new("a/y.dll"), &temp_dir);
+        let remaining = skip_through_path(files, &after);
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].path, temp_dir.join("a/z.dll"));
+    }
+
+    #[test]
+    fn test_enumerate_files_iter_is_lazy() {
+        let temp_dir = std::env::temp_dir().join("flist_test_iter");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("sub")).unwrap();
+        fs::File::create(temp_dir.join("a.txt")).unwrap();
+        fs::File::create(temp_dir.join("sub").join("b.txt")).unwrap();
+        fs::File::create(temp_dir.join("c.md")).unwrap();
+
+        let mut files: Vec<PathBuf> = enumerate_files_iter(&temp_dir, "*.txt").unwrap().collect();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![temp_dir.join("a.txt"), temp_dir.join("sub").join("b.txt")]
+        );
+
+        // Matches are counted as they are yielded
+        let progress = Arc::new(ScanProgress::default());
+        let options = EnumerateOptions {
+            progress: Some(Arc::clone(&progress)),
+            ..Default::default()
+        };
+        let mut iter = enumerate_files_iter_with_options(&temp_dir, &["*.txt"], &options).unwrap();
+        assert!(iter.next().is_some());
+        assert!(progress.message().contains(" of 1 files"));
+
+        assert!(enumerate_files_iter(&temp_dir, "[invalid").is_err());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
     #[test]
     fn test_enumerate_files_recursive() {
         // Create a temporary directory with subdirectories
         let temp_dir = std::env::temp_dir().join("flist_test_recursive");
         let _ = fs::remove_dir_all(&temp_dir);
-        fs::create_dir_all(&temp_dir.join("subdir1")).unwrap();
-        fs::create_dir_all(&temp_dir.join("subdir2")).unwrap();
+        fs::create_dir_all(temp_dir.join("subdir1")).unwrap();
+        fs::create_dir_all(temp_dir.join("subdir2")).unwrap();
 
         // Create test files in different directories
         fs::File::create(temp_dir.join("root.txt")).unwrap();
@@ -323,6 +5068,82 @@ mod tests {
         fs::remove_dir_all(&temp_dir).unwrap();
     }
 
+    #[test]
+    fn test_enumerate_files_with_depth() {
+        let temp_dir = std::env::temp_dir().join("flist_test_depth");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("sub").join("deeper")).unwrap();
+
+        fs::File::create(temp_dir.join("root.txt")).unwrap();
+        fs::File::create(temp_dir.join("sub").join("sub.txt")).unwrap();
+        fs::File::create(temp_dir.join("sub").join("deeper").join("deep.txt")).unwrap();
+
+        let count = |depth| {
+            enumerate_files_with_depth(&temp_dir, "*.txt", depth)
+                .unwrap()
+                .len()
+        };
+        assert_eq!(count(Some(0)), 1);
+        assert_eq!(count(Some(1)), 1);
+        assert_eq!(count(Some(2)), 2);
+        assert_eq!(count(Some(10)), 3);
+        assert_eq!(count(None), 3);
+
+        // Directories are cut off at the same level
+        let options = EnumerateOptions {
+            include_dirs: true,
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        let entries = enumerate_files_with_options(&temp_dir, &["*"], &options).unwrap();
+        assert_eq!(entries.len(), 2); // root.txt and sub
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_enumerate_files_filtered() {
+        let temp_dir = std::env::temp_dir().join("flist_test_exclude");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("src")).unwrap();
+        fs::create_dir_all(temp_dir.join("node_modules").join("lib")).unwrap();
+        fs::create_dir_all(temp_dir.join(".git")).unwrap();
+
+        fs::File::create(temp_dir.join("index.js")).unwrap();
+        fs::File::create(temp_dir.join("src").join("app.js")).unwrap();
+        fs::File::create(temp_dir.join("src").join("app.min.js")).unwrap();
+        fs::File::create(temp_dir.join("node_modules").join("lib").join("dep.js")).unwrap();
+        fs::File::create(temp_dir.join(".git").join("hook.js")).unwrap();
+
+        let mut files =
+            enumerate_files_filtered(&temp_dir, "*.js", &["node_modules", ".git", "*.min.js"])
+                .unwrap();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                temp_dir.join("index.js"),
+                temp_dir.join("src").join("app.js")
+            ]
+        );
+
+        // Without excludes everything is found
+        assert_eq!(
+            enumerate_files_filtered(&temp_dir, "*.js", &[])
+                .unwrap()
+                .len(),
+            5
+        );
+
+        // A search directory matching an exclude is still searched
+        let files = enumerate_files_filtered(&temp_dir.join("src"), "*.js", &["src"]).unwrap();
+        assert_eq!(files.len(), 2);
+
+        assert!(enumerate_files_filtered(&temp_dir, "*.js", &["[invalid"]).is_err());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
     #[test]
     fn test_invalid_pattern() {
         let temp_dir = std::env::temp_dir();
@@ -1,7 +1,10 @@
 //! File version representation and parsing.
 //!
 //! This module provides the [`FileVersion`] struct for representing and comparing
-//! file versions in the format `major.minor.build.private`.
+//! file versions in the format `major.minor.build.private`, and the
+//! [`VersionPattern`] struct for matching versions against wildcard patterns.
+//! [`SemanticVersion`] covers versions with a prerelease suffix such as
+//! `1.2.3-rc1`, as found in the version strings of some builds.
 
 use std::cmp::Ordering;
 use std::fmt;
@@ -10,7 +13,12 @@ use std::str::FromStr;
 /// Represents a file version with up to 4 parts: major.minor.build.private.
 ///
 /// Each part is optional and represented as `Option<u32>`. This allows for partial
-/// versions like "1.0" or "2.3.4".
+/// versions like "1.0" or "2.3.4". A single leading `v` or `V`, as in the tag
+/// `v1.2.3.4`, is ignored when parsing.
+///
+/// A part that is not set counts as zero everywhere: `1.2` is displayed as
+/// `1.2.0.0` and compares equal to it, so `--minv 1.2` and `--maxv 1.2` both
+/// mean `1.2.0.0`. The fields still record which parts were given.
 ///
 /// # Examples
 ///
@@ -24,7 +32,7 @@ use std::str::FromStr;
 /// assert_eq!(version.build, Some(3));
 /// assert_eq!(version.private, Some(4));
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy)]
 pub struct FileVersion {
     pub major: Option<u32>,
     pub minor: Option<u32>,
@@ -63,13 +71,81 @@ impl FileVersion {
             private,
         }
     }
+
+    /// Creates a `FileVersion` from a `VS_VERSION` as stored in a PE version
+    /// resource, read as a little-endian `u64`.
+    ///
+    /// The bit layout is:
+    ///
+    /// * Bits 0-15: minor (low word of the most significant dword)
+    /// * Bits 16-31: major (high word of the most significant dword)
+    /// * Bits 32-47: private (low word of the least significant dword)
+    /// * Bits 48-63: build (high word of the least significant dword)
+    ///
+    /// # Arguments
+    ///
+    /// * `raw` - The `dwFileVersion` or `dwProductVersion` value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flist::file_version::FileVersion;
+    ///
+    /// let version = FileVersion::from_vs_version_u64(0x0003_0004_0001_0002);
+    /// assert_eq!(version.to_string(), "1.2.3.4");
+    /// ```
+    pub fn from_vs_version_u64(raw: u64) -> Self {
+        let part = |shift: u32| Some(((raw >> shift) & 0xFFFF) as u32);
+        Self::new(part(16), part(0), part(48), part(32))
+    }
+
+    /// Returns the four version parts, with parts that are not set as zero.
+    ///
+    /// This is the value used for comparisons and display.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flist::file_version::FileVersion;
+    ///
+    /// let version: FileVersion = "1.2".parse().unwrap();
+    /// assert_eq!(version.parts(), [1, 2, 0, 0]);
+    /// ```
+    pub fn parts(&self) -> [u32; 4] {
+        [self.major, self.minor, self.build, self.private].map(|part| part.unwrap_or(0))
+    }
+
+    /// Formats only the parts that are set, e.g. `1.2` instead of `1.2.0.0`.
+    ///
+    /// This is the alternate form of [`Display`](fmt::Display), `{:#}`. A part
+    /// that is not set but followed by a set part is left empty, as in `1..3`,
+    /// so that [`FromStr`] restores exactly the same fields.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flist::file_version::FileVersion;
+    ///
+    /// let version: FileVersion = "1.2".parse().unwrap();
+    /// assert_eq!(version.display_sparse(), "1.2");
+    /// assert_eq!(format!("{:#}", version), "1.2");
+    /// assert_eq!(version.to_string(), "1.2.0.0");
+    /// ```
+    pub fn display_sparse(&self) -> String {
+        format!("{:#}", self)
+    }
 }
 
 impl FromStr for FileVersion {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Accept tags such as v1.2.3.4
+        let s = s.strip_prefix(['v', 'V']).unwrap_or(s);
         let parts: Vec<&str> = s.split('.').collect();
+        if parts.len() > 4 {
+            anyhow::bail!("version has more than 4 components");
+        }
 
         let major = if !parts.is_empty() && !parts[0].is_empty() {
             Some(parts[0].parse::<u32>()?)
@@ -104,6 +180,14 @@ impl FromStr for FileVersion {
     }
 }
 
+impl PartialEq for FileVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.parts() == other.parts()
+    }
+}
+
+impl Eq for FileVersion {}
+
 impl PartialOrd for FileVersion {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -112,37 +196,401 @@ impl PartialOrd for FileVersion {
 
 impl Ord for FileVersion {
     fn cmp(&self, other: &Self) -> Ordering {
-        // Compare major
-        match self.major.cmp(&other.major) {
-            Ordering::Equal => {}
-            ord => return ord,
+        // Compares major, minor, build and private in turn
+        self.parts().cmp(&other.parts())
+    }
+}
+
+impl fmt::Display for FileVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            // Only up to the last part that is set
+            let parts = [self.major, self.minor, self.build, self.private];
+            let len = parts
+                .iter()
+                .rposition(Option::is_some)
+                .map_or(0, |last| last + 1);
+            for (index, part) in parts[..len].iter().enumerate() {
+                if index > 0 {
+                    f.write_str(".")?;
+                }
+                if let Some(part) = part {
+                    write!(f, "{}", part)?;
+                }
+            }
+            return Ok(());
         }
 
-        // Compare minor
-        match self.minor.cmp(&other.minor) {
-            Ordering::Equal => {}
-            ord => return ord,
+        let [major, minor, build, private] = self.parts();
+
+        write!(f, "{}.{}.{}.{}", major, minor, build, private)
+    }
+}
+
+/// Serializes as a version string in the form of
+/// [`display_sparse`](FileVersion::display_sparse), so `1.2` stays `"1.2"`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for FileVersion {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&format_args!("{:#}", self))
+    }
+}
+
+/// Deserializes a version string using [`FromStr`].
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for FileVersion {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        FileVersion::from_str(&text)
+            .map_err(|e| serde::de::Error::custom(format!("invalid version '{}': {}", text, e)))
+    }
+}
+
+/// A version pattern such as `1.2.*` or `10.0`, matched against file versions.
+///
+/// Each part is either a number or `*`, which matches any value. Parts omitted
+/// at the end match any value as well, so `1.2` matches every `1.2.x.y` version.
+/// A part of the version that is not set compares as zero.
+///
+/// # Examples
+///
+/// ```
+/// use flist::file_version::{FileVersion, VersionPattern};
+///
+/// let pattern: VersionPattern = "1.*.3".parse().unwrap();
+/// assert!(pattern.matches(&"1.7.3.9".parse::<FileVersion>().unwrap()));
+/// assert!(!pattern.matches(&"1.7.4.0".parse::<FileVersion>().unwrap()));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionPattern {
+    /// Required major version, `None` for any
+    pub major: Option<u32>,
+    /// Required minor version, `None` for any
+    pub minor: Option<u32>,
+    /// Required build number, `None` for any
+    pub build: Option<u32>,
+    /// Required private number, `None` for any
+    pub private: Option<u32>,
+}
+
+impl VersionPattern {
+    /// Returns whether `version` matches this pattern.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The version to test
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flist::file_version::{FileVersion, VersionPattern};
+    ///
+    /// let pattern: VersionPattern = "2".parse().unwrap();
+    /// assert!(pattern.matches(&FileVersion::new(Some(2), Some(5), None, None)));
+    /// ```
+    pub fn matches(&self, version: &FileVersion) -> bool {
+        let part_matches = |pattern: Option<u32>, part: Option<u32>| {
+            pattern.is_none_or(|p| p == part.unwrap_or(0))
+        };
+
+        part_matches(self.major, version.major)
+            && part_matches(self.minor, version.minor)
+            && part_matches(self.build, version.build)
+            && part_matches(self.private, version.private)
+    }
+
+    /// Returns the lowest version matching this pattern, with every wildcard
+    /// part set to zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flist::file_version::VersionPattern;
+    ///
+    /// let pattern: VersionPattern = "1.2.*".parse().unwrap();
+    /// assert_eq!(pattern.lowest().to_string(), "1.2.0.0");
+    /// ```
+    pub fn lowest(&self) -> FileVersion {
+        self.fill(0)
+    }
+
+    /// Returns the highest version matching this pattern, with every wildcard
+    /// part set to 65535, the largest value a PE version part can hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flist::file_version::VersionPattern;
+    ///
+    /// let pattern: VersionPattern = "1.2.*".parse().unwrap();
+    /// assert_eq!(pattern.highest().to_string(), "1.2.65535.65535");
+    /// ```
+    pub fn highest(&self) -> FileVersion {
+        self.fill(u16::MAX.into())
+    }
+
+    fn fill(&self, wildcard: u32) -> FileVersion {
+        FileVersion::new(
+            Some(self.major.unwrap_or(wildcard)),
+            Some(self.minor.unwrap_or(wildcard)),
+            Some(self.build.unwrap_or(wildcard)),
+            Some(self.private.unwrap_or(wildcard)),
+        )
+    }
+}
+
+impl FromStr for VersionPattern {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split('.').collect();
+        if parts.len() > 4 {
+            anyhow::bail!("expected at most 4 parts");
         }
 
-        // Compare build
-        match self.build.cmp(&other.build) {
-            Ordering::Equal => {}
-            ord => return ord,
+        let mut values = [None; 4];
+        for (value, part) in values.iter_mut().zip(&parts) {
+            *value = match *part {
+                "*" => None,
+                "" => anyhow::bail!("empty version part"),
+                number => Some(number.parse::<u32>()?),
+            };
         }
 
-        // Compare private
-        self.private.cmp(&other.private)
+        let [major, minor, build, private] = values;
+        Ok(VersionPattern {
+            major,
+            minor,
+            build,
+            private,
+        })
     }
 }
 
-impl fmt::Display for FileVersion {
+impl fmt::Display for VersionPattern {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let major = self.major.map_or(0, |v| v);
-        let minor = self.minor.map_or(0, |v| v);
-        let build = self.build.map_or(0, |v| v);
-        let private = self.private.map_or(0, |v| v);
+        let parts: Vec<String> = [self.major, self.minor, self.build, self.private]
+            .iter()
+            .map(|part| part.map_or("*".to_string(), |v| v.to_string()))
+            .collect();
 
-        write!(f, "{}.{}.{}.{}", major, minor, build, private)
+        write!(f, "{}", parts.join("."))
+    }
+}
+
+/// Parses an inclusive version range such as `1.0..2.0` into its bounds.
+///
+/// Either bound may be omitted for an open range, `1.0..` or `..2.0`. Like
+/// `--minv` and `--maxv`, a bound with wildcards covers the whole matching
+/// range, so `1.2.*..1.4.*` reaches from `1.2.0.0` through `1.4.65535.65535`.
+///
+/// # Arguments
+///
+/// * `spec` - The range to parse
+///
+/// # Returns
+///
+/// The minimum and maximum version, or an error if the range has no `..`, no
+/// bound, an invalid bound or a minimum above its maximum.
+///
+/// # Examples
+///
+/// ```
+/// use flist::file_version::{parse_version_range, FileVersion};
+///
+/// let (min, max) = parse_version_range("1.0..2.0").unwrap();
+/// assert_eq!(min, Some("1.0".parse::<FileVersion>().unwrap()));
+/// assert_eq!(max, Some("2.0".parse::<FileVersion>().unwrap()));
+/// assert_eq!(parse_version_range("..2.0").unwrap().0, None);
+/// ```
+pub fn parse_version_range(
+    spec: &str,
+) -> Result<(Option<FileVersion>, Option<FileVersion>), anyhow::Error> {
+    let (min, max) = spec
+        .split_once("..")
+        .ok_or_else(|| anyhow::anyhow!("expected '..' between the bounds"))?;
+    if min.is_empty() && max.is_empty() {
+        anyhow::bail!("expected at least one bound");
+    }
+
+    let bound = |text: &str, highest: bool| -> Result<Option<FileVersion>, anyhow::Error> {
+        if text.is_empty() {
+            return Ok(None);
+        }
+        if !text.contains('*') {
+            return FileVersion::from_str(text).map(Some);
+        }
+        let pattern = VersionPattern::from_str(text)?;
+        Ok(Some(if highest {
+            pattern.highest()
+        } else {
+            pattern.lowest()
+        }))
+    };
+    let min = bound(min, false)?;
+    let max = bound(max, true)?;
+
+    if let (Some(min), Some(max)) = (min, max)
+        && min > max
+    {
+        anyhow::bail!("minimum {} is higher than maximum {}", min, max);
+    }
+    Ok((min, max))
+}
+
+/// A semantic version such as `1.2.3` or `1.2.3-rc.1`.
+///
+/// Unlike [`FileVersion`], which holds the numeric version of a PE file, this
+/// accepts a `-prerelease` suffix and orders versions by the semver precedence
+/// rules: a prerelease is lower than its release (`1.2.3-rc1 < 1.2.3`), and
+/// prereleases compare identifier by identifier, numbers numerically and below
+/// other identifiers (`1.0.0-alpha < 1.0.0-alpha.1 < 1.0.0-beta < 1.0.0-beta.2
+/// < 1.0.0-beta.11 < 1.0.0-rc.1`).
+///
+/// Parsing is lenient about the form found in version strings: a leading `v` is
+/// ignored, omitted minor and patch numbers are zero, and `+build` metadata is
+/// accepted but dropped, as it does not affect precedence.
+///
+/// # Examples
+///
+/// ```
+/// use flist::file_version::SemanticVersion;
+///
+/// let rc: SemanticVersion = "1.2.3-rc1".parse().unwrap();
+/// let release: SemanticVersion = "1.2.3".parse().unwrap();
+/// assert!(rc < release);
+/// assert_eq!(rc.to_string(), "1.2.3-rc1");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SemanticVersion {
+    /// Major version
+    pub major: u64,
+    /// Minor version
+    pub minor: u64,
+    /// Patch version
+    pub patch: u64,
+    /// Dot separated prerelease identifiers, empty for a release
+    pub prerelease: Vec<PrereleaseIdentifier>,
+}
+
+/// One dot separated identifier of a [`SemanticVersion`] prerelease.
+///
+/// Numeric identifiers order below alphanumeric ones.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PrereleaseIdentifier {
+    /// An identifier of digits only, compared numerically
+    Numeric(u64),
+    /// Any other identifier, compared in ASCII order
+    Alphanumeric(String),
+}
+
+impl SemanticVersion {
+    /// Returns whether this is a prerelease version.
+    pub fn is_prerelease(&self) -> bool {
+        !self.prerelease.is_empty()
+    }
+}
+
+impl From<FileVersion> for SemanticVersion {
+    /// Converts `major.minor.build.private` to the release `major.minor.build`.
+    fn from(version: FileVersion) -> Self {
+        let [major, minor, build, _] = version.parts();
+        SemanticVersion {
+            major: major.into(),
+            minor: minor.into(),
+            patch: build.into(),
+            prerelease: Vec::new(),
+        }
+    }
+}
+
+impl FromStr for SemanticVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_prefix(['v', 'V']).unwrap_or(s);
+        // Build metadata does not take part in precedence
+        let s = s.split_once('+').map_or(s, |(version, _)| version);
+        let (numbers, prerelease) = match s.split_once('-') {
+            Some((numbers, prerelease)) => (numbers, Some(prerelease)),
+            None => (s, None),
+        };
+
+        let parts: Vec<&str> = numbers.split('.').collect();
+        if parts.len() > 3 {
+            anyhow::bail!("expected at most 3 numeric parts");
+        }
+        let mut values = [0u64; 3];
+        for (value, part) in values.iter_mut().zip(&parts) {
+            if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+                anyhow::bail!("invalid version part '{}'", part);
+            }
+            *value = part.parse()?;
+        }
+
+        let prerelease = match prerelease {
+            None => Vec::new(),
+            Some(prerelease) => prerelease
+                .split('.')
+                .map(|identifier| {
+                    if identifier.is_empty()
+                        || !identifier
+                            .bytes()
+                            .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+                    {
+                        anyhow::bail!("invalid prerelease identifier '{}'", identifier);
+                    }
+                    Ok(match identifier.parse() {
+                        Ok(number) => PrereleaseIdentifier::Numeric(number),
+                        Err(_) => PrereleaseIdentifier::Alphanumeric(identifier.to_string()),
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+        };
+
+        let [major, minor, patch] = values;
+        Ok(SemanticVersion {
+            major,
+            minor,
+            patch,
+            prerelease,
+        })
+    }
+}
+
+impl PartialOrd for SemanticVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SemanticVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| {
+                match (self.is_prerelease(), other.is_prerelease()) {
+                    // A release is higher than any of its prereleases
+                    (false, true) => Ordering::Greater,
+                    (true, false) => Ordering::Less,
+                    _ => self.prerelease.cmp(&other.prerelease),
+                }
+            })
+    }
+}
+
+impl fmt::Display for SemanticVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        for (index, identifier) in self.prerelease.iter().enumerate() {
+            f.write_str(if index == 0 { "-" } else { "." })?;
+            match identifier {
+                PrereleaseIdentifier::Numeric(number) => write!(f, "{}", number)?,
+                PrereleaseIdentifier::Alphanumeric(text) => f.write_str(text)?,
+            }
+        }
+        Ok(())
     }
 }
 
@@ -150,6 +598,21 @@ impl fmt::Display for FileVersion {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_from_vs_version_u64() {
+        // 10.0.19041.3636 as stored in a Windows system DLL
+        let raw = (19041u64 << 48) | (3636u64 << 32) | (10u64 << 16);
+        assert_eq!(
+            FileVersion::from_vs_version_u64(raw),
+            FileVersion::new(Some(10), Some(0), Some(19041), Some(3636))
+        );
+        assert_eq!(
+            FileVersion::from_vs_version_u64(u64::MAX).to_string(),
+            "65535.65535.65535.65535"
+        );
+        assert_eq!(FileVersion::from_vs_version_u64(0).to_string(), "0.0.0.0");
+    }
+
     #[test]
     fn test_parse_full_version() {
         let version = FileVersion::from_str("1.2.3.4").unwrap();
@@ -205,10 +668,75 @@ mod tests {
         assert_eq!(format!("{}", partial), "1.2.0.0");
     }
 
+    #[test]
+    fn test_display_sparse() {
+        for text in ["1", "1.2", "1.2.3", "1.2.3.4"] {
+            let version = FileVersion::from_str(text).unwrap();
+            assert_eq!(version.display_sparse(), text);
+            assert_eq!(format!("{:#}", version), text);
+        }
+        assert_eq!(FileVersion::from_str("1").unwrap().to_string(), "1.0.0.0");
+
+        // Zeros that were given are kept, unset parts in between stay empty
+        assert_eq!(
+            FileVersion::from_str("1.0").unwrap().display_sparse(),
+            "1.0"
+        );
+        assert_eq!(
+            FileVersion::new(Some(1), None, Some(3), None).display_sparse(),
+            "1..3"
+        );
+        assert_eq!(
+            FileVersion::new(None, None, None, None).display_sparse(),
+            ""
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        for text in ["1.2.3.4", "1.2", "10", "1..3", ""] {
+            let version = FileVersion::from_str(text).unwrap();
+            let json = serde_json::to_string(&version).unwrap();
+            assert_eq!(json, format!("\"{}\"", text));
+
+            let parsed: FileVersion = serde_json::from_str(&json).unwrap();
+            assert_eq!(
+                [parsed.major, parsed.minor, parsed.build, parsed.private],
+                [version.major, version.minor, version.build, version.private]
+            );
+        }
+
+        let parsed: Vec<FileVersion> = serde_json::from_str(r#"["1.2", "2.0.0.1"]"#).unwrap();
+        assert_eq!(parsed[0].build, None);
+        assert_eq!(parsed[1].private, Some(1));
+        assert!(serde_json::from_str::<FileVersion>(r#""1.x""#).is_err());
+        assert!(serde_json::from_str::<FileVersion>("1").is_err());
+    }
+
     #[test]
     fn test_invalid_version() {
         let result = FileVersion::from_str("abc");
         assert!(result.is_err());
+
+        let error = FileVersion::from_str("1.2.3.4.5").unwrap_err();
+        assert_eq!(error.to_string(), "version has more than 4 components");
+        assert!(FileVersion::from_str("1.2.3.4.").is_err());
+    }
+
+    #[test]
+    fn test_v_prefix() {
+        let version = FileVersion::from_str("v1.2").unwrap();
+        assert_eq!(version.major, Some(1));
+        assert_eq!(version.minor, Some(2));
+        assert_eq!(version.build, None);
+
+        let version = FileVersion::from_str("V1.2.3.4").unwrap();
+        assert_eq!(version, FileVersion::from_str("1.2.3.4").unwrap());
+
+        assert!(FileVersion::from_str("vv1.0").is_err());
+        assert!(FileVersion::from_str("v1.x").is_err());
+        assert!(FileVersion::from_str("1.v2").is_err());
     }
 
     #[test]
@@ -234,4 +762,203 @@ mod tests {
         assert!(v1 <= v2);
         assert!(v1 <= v3);
     }
+
+    #[test]
+    fn test_parse_version_pattern() {
+        let pattern = VersionPattern::from_str("1.*.3").unwrap();
+        assert_eq!(pattern.major, Some(1));
+        assert_eq!(pattern.minor, None);
+        assert_eq!(pattern.build, Some(3));
+        assert_eq!(pattern.private, None);
+        assert_eq!(pattern.to_string(), "1.*.3.*");
+
+        assert!(VersionPattern::from_str("1.2.3.4.5").is_err());
+        assert!(VersionPattern::from_str("1..3").is_err());
+        assert!(VersionPattern::from_str("1.x").is_err());
+    }
+
+    #[test]
+    fn test_version_pattern_matches() {
+        let version = FileVersion::from_str("1.2.3.4").unwrap();
+
+        assert!(
+            VersionPattern::from_str("1.2.3.4")
+                .unwrap()
+                .matches(&version)
+        );
+        assert!(VersionPattern::from_str("1.2").unwrap().matches(&version));
+        assert!(
+            VersionPattern::from_str("*.2.*.4")
+                .unwrap()
+                .matches(&version)
+        );
+        assert!(VersionPattern::from_str("*").unwrap().matches(&version));
+        assert!(
+            !VersionPattern::from_str("1.2.3.5")
+                .unwrap()
+                .matches(&version)
+        );
+        assert!(!VersionPattern::from_str("2.*").unwrap().matches(&version));
+
+        // Unset version parts compare as zero
+        let partial = FileVersion::from_str("1.2").unwrap();
+        assert!(
+            VersionPattern::from_str("1.2.0.0")
+                .unwrap()
+                .matches(&partial)
+        );
+    }
+
+    #[test]
+    fn test_unset_parts_are_zero() {
+        let partial = FileVersion::from_str("1.2").unwrap();
+        let full = FileVersion::from_str("1.2.0.0").unwrap();
+
+        assert_eq!(partial, full);
+        assert_eq!(partial.cmp(&full), Ordering::Equal);
+        assert_eq!(partial.to_string(), "1.2.0.0");
+        assert!(partial < FileVersion::from_str("1.2.0.1").unwrap());
+        assert!(partial > FileVersion::from_str("1.1.9").unwrap());
+        assert_eq!(
+            FileVersion::from_str("").unwrap(),
+            FileVersion::from_str("0").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_pattern_bounds() {
+        let pattern: VersionPattern = "1.2.*".parse().unwrap();
+        assert_eq!(pattern.lowest(), FileVersion::from_str("1.2.0.0").unwrap());
+        assert_eq!(
+            pattern.highest(),
+            FileVersion::from_str("1.2.65535.65535").unwrap()
+        );
+        assert!(pattern.highest() < FileVersion::from_str("1.3.0.0").unwrap());
+
+        // Omitted trailing parts are wildcards
+        let pattern: VersionPattern = "1".parse().unwrap();
+        assert_eq!(pattern.highest().to_string(), "1.65535.65535.65535");
+
+        // Without wildcards both bounds are the version itself
+        let pattern: VersionPattern = "1.2.3.4".parse().unwrap();
+        assert_eq!(pattern.lowest(), pattern.highest());
+    }
+
+    #[test]
+    fn test_parse_version_range() {
+        let version = |text: &str| Some(FileVersion::from_str(text).unwrap());
+
+        assert_eq!(
+            parse_version_range("1.0.0.0..2.0.0.0").unwrap(),
+            (version("1.0.0.0"), version("2.0.0.0"))
+        );
+        assert_eq!(
+            parse_version_range("..2.0.0.0").unwrap(),
+            (None, version("2.0.0.0"))
+        );
+        assert_eq!(
+            parse_version_range("1.0.0.0..").unwrap(),
+            (version("1.0.0.0"), None)
+        );
+        assert_eq!(
+            parse_version_range("1.2..1.2").unwrap(),
+            (version("1.2"), version("1.2"))
+        );
+        assert_eq!(
+            parse_version_range("1.2.*..1.4.*").unwrap(),
+            (version("1.2.0.0"), version("1.4.65535.65535"))
+        );
+        assert_eq!(
+            parse_version_range("v1.0..v2.0").unwrap(),
+            (version("1.0"), version("2.0"))
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_version_range() {
+        for spec in ["", "..", "1.0", "1.0-2.0", "x..2.0", "1.0..2.x", "2.0..1.0"] {
+            assert!(parse_version_range(spec).is_err(), "{}", spec);
+        }
+        assert_eq!(
+            parse_version_range("2.0..1.0").unwrap_err().to_string(),
+            "minimum 2.0.0.0 is higher than maximum 1.0.0.0"
+        );
+    }
+
+    #[test]
+    fn test_semantic_version_parse() {
+        let version: SemanticVersion = "1.2.3-rc.1".parse().unwrap();
+        assert_eq!((version.major, version.minor, version.patch), (1, 2, 3));
+        assert_eq!(
+            version.prerelease,
+            vec![
+                PrereleaseIdentifier::Alphanumeric("rc".to_string()),
+                PrereleaseIdentifier::Numeric(1)
+            ]
+        );
+        assert!(version.is_prerelease());
+
+        // Lenient forms found in version strings
+        assert_eq!(
+            "v2".parse::<SemanticVersion>().unwrap().to_string(),
+            "2.0.0"
+        );
+        assert_eq!(
+            "1.2.3-beta+exp.sha.5114f85"
+                .parse::<SemanticVersion>()
+                .unwrap()
+                .to_string(),
+            "1.2.3-beta"
+        );
+
+        for invalid in [
+            "",
+            "1.2.3.4",
+            "1..3",
+            "1.2.x",
+            "1.2.3-",
+            "1.2.3-rc..1",
+            "1.2.3-rc_1",
+        ] {
+            assert!(invalid.parse::<SemanticVersion>().is_err(), "{}", invalid);
+        }
+
+        // Strict PE version parsing is unaffected
+        assert!(FileVersion::from_str("1.2.3-rc1").is_err());
+    }
+
+    #[test]
+    fn test_semantic_version_precedence() {
+        // Ordered example from the semver specification
+        let ordered = [
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+            "1.2.3-rc1",
+            "1.2.3",
+            "1.10.0",
+        ];
+        let versions: Vec<SemanticVersion> = ordered.iter().map(|v| v.parse().unwrap()).collect();
+        for pair in versions.windows(2) {
+            assert!(pair[0] < pair[1], "{} < {}", pair[0], pair[1]);
+        }
+
+        // Build metadata does not affect precedence
+        assert_eq!(
+            "1.0.0+a".parse::<SemanticVersion>().unwrap(),
+            "1.0.0+b".parse::<SemanticVersion>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_semantic_version_from_file_version() {
+        let version = SemanticVersion::from(FileVersion::from_str("10.0.19041.1").unwrap());
+        assert_eq!(version.to_string(), "10.0.19041");
+        assert!(!version.is_prerelease());
+    }
 }
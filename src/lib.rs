@@ -7,6 +7,9 @@
 //!
 //! - Recursive file search with pattern matching
 //! - Extract and display file version information from PE files (.exe, .dll)
+//!   ELF shared objects (.so), Mach-O dylibs (.dylib) and MSI packages (.msi)
+//! - Read the AssemblyVersion of managed .NET assemblies
+//! - Read semver-style ProductVersion strings such as `1.2.3-beta`
 //! - Filter files by version constraints (min/max)
 //! - Sort output by file path
 //! - Export results to file
@@ -23,7 +26,13 @@
 //! ```
 
 pub mod cli;
+pub mod clr;
 pub mod file_lister;
 pub mod file_version;
+pub mod open_limiter;
 pub mod output;
+pub mod progress;
+pub mod search;
+pub mod streams;
+pub mod timestamp;
 pub mod version_reader;
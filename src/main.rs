@@ -1,10 +1,24 @@
 use clap::Parser;
 use flist::cli::CliArgs;
-use flist::file_lister;
-use flist::file_version::FileVersion;
-use flist::output;
-use std::path::PathBuf;
+use flist::file_lister::{
+    self, CollectOptions, EnumerateOptions, FileInfo, PathOrder, SortDirection, WalkErrors,
+};
+use flist::file_version::{self, FileVersion, VersionPattern};
+use flist::output::{
+    self, DisplayOptions, LineTemplate, OutputFormat, RunParameters, TemplateField,
+    VERBOSITY_NORMAL, VERBOSITY_QUIET, VERBOSITY_SILENT, VERBOSITY_VERBOSE,
+};
+use flist::progress::{Heartbeat, ProgressBar, ScanProgress};
+use flist::search::Stats;
+use flist::streams;
+use flist::timestamp;
+use flist::version_reader::VersionCache;
+use is_terminal::IsTerminal;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Main entry point for the FList application.
 ///
@@ -12,19 +26,80 @@ use std::str::FromStr;
 /// optionally extracts version information, filters and sorts results,
 /// and outputs to console and/or file.
 fn main() -> Result<(), anyhow::Error> {
+    let start = Instant::now();
     let mut args = CliArgs::parse();
 
     // Auto-enable version info if min/max version specified
     args.normalize();
+    // Summary lines need the default verbosity, warnings only disappear with -qq
+    let verbosity = args.verbosity();
+    let quiet = verbosity < VERBOSITY_NORMAL;
 
-    // Parse version strings
+    // Describe the JSON output without searching
+    if args.print_schema {
+        return print_schema();
+    }
+
+    // Check a pattern against a name without searching
+    if let Some([pattern, name]) = args.test_pattern.as_deref() {
+        let mut matcher = file_lister::NameMatcher::new(&[pattern.as_str()])
+            .map_err(|e| anyhow::anyhow!("Invalid pattern '{}': {}", pattern, e))?
+            .ignore_case(args.ignore_case);
+        if args.full_path {
+            // The name is given relative to the current directory
+            matcher = matcher.full_path(std::path::Path::new(""));
+        }
+        let name = std::path::Path::new(name);
+        let matched = matcher.matches_path(name);
+        println!(
+            "Pattern \"{}\" {} \"{}\".",
+            pattern,
+            if matched { "matches" } else { "does not match" },
+            name.display()
+        );
+        if !quiet {
+            println!(
+                "Matched against \"{}\" ({}).",
+                matcher.match_subject(name).unwrap_or_default(),
+                matcher.describe_mode()
+            );
+        }
+        std::process::exit(if matched { 0 } else { 1 });
+    }
+
+    // JSON and CSV are parsed by other tools, summary lines would break them
+    let structured = args.format != OutputFormat::Text;
+    if structured && args.detect_regressions {
+        anyhow::bail!("--detect-regressions requires --format text");
+    }
+    // Two JSON arrays or XML documents in one file are not valid JSON or XML,
+    // and a second Markdown table would repeat the header
+    match args.file_format() {
+        OutputFormat::Json if args.append => {
+            anyhow::bail!("--append cannot be used with --format json")
+        }
+        OutputFormat::Xml if args.append => {
+            anyhow::bail!("--append cannot be used with --format xml")
+        }
+        OutputFormat::Markdown if args.append => {
+            anyhow::bail!("--append cannot be used with --format markdown")
+        }
+        _ => {}
+    }
+
+    // Parse version strings; a wildcard bound covers the whole matching range
     let min_version = args
         .min_version
         .as_ref()
         .map(|s| {
-            FileVersion::from_str(s).map_err(|e| {
+            let version = if s.contains('*') {
+                VersionPattern::from_str(s).map(|pattern| pattern.lowest())
+            } else {
+                FileVersion::from_str(s)
+            };
+            version.map_err(|e| {
                 anyhow::anyhow!(
-                    "Invalid minimum version '{}': {}. Expected format: major.minor.build.private (e.g., 1.2.3.4)",
+                    "Invalid minimum version '{}': {}. Expected format: major.minor.build.private (e.g., 1.2.3.4 or 1.2.*)",
                     s, e
                 )
             })
@@ -35,15 +110,94 @@ fn main() -> Result<(), anyhow::Error> {
         .max_version
         .as_ref()
         .map(|s| {
-            FileVersion::from_str(s).map_err(|e| {
+            let version = if s.contains('*') {
+                VersionPattern::from_str(s).map(|pattern| pattern.highest())
+            } else {
+                FileVersion::from_str(s)
+            };
+            version.map_err(|e| {
+                anyhow::anyhow!(
+                    "Invalid maximum version '{}': {}. Expected format: major.minor.build.private (e.g., 2.0.0.0 or 1.2.*)",
+                    s, e
+                )
+            })
+        })
+        .transpose()?;
+
+    // A version range sets both bounds at once
+    let (min_version, max_version) = match &args.version_range {
+        Some(range) => file_version::parse_version_range(range).map_err(|e| {
+            anyhow::anyhow!(
+                "Invalid version range '{}': {}. Expected format: MIN..MAX, MIN.. or ..MAX (e.g., 1.0.0.0..2.0.0.0)",
+                range, e
+            )
+        })?,
+        None => (min_version, max_version),
+    };
+
+    let exclude_versions = args
+        .exclude_versions
+        .iter()
+        .map(|s| {
+            VersionPattern::from_str(s).map_err(|e| {
                 anyhow::anyhow!(
-                    "Invalid maximum version '{}': {}. Expected format: major.minor.build.private (e.g., 1.2.3.4)",
+                    "Invalid excluded version '{}': {}. Expected format: major.minor.build.private, * matches any part (e.g., 1.2.*)",
                     s, e
                 )
             })
         })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let exact_version = args
+        .exact_version
+        .as_ref()
+        .map(|s| {
+            VersionPattern::from_str(s).map_err(|e| {
+                anyhow::anyhow!(
+                    "Invalid version '{}': {}. Expected format: major.minor.build.private, omitted parts match any value (e.g., 1.2)",
+                    s, e
+                )
+            })
+        })
+        .transpose()?;
+
+    let template = args
+        .template
+        .as_ref()
+        .map(|s| {
+            output::parse_field_separator(s)
+                .parse::<LineTemplate>()
+                .map_err(|e| anyhow::anyhow!("Invalid template '{}': {}", s, e))
+        })
+        .transpose()?;
+    let template_uses = |field| template.as_ref().is_some_and(|t| t.uses(field));
+
+    let parse_time = |time: &Option<String>, name: &str| {
+        time.as_ref()
+            .map(|s| {
+                timestamp::parse_time(s)
+                    .map_err(|e| anyhow::anyhow!("Invalid {} time '{}': {}", name, s, e))
+            })
+            .transpose()
+    };
+    let created_after = parse_time(&args.created_after, "--created-after")?;
+    let created_before = parse_time(&args.created_before, "--created-before")?;
+    let modified_after = parse_time(&args.modified_after, "--modified-after")?;
+    let modified_before = parse_time(&args.modified_before, "--modified-before")?;
+
+    // Read the reference version for the consistency check
+    let reference_version = args
+        .at_least_version_of
+        .as_ref()
+        .map(|reference| {
+            file_lister::read_reference_version(Path::new(reference), args.version_field)
+        })
         .transpose()?;
 
+    if args.ads && !streams::is_supported() && verbosity > VERBOSITY_SILENT {
+        eprintln!("Alternate data streams are only supported on Windows, --ads is ignored.");
+    }
+
     // Determine working directory
     let directory = args
         .directory
@@ -51,45 +205,701 @@ fn main() -> Result<(), anyhow::Error> {
         .map(PathBuf::from)
         .unwrap_or_else(|| std::env::current_dir().unwrap());
 
+    // Search several directories listed in a file, merging their results
+    let directories = match &args.dirs_from {
+        Some(list) => {
+            let directories = file_lister::read_directories_file(Path::new(list))
+                .map_err(|e| anyhow::anyhow!("Failed to read directory list '{}': {}", list, e))?;
+            if directories.is_empty() {
+                anyhow::bail!("Directory list '{}' contains no directories", list);
+            }
+            directories
+        }
+        None => vec![directory.clone()],
+    };
+
+    // Combine the inline pattern with patterns read from a file
+    let mut patterns: Vec<String> = args.pattern.clone();
+    if let Some(pattern_file) = &args.pattern_from {
+        let file_patterns = file_lister::read_patterns_file(std::path::Path::new(pattern_file))
+            .map_err(|e| {
+                anyhow::anyhow!("Failed to read patterns file '{}': {}", pattern_file, e)
+            })?;
+        patterns.extend(file_patterns);
+    }
+
+    // Validate the search without walking the directory
+    if args.check {
+        for directory in &directories {
+            check_search(directory, &patterns, &args.exclude)?;
+            if !args.no_flistignore {
+                file_lister::read_flistignore(directory)?;
+            }
+        }
+        if !quiet {
+            match directories.as_slice() {
+                [directory] => println!(
+                    "Patterns and directory \"{}\" are valid.",
+                    directory.display()
+                ),
+                _ => println!("Patterns and {} directories are valid.", directories.len()),
+            }
+        }
+        return Ok(());
+    }
+
+    // Exact file paths, e.g. expanded by the shell, skip directory enumeration entirely
+    let direct_files: Option<Vec<PathBuf>> = if args.stdin_paths {
+        Some(read_stdin_paths(&args, verbosity)?)
+    } else {
+        args.direct_files()?
+    };
+
+    // A relative --after-path names a file in the searched directory
+    let after_path = args.after_path.as_ref().map(|after| {
+        let after = Path::new(after);
+        if direct_files.is_none() && args.dirs_from.is_none() {
+            file_lister::resolve_after_path(after, &directory)
+        } else {
+            after.to_path_buf()
+        }
+    });
+
     // Print header
-    if !args.quiet {
-        println!(
-            "List files in \"{}\" and its subdirectories.",
-            directory.display()
-        );
+    if !quiet && !structured && !args.count && !args.null {
+        match direct_files.as_deref() {
+            Some([path]) => println!("List file \"{}\".", path.display()),
+            Some(paths) => println!("List {} files.", paths.len()),
+            None if args.dirs_from.is_some() => {
+                println!("List files in {} directories.", directories.len())
+            }
+            None if args.depth.is_some_and(|depth| depth <= 1) => {
+                println!("List files in \"{}\".", directory.display())
+            }
+            None => println!(
+                "List files in \"{}\" and its subdirectories.",
+                directory.display()
+            ),
+        }
         println!("Use \"flist --help\" to print help.");
         println!();
     }
 
-    // Enumerate files
-    let files = file_lister::enumerate_files(&directory, &args.pattern)
+    // Report progress periodically while searching and reading files
+    let progress = Arc::new(ScanProgress::default());
+    let heartbeat = (args.progress_interval > 0 && !quiet).then(|| {
+        Heartbeat::start(
+            Duration::from_secs(args.progress_interval),
+            Arc::clone(&progress),
+        )
+    });
+    // A progress bar only makes sense on a terminal; piped output stays clean
+    let progress_bar = (args.progress
+        && !quiet
+        && std::io::stdout().is_terminal()
+        && std::io::stderr().is_terminal())
+    .then(|| ProgressBar::start(Arc::clone(&progress)));
+
+    // Entries that could not be read, reported after the listing
+    let walk_errors = Arc::new(WalkErrors::default());
+    let enumerate_options = EnumerateOptions {
+        include_dirs: args.match_dirs,
+        max_depth: args.depth,
+        exclude: args.exclude.clone(),
+        ignore_case: args.ignore_case,
+        use_gitignore: args.use_gitignore,
+        use_flistignore: !args.no_flistignore,
+        follow_symlinks: args.follow_symlinks,
+        full_path: args.full_path,
+        progress: Some(Arc::clone(&progress)),
+        errors: (args.show_errors || args.follow_symlinks).then(|| Arc::clone(&walk_errors)),
+    };
+    let collect_options = CollectOptions {
+        include_version: args.include_file_version || template_uses(TemplateField::Version),
+        include_product_version: args.product_version
+            || template_uses(TemplateField::ProductVersion),
+        include_assembly_version: args.assembly_version
+            || template_uses(TemplateField::AssemblyVersion),
+        read_semantic_version: args.semver
+            || args.min_semver.is_some()
+            || args.max_semver.is_some(),
+        read_strings: !args.strings.is_empty(),
+        strings_language: args.language,
+        verify_checksum: args.verify_pe_checksum,
+        max_open_files: Some(args.max_open_files),
+        list_streams: args.ads,
+        read_buffer_size: Some(args.buffer_size),
+        include_dirs: args.match_dirs,
+        read_created: args.show_created
+            || created_after.is_some()
+            || created_before.is_some()
+            || template_uses(TemplateField::Created),
+        read_size: structured
+            || args.file_format() != OutputFormat::Text
+            || args.show_size
+            || !(quiet || args.null || args.can_stream())
+            || args.min_size.is_some()
+            || args.max_size.is_some()
+            || template_uses(TemplateField::Size),
+        read_modified: structured
+            || args.file_format() != OutputFormat::Text
+            || modified_after.is_some()
+            || modified_before.is_some()
+            || template_uses(TemplateField::Modified),
+        read_pe_timestamp: args.show_pe_timestamp,
+        read_arch: args.show_arch || args.arch.is_some() || template_uses(TemplateField::Arch),
+        read_signed: args.unsigned,
+        compute_hash: args.dedup,
+        threads: args.threads,
+        progress: Some(Arc::clone(&progress)),
+        version_cache: Some(Arc::new(VersionCache::new())),
+    };
+    let mut display_options = DisplayOptions {
+        include_version: args.include_file_version,
+        show_product_version: args.product_version,
+        show_assembly_version: args.assembly_version,
+        show_semantic_version: args.semver,
+        string_fields: args.strings.clone(),
+        quiet,
+        show_checksum: args.verify_pe_checksum,
+        show_size: args.show_size,
+        show_created: args.show_created,
+        show_pe_timestamp: args.show_pe_timestamp,
+        show_arch: args.show_arch,
+        show_root: args.show_root,
+        max_width: args.max_width,
+        field_separator: args
+            .field_separator
+            .as_deref()
+            .map(output::parse_field_separator),
+        path_style: args.paths,
+        root: directory.clone(),
+        group_separator: args.group_separator,
+        group_key: args.sort.first().copied().unwrap_or_default(),
+        file_summary: args.file_summary,
+        color: args.color.enabled(),
+        total: None,
+        null_separated: args.null,
+        template,
+        group_by_dir: args.group_by_dir,
+    };
+
+    // Without sorting or filters over all files, print each file as it is found
+    let patterns_ref: Vec<&str> = patterns.iter().map(String::as_str).collect();
+    if direct_files.is_none() && args.can_stream() {
+        let files = file_lister::enumerate_files_iter_with_options(
+            &directory,
+            &patterns_ref,
+            &enumerate_options,
+        )
         .map_err(|e| anyhow::anyhow!("Failed to enumerate files: {}", e))?;
 
-    // Collect file info with versions
-    let mut file_infos = file_lister::collect_file_info(files, args.include_file_version);
+        let widths = output::fixed_column_widths(&display_options);
+        let mut count = 0;
+        let mut limited = false;
+        for path in files {
+            if !args.ext.is_empty() && !file_lister::has_extension(&path, &args.ext) {
+                continue;
+            }
+            // Stop searching once enough files were printed
+            if args.limit.is_some_and(|limit| count >= limit) {
+                limited = true;
+                break;
+            }
+
+            let mut report = file_lister::collect_file_info_report(vec![path], &collect_options);
+            let vanished = &report.vanished;
+            output::print_skipped(
+                vanished,
+                "vanished during scan",
+                verbosity,
+                VERBOSITY_VERBOSE,
+            );
+            if args.absolute {
+                canonicalize_paths(&mut report.files, verbosity);
+            }
+            for file_info in &report.files {
+                let print = || match args.format {
+                    OutputFormat::Ndjson => output::write_ndjson_line(
+                        &mut std::io::stdout().lock(),
+                        file_info,
+                        &display_options,
+                    )
+                    .map_err(|e| anyhow::anyhow!("Failed to write NDJSON output: {}", e)),
+                    _ => {
+                        output::print_file(file_info, &widths, &display_options);
+                        Ok(())
+                    }
+                };
+                if !args.count {
+                    match &progress_bar {
+                        Some(bar) => bar.suspend(print)?,
+                        None => print()?,
+                    }
+                }
+                count += 1;
+            }
+        }
+        drop(heartbeat);
+        drop(progress_bar);
+        if args.count {
+            println!("{}", count);
+        } else if !structured {
+            if limited && !quiet && !args.null {
+                println!();
+                println!("Showing the first {} files.", count);
+            } else {
+                output::print_footer(count, &display_options);
+            }
+        }
+        let stats = Stats {
+            seen: progress.seen(),
+            matched: progress.matched(),
+            errors: walk_errors.len(),
+            ..Default::default()
+        };
+        report_walk_errors(&walk_errors, args.show_errors, verbosity);
+        output::print_stats(&stats, verbosity);
+        output::print_timings(&[], start.elapsed(), verbosity);
+        exit_if_empty(count, args.fail_if_empty);
+        return Ok(());
+    }
+
+    // Enumerate files and collect file info with versions
+    let scan_start = Instant::now();
+    let mut enumerate_time = Duration::ZERO;
+    let report = match direct_files {
+        Some(paths) if !args.ext.is_empty() => file_lister::collect_file_info_report(
+            file_lister::filter_by_extensions(paths, &args.ext),
+            &collect_options,
+        ),
+        Some(paths) => file_lister::collect_file_info_report(paths, &collect_options),
+        None => {
+            let mut report = file_lister::CollectReport::default();
+            for root in &directories {
+                let enumerate_start = Instant::now();
+                let files = file_lister::enumerate_files_with_options(
+                    root,
+                    &patterns_ref,
+                    &enumerate_options,
+                )
+                .map_err(|e| {
+                    anyhow::anyhow!("Failed to enumerate files in '{}': {}", root.display(), e)
+                })?;
+                enumerate_time += enumerate_start.elapsed();
+                let files = if args.ext.is_empty() {
+                    files
+                } else {
+                    file_lister::filter_by_extensions(files, &args.ext)
+                };
+                let mut part = file_lister::collect_file_info_report(files, &collect_options);
+                if args.dirs_from.is_some() {
+                    for file_info in &mut part.files {
+                        file_info.root = Some(root.clone());
+                    }
+                }
+                report.files.extend(part.files);
+                report.vanished.extend(part.vanished);
+            }
+            report
+        }
+    };
+    drop(heartbeat);
+    drop(progress_bar);
+    let scanned = Instant::now();
+    let walk_error_count = walk_errors.len();
+    let vanished = &report.vanished;
+    output::print_skipped(
+        vanished,
+        "vanished during scan",
+        verbosity,
+        VERBOSITY_VERBOSE,
+    );
+    let mut file_infos = report.files;
+    if args.absolute {
+        canonicalize_paths(&mut file_infos, verbosity);
+    }
+    let collected = file_infos.len();
 
     // Filter by version
     if min_version.is_some() || max_version.is_some() {
-        file_infos = file_lister::filter_by_version(file_infos, min_version, max_version);
+        file_infos = apply_filter(
+            file_infos,
+            "version outside --minv/--maxv",
+            verbosity,
+            |files| {
+                file_lister::filter_by_version_of(
+                    files,
+                    min_version,
+                    max_version,
+                    args.version_field,
+                )
+            },
+        );
     }
 
-    // Sort if requested
-    if args.sort_by_path {
-        file_infos = file_lister::sort_by_path(file_infos);
+    // Filter by the semantic version of the ProductVersion string
+    if args.min_semver.is_some() || args.max_semver.is_some() {
+        file_infos = apply_filter(
+            file_infos,
+            "semantic version outside --min-semver/--max-semver",
+            verbosity,
+            |files| {
+                file_lister::filter_by_semantic_version(
+                    files,
+                    args.min_semver.as_ref(),
+                    args.max_semver.as_ref(),
+                )
+            },
+        );
+    }
+
+    // Filter by size
+    if args.min_size.is_some() || args.max_size.is_some() {
+        file_infos = apply_filter(
+            file_infos,
+            "size outside --min-size/--max-size",
+            verbosity,
+            |files| file_lister::filter_by_size(files, args.min_size, args.max_size),
+        );
+    }
+
+    // Filter by modification time
+    if modified_after.is_some() || modified_before.is_some() {
+        let reason = "modified outside --modified-after/--modified-before";
+        file_infos = apply_filter(file_infos, reason, verbosity, |files| {
+            file_lister::filter_by_mtime(files, modified_after, modified_before)
+        });
+    }
+
+    // Filter by creation time
+    if created_after.is_some() || created_before.is_some() {
+        let unknown = file_infos.iter().filter(|f| f.created.is_none()).count();
+        if unknown > 0 && verbosity > VERBOSITY_SILENT {
+            eprintln!(
+                "Creation time is not available for {} files on this platform or filesystem, they are excluded.",
+                unknown
+            );
+        }
+        let reason = "created outside --created-after/--created-before";
+        file_infos = apply_filter(file_infos, reason, verbosity, |files| {
+            file_lister::filter_by_created(files, created_after, created_before)
+        });
+    }
+
+    // Drop files matching an excluded version
+    if !exclude_versions.is_empty() {
+        file_infos = apply_filter(
+            file_infos,
+            "version excluded by --neqv",
+            verbosity,
+            |files| {
+                file_lister::filter_excluded_versions_of(
+                    files,
+                    &exclude_versions,
+                    args.version_field,
+                )
+            },
+        );
+    }
+
+    // Keep only files of the requested version
+    if let Some(version) = &exact_version {
+        file_infos = apply_filter(
+            file_infos,
+            "version does not match --eq",
+            verbosity,
+            |files| file_lister::filter_by_exact_version_of(files, version, args.version_field),
+        );
+    }
+
+    // Keep only files older than the reference file
+    if let Some(reference) = reference_version {
+        let reason = "not older than the --at-least-version-of reference";
+        file_infos = apply_filter(file_infos, reason, verbosity, |files| {
+            file_lister::filter_older_than_of(files, reference, args.version_field)
+        });
+    }
+
+    // Keep only files whose file and product version differ
+    if args.mismatched {
+        let reason = "file and product version match";
+        file_infos = apply_filter(file_infos, reason, verbosity, |files| {
+            file_lister::filter_mismatched_versions(files)
+        });
+    }
+
+    // Keep only files with, or only files without, version information
+    if args.has_version || args.no_version {
+        let reason = if args.has_version {
+            "no version information"
+        } else {
+            "has version information"
+        };
+        file_infos = apply_filter(file_infos, reason, verbosity, |files| {
+            file_lister::filter_by_has_version(files, args.has_version)
+        });
+    }
+
+    // Keep only files built for the requested architecture
+    if let Some(arch) = args.arch {
+        let reason = format!("not built for {}", arch);
+        file_infos = apply_filter(file_infos, &reason, verbosity, |files| {
+            file_lister::filter_by_arch(files, arch)
+        });
+    }
+
+    // Keep only PE files without an embedded signature
+    if args.unsigned {
+        file_infos = apply_filter(file_infos, "signed or not a PE file", verbosity, |files| {
+            file_lister::filter_unsigned(files)
+        });
+    }
+
+    // Keep only files with a checksum mismatch
+    if args.bad_checksum_only {
+        file_infos = apply_filter(file_infos, "checksum matches", verbosity, |files| {
+            file_lister::filter_bad_checksum(files)
+        });
+    }
+
+    // Keep only the highest version of each file name
+    if args.keep_highest {
+        let reason = "a higher version of the same name exists";
+        file_infos = apply_filter(file_infos, reason, verbosity, |files| {
+            file_lister::keep_highest_version(files)
+        });
+    }
+
+    let filtered_out = collected - file_infos.len();
+
+    // Sort by the requested keys (path by default) unless the raw enumeration
+    // order was requested
+    if !args.unordered {
+        let direction = if args.reverse {
+            SortDirection::Descending
+        } else {
+            SortDirection::Ascending
+        };
+        let order = if args.natural_sort {
+            PathOrder::Natural
+        } else {
+            PathOrder::Lexicographic
+        };
+        file_infos = file_lister::sort_by_keys_with_order(file_infos, &args.sort, direction, order);
+    }
+
+    // Resume after the last path of a previous run
+    if let Some(after_path) = &after_path {
+        file_infos = file_lister::skip_through_path(file_infos, after_path);
+    }
+
+    // Keep only the first files
+    if let Some(limit) = args.limit
+        && file_infos.len() > limit
+    {
+        display_options.total = Some(file_infos.len());
+        file_infos.truncate(limit);
     }
 
     // Output to console
-    output::print_results(&file_infos, args.include_file_version, args.quiet);
+    let filtered = Instant::now();
+    match args.format {
+        OutputFormat::Text if args.count => println!("{}", file_infos.len()),
+        OutputFormat::Text => output::print_results_with_options(&file_infos, &display_options),
+        OutputFormat::Json => output::write_json(&file_infos, &display_options)
+            .map_err(|e| anyhow::anyhow!("Failed to write JSON output: {}", e))?,
+        OutputFormat::Ndjson => output::write_ndjson(&file_infos, &display_options)
+            .map_err(|e| anyhow::anyhow!("Failed to write NDJSON output: {}", e))?,
+        OutputFormat::Csv => output::write_csv(&file_infos, &display_options)
+            .map_err(|e| anyhow::anyhow!("Failed to write CSV output: {}", e))?,
+        OutputFormat::Xml => output::write_xml(&file_infos, &display_options)
+            .map_err(|e| anyhow::anyhow!("Failed to write XML output: {}", e))?,
+        OutputFormat::Markdown => output::write_markdown(&file_infos, &display_options)
+            .map_err(|e| anyhow::anyhow!("Failed to write Markdown output: {}", e))?,
+    }
+
+    // Entries that could not be read while searching
+    report_walk_errors(&walk_errors, args.show_errors, verbosity);
+
+    // Report files older than a same-named file earlier in path order
+    if args.detect_regressions {
+        let regressions = file_lister::find_version_regressions(&file_infos);
+        output::print_regressions(&regressions, quiet);
+    }
+
+    // Groups of files with identical contents
+    if args.dedup {
+        output::print_duplicates(&file_infos);
+    }
+
+    // Histogram of major versions
+    if args.summarize {
+        output::print_version_summary(&file_infos);
+    }
 
     // Output to file if specified
-    if let Some(output_file) = args.output_file {
-        output::write_to_file(
+    if let Some(output_file) = &args.output_file {
+        let output_path = PathBuf::from(output_file);
+        output::write_output_file(
             &file_infos,
-            &PathBuf::from(&output_file),
-            args.include_file_version,
+            &output_path,
+            args.file_format(),
+            &display_options,
+            args.append,
         )
         .map_err(|e| anyhow::anyhow!("Failed to write to output file '{}': {}", output_file, e))?;
     }
 
+    // Write the JSON summary sidecar if specified
+    if let Some(summary_file) = &args.summary_json {
+        let parameters = RunParameters {
+            directory: directory.clone(),
+            patterns,
+            include_version: args.include_file_version,
+            min_version,
+            max_version,
+            exclude_versions,
+            reference_version,
+            created_after,
+            created_before,
+            verify_checksum: args.verify_pe_checksum,
+            min_size: args.min_size,
+            max_size: args.max_size,
+            modified_after,
+            modified_before,
+        };
+        output::write_summary_json(
+            &file_infos,
+            &PathBuf::from(summary_file),
+            &parameters,
+            start.elapsed(),
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to write summary file '{}': {}", summary_file, e))?;
+    }
+
+    let stats = Stats {
+        seen: progress.seen(),
+        matched: progress.matched(),
+        filtered: filtered_out,
+        errors: walk_error_count,
+        enumerate_time,
+        read_time: scanned - scan_start - enumerate_time,
+        filter_time: filtered - scanned,
+    };
+    let mut phases = stats.phases().to_vec();
+    phases.push(("output", filtered.elapsed()));
+    output::print_stats(&stats, verbosity);
+    output::print_timings(&phases, start.elapsed(), verbosity);
+
+    exit_if_empty(file_infos.len(), args.fail_if_empty);
+    Ok(())
+}
+
+/// Checks that the patterns and excludes compile and that the directory can be
+/// read, for `--check`.
+fn check_search(
+    directory: &Path,
+    patterns: &[String],
+    exclude: &[String],
+) -> Result<(), anyhow::Error> {
+    for pattern in patterns {
+        file_lister::NameMatcher::new(&[pattern.as_str()])
+            .map_err(|e| anyhow::anyhow!("Invalid pattern '{}': {}", pattern, e))?;
+    }
+    for pattern in exclude {
+        file_lister::NameMatcher::new(&[pattern.as_str()])
+            .map_err(|e| anyhow::anyhow!("Invalid exclude pattern '{}': {}", pattern, e))?;
+    }
+
+    if !directory.is_dir() {
+        anyhow::bail!(
+            "Directory '{}' does not exist or is not a directory",
+            directory.display()
+        );
+    }
+    std::fs::read_dir(directory)
+        .map_err(|e| anyhow::anyhow!("Cannot read directory '{}': {}", directory.display(), e))?;
+    Ok(())
+}
+
+/// Applies a filter to `files`; with `-v`, prints each file it removed and why.
+fn apply_filter(
+    files: Vec<FileInfo>,
+    reason: &str,
+    verbosity: u8,
+    filter: impl FnOnce(Vec<FileInfo>) -> Vec<FileInfo>,
+) -> Vec<FileInfo> {
+    if verbosity < VERBOSITY_VERBOSE {
+        return filter(files);
+    }
+
+    let before: Vec<PathBuf> = files.iter().map(|file| file.path.clone()).collect();
+    let files = filter(files);
+    let kept: HashSet<&Path> = files.iter().map(|file| file.path.as_path()).collect();
+    let skipped: Vec<PathBuf> = before
+        .into_iter()
+        .filter(|path| !kept.contains(path.as_path()))
+        .collect();
+    output::print_skipped(&skipped, reason, verbosity, VERBOSITY_VERBOSE);
+    files
+}
+
+/// Prints the errors of the search with `--show-errors`, otherwise only the
+/// skipped symbolic link loops unless `-qq`.
+fn report_walk_errors(walk_errors: &WalkErrors, show_errors: bool, verbosity: u8) {
+    let mut errors = walk_errors.take();
+    if !show_errors {
+        errors.retain(|error| error.is_loop && verbosity > VERBOSITY_SILENT);
+    }
+    output::print_walk_errors(&errors);
+}
+
+/// Replaces the paths of `files` by canonical paths for `--absolute`, warning
+/// about paths that could not be resolved unless `-qq`.
+fn canonicalize_paths(files: &mut [FileInfo], verbosity: u8) {
+    for (path, error) in file_lister::canonicalize_paths(files) {
+        if verbosity > VERBOSITY_SILENT {
+            eprintln!(
+                "Warning: could not resolve \"{}\": {}, printing it unresolved.",
+                path.display(),
+                error
+            );
+        }
+    }
+}
+
+/// Prints the JSON Schema of the `--format json` output for `--print-schema`.
+#[cfg(feature = "serde")]
+fn print_schema() -> Result<(), anyhow::Error> {
+    println!("{}", serde_json::to_string_pretty(&output::json_schema())?);
     Ok(())
 }
+
+/// The JSON Schema is generated by the `serde` feature, which is disabled.
+#[cfg(not(feature = "serde"))]
+fn print_schema() -> Result<(), anyhow::Error> {
+    anyhow::bail!("--print-schema requires flist to be built with the serde feature")
+}
+
+/// Reads the files to list from stdin for `--stdin-paths`, reporting paths
+/// that are not existing files unless `-qq`. Paths matching `--exclude` are
+/// left out.
+fn read_stdin_paths(args: &CliArgs, verbosity: u8) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let paths = file_lister::read_path_list(std::io::stdin().lock())
+        .map_err(|e| anyhow::anyhow!("Failed to read paths from stdin: {}", e))?;
+    let (files, missing): (Vec<PathBuf>, Vec<PathBuf>) =
+        paths.into_iter().partition(|path| path.is_file());
+    output::print_skipped(&missing, "not an existing file", verbosity, VERBOSITY_QUIET);
+    let excludes: Vec<&str> = args.exclude.iter().map(String::as_str).collect();
+    file_lister::filter_excluded_paths(files, &excludes, args.ignore_case)
+}
+
+/// Exits with `code` if no files were found and `--fail-if-empty` was given.
+fn exit_if_empty(count: usize, code: Option<u8>) {
+    if let Some(code) = code
+        && count == 0
+    {
+        std::process::exit(code.into());
+    }
+}
@@ -0,0 +1,127 @@
+//! Bounding the number of files open at the same time.
+//!
+//! This module provides [`OpenFileLimiter`], a counting semaphore used around the
+//! sections of the pipeline that open and read files. It keeps scans that read
+//! many files concurrently below the process limit for open file handles.
+
+use std::sync::{Condvar, Mutex};
+
+/// A counting semaphore limiting how many files are open at the same time.
+///
+/// Call [`acquire`](OpenFileLimiter::acquire) before opening a file and keep the
+/// returned permit alive until the file is closed. When the limit is reached,
+/// `acquire` blocks until another permit is dropped.
+///
+/// # Examples
+///
+/// ```
+/// use flist::open_limiter::OpenFileLimiter;
+///
+/// let limiter = OpenFileLimiter::new(2);
+/// let first = limiter.acquire();
+/// let second = limiter.acquire();
+/// assert_eq!(limiter.in_use(), 2);
+/// drop(first);
+/// assert_eq!(limiter.in_use(), 1);
+/// # drop(second);
+/// ```
+#[derive(Debug)]
+pub struct OpenFileLimiter {
+    max_open: usize,
+    open: Mutex<usize>,
+    released: Condvar,
+}
+
+/// A permit to keep one file open, returned by [`OpenFileLimiter::acquire`].
+///
+/// The permit is released when dropped.
+#[derive(Debug)]
+pub struct OpenFilePermit<'a> {
+    limiter: &'a OpenFileLimiter,
+}
+
+impl OpenFileLimiter {
+    /// Default limit, well below the common limits of 1024 open files on Unix
+    /// and the per-process handle limits on Windows.
+    pub const DEFAULT_MAX_OPEN_FILES: usize = 64;
+
+    /// Creates a limiter allowing up to `max_open` files open at the same time.
+    ///
+    /// A limit of `0` is treated as `1`, so callers can never block forever.
+    pub fn new(max_open: usize) -> Self {
+        Self {
+            max_open: max_open.max(1),
+            open: Mutex::new(0),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Waits until a file may be opened and returns a permit for it.
+    pub fn acquire(&self) -> OpenFilePermit<'_> {
+        let mut open = self.open.lock().unwrap_or_else(|e| e.into_inner());
+        while *open >= self.max_open {
+            open = self.released.wait(open).unwrap_or_else(|e| e.into_inner());
+        }
+        *open += 1;
+        OpenFilePermit { limiter: self }
+    }
+
+    /// Returns the number of permits currently held.
+    pub fn in_use(&self) -> usize {
+        *self.open.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for OpenFilePermit<'_> {
+    fn drop(&mut self) {
+        let mut open = self.limiter.open.lock().unwrap_or_else(|e| e.into_inner());
+        *open -= 1;
+        self.limiter.released.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_limiter_caps_concurrency() {
+        let limiter = OpenFileLimiter::new(3);
+        let current = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+
+        thread::scope(|scope| {
+            for _ in 0..16 {
+                scope.spawn(|| {
+                    let _permit = limiter.acquire();
+                    let now = current.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(5));
+                    current.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+        assert_eq!(limiter.in_use(), 0);
+    }
+
+    #[test]
+    fn test_permit_released_on_drop() {
+        let limiter = OpenFileLimiter::new(1);
+        drop(limiter.acquire());
+        // Would block forever if the first permit had not been released
+        let _permit = limiter.acquire();
+        assert_eq!(limiter.in_use(), 1);
+    }
+
+    #[test]
+    fn test_zero_limit_allows_one() {
+        let limiter = OpenFileLimiter::new(0);
+        let _permit = limiter.acquire();
+        assert_eq!(limiter.in_use(), 1);
+    }
+}
@@ -3,10 +3,192 @@
 //! This module provides functions for displaying file listing results to the
 //! console and writing them to output files.
 
-use crate::file_lister::FileInfo;
-use std::fs::File;
+use crate::file_lister::{self, FileInfo, SortKey, VersionRegression, WalkError};
+use crate::file_version::{FileVersion, VersionPattern};
+use crate::search::Stats;
+use crate::timestamp::format_time;
+use crate::version_reader::StringField;
+use clap::ValueEnum;
+use is_terminal::IsTerminal;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{File, OpenOptions};
 use std::io::{BufWriter, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+/// How file paths are printed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum PathStyle {
+    /// Paths as found while searching
+    #[default]
+    Full,
+    /// Paths relative to the search directory
+    Relative,
+    /// Absolute paths
+    Absolute,
+    /// Relative and absolute path, separated by the field separator or a tab
+    Both,
+}
+
+/// Format of the results, on the console or in the output file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Aligned columns with summary lines
+    #[default]
+    Text,
+    /// A JSON array of objects, without summary lines
+    Json,
+    /// One JSON object per line, without summary lines
+    Ndjson,
+    /// A header row and one comma-separated row per file, without summary lines
+    Csv,
+    /// A `<files>` document with one `<file>` element per file, without summary lines
+    Xml,
+    /// A GitHub-flavored Markdown table, without summary lines
+    Markdown,
+}
+
+/// When to color console output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Color if stdout is a terminal and `NO_COLOR` is not set
+    #[default]
+    Auto,
+    /// Always color
+    Always,
+    /// Never color
+    Never,
+}
+
+impl ColorChoice {
+    /// Returns whether console output should be colored.
+    ///
+    /// With [`ColorChoice::Auto`], colors are used when stdout is a terminal and
+    /// the `NO_COLOR` environment variable is unset or empty.
+    pub fn enabled(self) -> bool {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        self.resolve(no_color, std::io::stdout().is_terminal())
+    }
+
+    /// Resolves the choice for the given environment.
+    fn resolve(self, no_color: bool, is_terminal: bool) -> bool {
+        match self {
+            ColorChoice::Auto => is_terminal && !no_color,
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// ANSI style of versions.
+const STYLE_VERSION: &str = "1";
+/// ANSI style of paths.
+const STYLE_PATH: &str = "2";
+/// ANSI style of paths of files without version information.
+const STYLE_NO_VERSION: &str = "31";
+
+/// Verbosity of `-qq`: only the results, not even warnings on stderr.
+pub const VERBOSITY_SILENT: u8 = 0;
+/// Verbosity of `-q`: no header and summary lines, warnings still go to stderr.
+pub const VERBOSITY_QUIET: u8 = 1;
+/// Default verbosity: header, results and summary lines.
+pub const VERBOSITY_NORMAL: u8 = 2;
+/// Verbosity of `-v`: additionally explains why files were skipped by a filter.
+pub const VERBOSITY_VERBOSE: u8 = 3;
+/// Verbosity of `-vv`: additionally prints how long the run took.
+pub const VERBOSITY_TIMING: u8 = 4;
+
+/// Wraps `text` in the ANSI escape sequences of `style` if `color` is set.
+fn paint(text: &str, style: &str, color: bool) -> String {
+    if color {
+        format!("\x1b[{}m{}\x1b[0m", style, text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Options controlling how results are displayed on the console.
+///
+/// These only affect console output. Files written with [`write_to_file`] always
+/// contain full, unmodified paths. The machine-readable formats use them only for
+/// the path style, versions and version strings, see [`FileRecord`].
+///
+/// # Examples
+///
+/// ```
+/// use flist::output::DisplayOptions;
+///
+/// let options = DisplayOptions {
+///     include_version: true,
+///     max_width: Some(60),
+///     ..Default::default()
+/// };
+/// assert!(!options.quiet);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DisplayOptions {
+    /// Whether to display version information
+    pub include_version: bool,
+    /// Whether to display the product version, after the file version
+    pub show_product_version: bool,
+    /// Whether to display the assembly version of .NET assemblies, after the
+    /// product version
+    pub show_assembly_version: bool,
+    /// Whether to display the `ProductVersion` string as a semantic version,
+    /// after the assembly version
+    pub show_semantic_version: bool,
+    /// Version strings to display, after the versions
+    pub string_fields: Vec<StringField>,
+    /// Whether to suppress summary messages
+    pub quiet: bool,
+    /// Whether to display the PE checksum status
+    pub show_checksum: bool,
+    /// Whether to display the file size in bytes, after the version strings
+    pub show_size: bool,
+    /// Whether to display the creation time
+    pub show_created: bool,
+    /// Whether to display the link time from the PE header, after the creation time
+    pub show_pe_timestamp: bool,
+    /// Whether to display the target architecture from the PE header, after the
+    /// link time
+    pub show_arch: bool,
+    /// Whether to display the search directory of each file, before the path
+    pub show_root: bool,
+    /// Maximum display width of a path; longer paths are shortened in the middle
+    pub max_width: Option<usize>,
+    /// Separator between columns; replaces the padded layout when set
+    pub field_separator: Option<String>,
+    /// How file paths are printed
+    pub path_style: PathStyle,
+    /// Search directory that relative paths are computed from
+    pub root: PathBuf,
+    /// Whether to insert a blank line between groups of the active sort key
+    pub group_separator: bool,
+    /// Most significant sort key, which defines the groups: files in the same
+    /// directory for `Path`, otherwise files with an equal key
+    pub group_key: SortKey,
+    /// Whether files written with [`write_to_file_with_options`] include the
+    /// "Found N files." summary lines; independent of `quiet`
+    pub file_summary: bool,
+    /// Whether to color the console output with ANSI escape sequences; see
+    /// [`ColorChoice::enabled`]
+    pub color: bool,
+    /// Number of files found before the results were limited to the first few,
+    /// `None` if they were not limited. Changes the summary lines to
+    /// "Showing N of M files."
+    pub total: Option<usize>,
+    /// Whether to print only paths, each terminated by a NUL character, for
+    /// `xargs -0`; columns, summary lines and group separators are omitted
+    pub null_separated: bool,
+    /// Layout of each file line, replacing the columns when set
+    pub template: Option<LineTemplate>,
+    /// Whether to group the text output by directory, under a `DIR:` header
+    /// per directory followed by the indented file names
+    pub group_by_dir: bool,
+}
 
 /// Prints results to the console.
 ///
@@ -27,135 +209,3649 @@ use std::path::Path;
 /// use flist::output::print_results;
 ///
 /// let files = vec![
-///     FileInfo { path: PathBuf::from("test.dll"), version: None },
+///     FileInfo { path: PathBuf::from("test.dll"), version: None, ..Default::default() },
 /// ];
 /// print_results(&files, false, true);
 /// ```
 pub fn print_results(files: &[FileInfo], include_version: bool, quiet: bool) {
-    if !quiet {
-        println!("Found {} files.", files.len());
+    print_results_with_options(
+        files,
+        &DisplayOptions {
+            include_version,
+            quiet,
+            ..Default::default()
+        },
+    );
+}
+
+/// Prints results to the console using the given display options.
+///
+/// # Arguments
+///
+/// * `files` - Slice of file information to display
+/// * `options` - Display options such as version column and path width
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::FileInfo;
+/// use flist::output::{print_results_with_options, DisplayOptions};
+///
+/// let files = vec![
+///     FileInfo { path: PathBuf::from("some/deeply/nested/test.dll"), version: None, ..Default::default() },
+/// ];
+/// let options = DisplayOptions { quiet: true, max_width: Some(16), ..Default::default() };
+/// print_results_with_options(&files, &options);
+/// ```
+pub fn print_results_with_options(files: &[FileInfo], options: &DisplayOptions) {
+    if !options.quiet && !options.null_separated {
+        println!("{}", summary_line(files.len(), options));
         println!();
     }
 
-    for file_info in files {
-        if include_version {
-            if let Some(version) = file_info.version {
-                println!("{:<15} {}", version, file_info.path.display());
-            } else {
-                println!("{:<15} {}", "", file_info.path.display());
+    let widths = column_widths(files, options);
+    if options.group_by_dir && !options.null_separated {
+        for line in dir_group_lines(files, &widths, options) {
+            println!("{}", line);
+        }
+    } else {
+        for (index, file_info) in files.iter().enumerate() {
+            if options.group_separator
+                && !options.null_separated
+                && index > 0
+                && !same_group(&files[index - 1], file_info, options.group_key)
+            {
+                println!();
             }
-        } else {
-            println!("{}", file_info.path.display());
+
+            print_file(file_info, &widths, options);
         }
     }
 
-    if !quiet {
+    print_footer(files.len(), options);
+    if !options.quiet
+        && !options.null_separated
+        && let Some(line) = total_size_line(files)
+    {
+        println!("{}", line);
+    }
+}
+
+/// Prints the line of a single file, followed by a line per alternate data stream.
+///
+/// Use this with [`print_footer`] to print files one by one as they are found,
+/// passing [`fixed_column_widths`] since later files are not known yet.
+///
+/// # Arguments
+///
+/// * `file_info` - The file to display
+/// * `widths` - Column widths from [`column_widths`] or [`fixed_column_widths`]
+/// * `options` - Display options such as version column and path width
+pub fn print_file(file_info: &FileInfo, widths: &[usize], options: &DisplayOptions) {
+    if options.null_separated {
+        print!("{}", null_record(file_info, options));
+        return;
+    }
+
+    let path = format_entry_path(file_info, options);
+    let path = match options.max_width {
+        Some(max_width) => truncate_middle(&path, max_width),
+        None => path,
+    };
+
+    println!("{}", format_line(file_info, &path, widths, options));
+    for stream in &file_info.streams {
+        let stream_path = format!("{}:{}", path, stream);
+        println!("{}", format_line(file_info, &stream_path, widths, options));
+    }
+}
+
+/// Prints the number of files found after the listing, unless quiet or NUL
+/// separated.
+///
+/// # Arguments
+///
+/// * `count` - Number of files listed
+/// * `options` - Display options, `quiet`, `null_separated` and `total` are used
+pub fn print_footer(count: usize, options: &DisplayOptions) {
+    if !options.quiet && !options.null_separated {
         println!();
-        println!("Found {} files.", files.len());
+        println!("{}", summary_line(count, options));
     }
 }
 
-/// Writes results to a file.
+/// Formats the summary line for `count` listed files.
 ///
-/// Creates or overwrites the specified file with the file listing results.
-/// Each line contains the file path and optionally the version information.
+/// This is "Found N files.", or "Showing N of M files." when the results were
+/// limited from [`DisplayOptions::total`] files.
+fn summary_line(count: usize, options: &DisplayOptions) -> String {
+    match options.total {
+        Some(total) if total > count => format!("Showing {} of {} files.", count, total),
+        _ => format!("Found {} files.", count),
+    }
+}
+
+/// Formats the total size of `files`, as "Matched N files totaling 1.3 GB.".
+///
+/// Files without a size are counted in a note. Returns `None` if no sizes were
+/// read at all.
+fn total_size_line(files: &[FileInfo]) -> Option<String> {
+    let uncounted = files.iter().filter(|f| f.size.is_none()).count();
+    if uncounted == files.len() {
+        return None;
+    }
+
+    let mut line = format!(
+        "Matched {} files totaling {}",
+        files.len(),
+        format_size(file_lister::total_size(files))
+    );
+    if uncounted > 0 {
+        line.push_str(&format!(" ({} files of unknown size)", uncounted));
+    }
+    line.push('.');
+    Some(line)
+}
+
+/// Formats a size in bytes with binary units, such as `512 B` or `1.3 GB`.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Formats the lines of files grouped by directory, for
+/// [`DisplayOptions::group_by_dir`].
+///
+/// Files are ordered by directory, keeping their order within a directory.
+/// Each directory starts with a `DIR:` header, followed by its files indented
+/// by two spaces and shown by name after the selected columns. Directories are
+/// separated by a blank line.
+fn dir_group_lines(files: &[FileInfo], widths: &[usize], options: &DisplayOptions) -> Vec<String> {
+    let mut files: Vec<&FileInfo> = files.iter().collect();
+    files.sort_by(|a, b| a.path.parent().cmp(&b.path.parent()));
+
+    let mut lines = Vec::new();
+    for (index, file_info) in files.iter().enumerate() {
+        if index == 0 || files[index - 1].path.parent() != file_info.path.parent() {
+            if index > 0 {
+                lines.push(String::new());
+            }
+            let dir = match format_parent(file_info, options) {
+                dir if dir.is_empty() => ".".to_string(),
+                dir => dir,
+            };
+            let dir = match options.max_width {
+                Some(max_width) => truncate_middle(&dir, max_width),
+                None => dir,
+            };
+            lines.push(format!(
+                "DIR: {}",
+                paint(&dir, STYLE_VERSION, options.color)
+            ));
+        }
+
+        let name = file_info
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let name = if file_info.is_dir {
+            format!("{}{}", name, std::path::MAIN_SEPARATOR_STR)
+        } else {
+            name
+        };
+        lines.push(format!(
+            "  {}",
+            format_line(file_info, &name, widths, options)
+        ));
+        for stream in &file_info.streams {
+            let stream_name = format!("{}:{}", name, stream);
+            lines.push(format!(
+                "  {}",
+                format_line(file_info, &stream_name, widths, options)
+            ));
+        }
+    }
+    lines
+}
+
+/// Returns whether two adjacent files belong to the same group of `key`: the
+/// same directory for [`SortKey::Path`], otherwise an equal key, e.g. the same
+/// version or extension.
+fn same_group(a: &FileInfo, b: &FileInfo, key: SortKey) -> bool {
+    match key {
+        SortKey::Path => a.path.parent() == b.path.parent(),
+        _ => key.compare(a, b).is_eq(),
+    }
+}
+
+/// Prints version regressions to the console.
+///
+/// Each regression is printed on one line, naming the later file with the lower
+/// version and the earlier file it regresses from. In non-quiet mode, a header
+/// with the number of regressions is printed first.
 ///
 /// # Arguments
 ///
-/// * `files` - Slice of file information to write
-/// * `output_path` - Path to the output file
-/// * `include_version` - Whether to include version information
+/// * `regressions` - The regressions to display
+/// * `quiet` - Whether to suppress the header
 ///
-/// # Returns
+/// # Examples
 ///
-/// `Ok(())` on success, or an error if the file cannot be created or written.
+/// ```
+/// use flist::output::print_regressions;
+///
+/// print_regressions(&[], false);
+/// ```
+pub fn print_regressions(regressions: &[VersionRegression], quiet: bool) {
+    if !quiet {
+        println!();
+        println!("Found {} version regressions.", regressions.len());
+        println!();
+    }
+
+    for regression in regressions {
+        println!("{}", format_regression(regression));
+    }
+}
+
+/// Prints how many files have each major version.
+///
+/// Files are grouped by the major part of their version, in ascending order,
+/// followed by an "unknown" group for files without version information. A
+/// last line gives the totals of files with and without version.
+///
+/// # Arguments
+///
+/// * `files` - The files to summarize, usually after filtering
 ///
 /// # Examples
 ///
-/// ```no_run
-/// use std::path::{Path, PathBuf};
+/// ```
+/// use std::path::PathBuf;
 /// use flist::file_lister::FileInfo;
-/// use flist::output::write_to_file;
+/// use flist::output::print_version_summary;
 ///
 /// let files = vec![
-///     FileInfo { path: PathBuf::from("test.dll"), version: None },
+///     FileInfo { path: PathBuf::from("a.dll"), version: Some("1.2".parse().unwrap()), ..Default::default() },
+///     FileInfo { path: PathBuf::from("b.txt"), version: None, ..Default::default() },
 /// ];
-/// write_to_file(&files, Path::new("output.txt"), false).unwrap();
+/// print_version_summary(&files);
 /// ```
-pub fn write_to_file(
-    files: &[FileInfo],
-    output_path: &Path,
-    include_version: bool,
-) -> Result<(), anyhow::Error> {
-    let file = File::create(output_path)?;
-    let mut writer = BufWriter::new(file);
+pub fn print_version_summary(files: &[FileInfo]) {
+    println!();
+    for line in version_summary_lines(files) {
+        println!("{}", line);
+    }
+}
+
+/// Formats the lines printed by [`print_version_summary`].
+fn version_summary_lines(files: &[FileInfo]) -> Vec<String> {
+    let mut majors: BTreeMap<u32, usize> = BTreeMap::new();
+    let mut unknown = 0;
+    for file_info in files {
+        match file_info.version {
+            Some(version) => *majors.entry(version.parts()[0]).or_default() += 1,
+            None => unknown += 1,
+        }
+    }
+
+    let mut groups: Vec<(String, usize)> = majors
+        .into_iter()
+        .map(|(major, count)| (format!("{}.x", major), count))
+        .collect();
+    if unknown > 0 {
+        groups.push(("unknown".to_string(), unknown));
+    }
+    let width = groups
+        .iter()
+        .map(|(label, _)| label.len())
+        .chain(["Major version".len()])
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = vec![format!("{:<width$}  Files", "Major version", width = width)];
+    lines.extend(
+        groups
+            .iter()
+            .map(|(label, count)| format!("{:<width$}  {:>5}", label, count, width = width)),
+    );
+    lines.push(format!(
+        "{} files with version, {} without.",
+        files.len() - unknown,
+        unknown
+    ));
+    lines
+}
+
+/// Prints groups of files with identical contents.
+///
+/// Files are grouped by [`FileInfo::hash`]; only groups with more than one
+/// member are printed, as the hash followed by the indented paths of the
+/// group. Groups are in the order of their first file. Files without a hash
+/// are ignored.
+///
+/// # Arguments
+///
+/// * `files` - The files to search for duplicates, with hashes computed
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::FileInfo;
+/// use flist::output::print_duplicates;
+///
+/// let file = |name: &str| FileInfo {
+///     path: PathBuf::from(name),
+///     hash: Some("af1349b9".to_string()),
+///     ..Default::default()
+/// };
+/// print_duplicates(&[file("a/app.dll"), file("b/app.dll")]);
+/// ```
+pub fn print_duplicates(files: &[FileInfo]) {
+    for line in duplicate_lines(files) {
+        println!("{}", line);
+    }
+}
 
+/// Formats the lines printed by [`print_duplicates`].
+fn duplicate_lines(files: &[FileInfo]) -> Vec<String> {
+    let mut groups: Vec<(&str, Vec<&FileInfo>)> = Vec::new();
+    let mut index_of: HashMap<&str, usize> = HashMap::new();
     for file_info in files {
-        if include_version {
-            if let Some(version) = file_info.version {
-                writeln!(writer, "{:<15} {}", version, file_info.path.display())?;
-            } else {
-                writeln!(writer, "{:<15} {}", "", file_info.path.display())?;
+        let Some(hash) = file_info.hash.as_deref() else {
+            continue;
+        };
+        match index_of.get(hash) {
+            Some(&index) => groups[index].1.push(file_info),
+            None => {
+                index_of.insert(hash, groups.len());
+                groups.push((hash, vec![file_info]));
             }
+        }
+    }
+    groups.retain(|(_, members)| members.len() > 1);
+
+    let mut lines = vec![
+        String::new(),
+        format!("Found {} groups of identical files.", groups.len()),
+    ];
+    for (hash, members) in groups {
+        lines.push(String::new());
+        lines.push(format!("{} ({} files)", hash, members.len()));
+        lines.extend(
+            members
+                .iter()
+                .map(|file_info| format!("  {}", file_info.path.display())),
+        );
+    }
+    lines
+}
+
+/// Prints the entries that could not be read while searching to stderr.
+///
+/// Each error is printed on one line with the path and the reason, so the
+/// listing on stdout stays usable on its own. Skipped symbolic link loops are
+/// printed as warnings.
+///
+/// # Arguments
+///
+/// * `errors` - The errors recorded by the walk
+///
+/// # Examples
+///
+/// ```
+/// use flist::output::print_walk_errors;
+///
+/// print_walk_errors(&[]);
+/// ```
+pub fn print_walk_errors(errors: &[WalkError]) {
+    for error in errors {
+        if error.is_loop {
+            eprintln!("Warning: {}", error);
         } else {
-            writeln!(writer, "{}", file_info.path.display())?;
+            eprintln!("Could not read {}", error);
         }
     }
+}
 
-    writer.flush()?;
-    Ok(())
+/// Prints files that were skipped to stderr, one line each with the reason.
+///
+/// # Arguments
+///
+/// * `paths` - The skipped files
+/// * `reason` - Why they were skipped, e.g. `vanished during scan`
+/// * `verbosity` - The verbosity of the run
+/// * `min_verbosity` - Lowest verbosity that prints the lines, such as
+///   [`VERBOSITY_VERBOSE`] for files removed by a filter
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::output::{print_skipped, VERBOSITY_NORMAL, VERBOSITY_VERBOSE};
+///
+/// // Not printed: per-file diagnostics need -v
+/// let paths = [PathBuf::from("old.dll")];
+/// print_skipped(&paths, "version outside --minv/--maxv", VERBOSITY_NORMAL, VERBOSITY_VERBOSE);
+/// ```
+pub fn print_skipped(paths: &[PathBuf], reason: &str, verbosity: u8, min_verbosity: u8) {
+    if verbosity < min_verbosity {
+        return;
+    }
+    for path in paths {
+        eprintln!("Skipped \"{}\": {}.", path.display(), reason);
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::file_version::FileVersion;
-    use std::fs;
-    use std::path::PathBuf;
+/// Prints how long the phases of a run took to stderr, with `-vv`.
+///
+/// # Arguments
+///
+/// * `phases` - Name and duration of each phase, in order
+/// * `total` - Duration of the whole run
+/// * `verbosity` - Nothing is printed below [`VERBOSITY_TIMING`]
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use flist::output::{print_timings, VERBOSITY_TIMING};
+///
+/// let phases = [("scan", Duration::from_millis(120))];
+/// print_timings(&phases, Duration::from_millis(130), VERBOSITY_TIMING);
+/// ```
+pub fn print_timings(phases: &[(&str, Duration)], total: Duration, verbosity: u8) {
+    if verbosity >= VERBOSITY_TIMING {
+        eprintln!("{}", timing_line(phases, total));
+    }
+}
 
-    #[test]
-    fn test_write_to_file_without_version() {
-        let files = vec![
-            FileInfo {
-                path: PathBuf::from("file1.txt"),
-                version: None,
-            },
-            FileInfo {
-                path: PathBuf::from("file2.txt"),
-                version: None,
-            },
-        ];
+/// Prints the counts of a search to stderr, with `-vv`.
+///
+/// # Arguments
+///
+/// * `stats` - Counts of the search, see [`Stats`]
+/// * `verbosity` - Nothing is printed below [`VERBOSITY_TIMING`]
+pub fn print_stats(stats: &Stats, verbosity: u8) {
+    if verbosity >= VERBOSITY_TIMING {
+        eprintln!("Stats: {}.", stats);
+    }
+}
 
-        let temp_file = std::env::temp_dir().join("flist_test_output.txt");
-        let result = write_to_file(&files, &temp_file, false);
-        assert!(result.is_ok());
+/// Formats phase durations as `Timing: scan 0.120 s, total 0.130 s.`.
+fn timing_line(phases: &[(&str, Duration)], total: Duration) -> String {
+    let parts: Vec<String> = phases
+        .iter()
+        .chain([&("total", total)])
+        .map(|(name, duration)| format!("{} {:.3} s", name, duration.as_secs_f64()))
+        .collect();
+    format!("Timing: {}.", parts.join(", "))
+}
 
-        let content = fs::read_to_string(&temp_file).unwrap();
-        assert!(content.contains("file1.txt"));
-        assert!(content.contains("file2.txt"));
+/// Formats one version regression as `later (version) < earlier (version)`.
+fn format_regression(regression: &VersionRegression) -> String {
+    let version =
+        |file_info: &FileInfo| file_info.version.map(|v| v.to_string()).unwrap_or_default();
+    format!(
+        "{} ({}) < {} ({})",
+        regression.later.path.display(),
+        version(&regression.later),
+        regression.earlier.path.display(),
+        version(&regression.earlier)
+    )
+}
 
-        fs::remove_file(&temp_file).unwrap();
+/// A column of the text output, in front of the path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Version,
+    ProductVersion,
+    AssemblyVersion,
+    SemanticVersion,
+    String(StringField),
+    Size,
+    Checksum,
+    Created,
+    PeTimestamp,
+    Arch,
+    Root,
+}
+
+impl Column {
+    /// Returns the columns selected in `options`, in display order.
+    fn selected(options: &DisplayOptions) -> Vec<Column> {
+        let mut columns = Vec::new();
+        if options.include_version {
+            columns.push(Column::Version);
+        }
+        if options.show_product_version {
+            columns.push(Column::ProductVersion);
+        }
+        if options.show_assembly_version {
+            columns.push(Column::AssemblyVersion);
+        }
+        if options.show_semantic_version {
+            columns.push(Column::SemanticVersion);
+        }
+        columns.extend(options.string_fields.iter().copied().map(Column::String));
+        if options.show_size {
+            columns.push(Column::Size);
+        }
+        if options.show_checksum {
+            columns.push(Column::Checksum);
+        }
+        if options.show_created {
+            columns.push(Column::Created);
+        }
+        if options.show_pe_timestamp {
+            columns.push(Column::PeTimestamp);
+        }
+        if options.show_arch {
+            columns.push(Column::Arch);
+        }
+        if options.show_root {
+            columns.push(Column::Root);
+        }
+        columns
     }
 
-    #[test]
-    fn test_write_to_file_with_version() {
-        let files = vec![
-            FileInfo {
-                path: PathBuf::from("file1.dll"),
-                version: Some("1.0.0.0".parse::<FileVersion>().unwrap()),
-            },
-            FileInfo {
-                path: PathBuf::from("file2.dll"),
-                version: Some("2.0.0.0".parse::<FileVersion>().unwrap()),
-            },
-        ];
+    /// Returns the value of this column for a file; empty if unknown.
+    fn value(self, file_info: &FileInfo) -> String {
+        match self {
+            Column::Version => file_info.version.map(|v| v.to_string()).unwrap_or_default(),
+            Column::ProductVersion => file_info
+                .product_version
+                .map(|v| v.to_string())
+                .unwrap_or_default(),
+            Column::AssemblyVersion => file_info
+                .assembly_version
+                .map(|v| v.to_string())
+                .unwrap_or_default(),
+            Column::SemanticVersion => file_info
+                .semantic_version
+                .as_ref()
+                .map(|v| v.to_string())
+                .unwrap_or_default(),
+            Column::String(field) => file_info
+                .version_strings
+                .as_ref()
+                .and_then(|strings| strings.get(field))
+                .unwrap_or_default()
+                .to_string(),
+            Column::Size => file_info.size.map(|s| s.to_string()).unwrap_or_default(),
+            Column::Checksum => match file_info.checksum_ok {
+                Some(true) => "OK",
+                Some(false) => "MISMATCH",
+                None => "",
+            }
+            .to_string(),
+            Column::Created => file_info.created.map(format_time).unwrap_or_default(),
+            Column::PeTimestamp => file_info.pe_timestamp.map(format_time).unwrap_or_default(),
+            Column::Arch => file_info
+                .arch
+                .map(|arch| arch.to_string())
+                .unwrap_or_default(),
+            Column::Root => file_info
+                .root
+                .as_ref()
+                .map(|root| root.display().to_string())
+                .unwrap_or_default(),
+        }
+    }
 
-        let temp_file = std::env::temp_dir().join("flist_test_output_version.txt");
-        let result = write_to_file(&files, &temp_file, true);
-        assert!(result.is_ok());
+    /// Width used when files are printed before all of them are known.
+    fn fixed_width(self) -> usize {
+        match self {
+            Column::Version
+            | Column::ProductVersion
+            | Column::AssemblyVersion
+            | Column::SemanticVersion => 15,
+            Column::String(_) | Column::Root => 24,
+            Column::Size => 12,
+            Column::Checksum | Column::Arch => 8,
+            Column::Created | Column::PeTimestamp => 20,
+        }
+    }
+}
 
-        let content = fs::read_to_string(&temp_file).unwrap();
-        assert!(content.contains("1.0.0.0"));
+/// Computes the width of each selected column from the values of `files`.
+///
+/// Each column is as wide as its longest value, so the columns of all lines
+/// line up however long the values are. Pass the result to [`print_file`].
+///
+/// # Arguments
+///
+/// * `files` - The files that will be printed
+/// * `options` - Display options selecting the columns
+///
+/// # Returns
+///
+/// One width in characters per selected column, in display order.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::FileInfo;
+/// use flist::output::{column_widths, DisplayOptions};
+///
+/// let files = vec![
+///     FileInfo { path: PathBuf::from("a.dll"), version: Some("1.0".parse().unwrap()), ..Default::default() },
+///     FileInfo { path: PathBuf::from("b.dll"), version: Some("10.20.30.40".parse().unwrap()), ..Default::default() },
+/// ];
+/// let options = DisplayOptions { include_version: true, ..Default::default() };
+/// assert_eq!(column_widths(&files, &options), vec![11]);
+/// ```
+pub fn column_widths(files: &[FileInfo], options: &DisplayOptions) -> Vec<usize> {
+    Column::selected(options)
+        .into_iter()
+        .map(|column| {
+            files
+                .iter()
+                .map(|file_info| column.value(file_info).chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect()
+}
+
+/// Returns fixed column widths for files printed as they are found.
+///
+/// When files are streamed, the widest value is not known in advance. These
+/// widths fit common values, longer values push the rest of their line right.
+///
+/// # Arguments
+///
+/// * `options` - Display options selecting the columns
+///
+/// # Returns
+///
+/// One width in characters per selected column, in display order.
+pub fn fixed_column_widths(options: &DisplayOptions) -> Vec<usize> {
+    Column::selected(options)
+        .into_iter()
+        .map(Column::fixed_width)
+        .collect()
+}
+
+/// A value that can be placed in a [`LineTemplate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateField {
+    /// `{path}` - the path as printed without a template
+    Path,
+    /// `{name}` - the file name
+    Name,
+    /// `{dir}` - the directory of the file, in the same style as the path
+    Dir,
+    /// `{version}` - the file version
+    Version,
+    /// `{product_version}` - the product version
+    ProductVersion,
+    /// `{assembly_version}` - the assembly version of a .NET assembly
+    AssemblyVersion,
+    /// `{size}` - the size in bytes
+    Size,
+    /// `{modified}` - the modification time
+    Modified,
+    /// `{created}` - the creation time
+    Created,
+    /// `{arch}` - the target architecture from the PE header
+    Arch,
+}
+
+impl TemplateField {
+    /// Placeholder names, in the order they are listed in error messages.
+    const NAMES: [(&'static str, TemplateField); 10] = [
+        ("path", TemplateField::Path),
+        ("name", TemplateField::Name),
+        ("dir", TemplateField::Dir),
+        ("version", TemplateField::Version),
+        ("product_version", TemplateField::ProductVersion),
+        ("assembly_version", TemplateField::AssemblyVersion),
+        ("size", TemplateField::Size),
+        ("modified", TemplateField::Modified),
+        ("created", TemplateField::Created),
+        ("arch", TemplateField::Arch),
+    ];
+
+    /// Returns the value of this field for a file printed as `path`; empty if
+    /// unknown.
+    fn value(self, file_info: &FileInfo, path: &str, options: &DisplayOptions) -> String {
+        match self {
+            TemplateField::Path => path.to_string(),
+            TemplateField::Name => file_info
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            TemplateField::Dir => format_parent(file_info, options),
+            TemplateField::Version => Column::Version.value(file_info),
+            TemplateField::ProductVersion => Column::ProductVersion.value(file_info),
+            TemplateField::AssemblyVersion => Column::AssemblyVersion.value(file_info),
+            TemplateField::Size => Column::Size.value(file_info),
+            TemplateField::Modified => file_info.modified.map(format_time).unwrap_or_default(),
+            TemplateField::Created => Column::Created.value(file_info),
+            TemplateField::Arch => Column::Arch.value(file_info),
+        }
+    }
+}
+
+/// A piece of a [`LineTemplate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TemplatePart {
+    Text(String),
+    Field(TemplateField),
+}
+
+/// A custom layout of the file lines, such as `{version}\t{name}`.
+///
+/// Placeholders in braces are replaced by the values of each file, see
+/// [`TemplateField`] for the names; unknown values are left empty. `{{` and
+/// `}}` stand for literal braces. Parsing fails on unknown placeholders, so a
+/// typo is reported before the search starts.
+///
+/// # Examples
+///
+/// ```
+/// use flist::output::{LineTemplate, TemplateField};
+///
+/// let template: LineTemplate = "{name} {version}".parse().unwrap();
+/// assert!(template.uses(TemplateField::Version));
+/// assert!(!template.uses(TemplateField::Size));
+/// assert!("{nmae}".parse::<LineTemplate>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineTemplate {
+    parts: Vec<TemplatePart>,
+}
+
+impl LineTemplate {
+    /// Returns whether the template contains the placeholder of `field`.
+    ///
+    /// Used to read the information the template needs.
+    pub fn uses(&self, field: TemplateField) -> bool {
+        self.parts.contains(&TemplatePart::Field(field))
+    }
+
+    /// Expands the template for a file printed as `path`.
+    fn expand(&self, file_info: &FileInfo, path: &str, options: &DisplayOptions) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                TemplatePart::Text(text) => text.clone(),
+                TemplatePart::Field(field) => field.value(file_info, path, options),
+            })
+            .collect()
+    }
+}
+
+impl FromStr for LineTemplate {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    let mut closed = false;
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        name.push(c);
+                    }
+                    if !closed {
+                        anyhow::bail!(
+                            "unclosed placeholder '{{{}', write '{{{{' for a literal brace",
+                            name
+                        );
+                    }
+                    let field = TemplateField::NAMES
+                        .iter()
+                        .find(|(known, _)| *known == name)
+                        .map(|&(_, field)| field)
+                        .ok_or_else(|| {
+                            let names: Vec<String> = TemplateField::NAMES
+                                .iter()
+                                .map(|(known, _)| format!("{{{}}}", known))
+                                .collect();
+                            anyhow::anyhow!(
+                                "unknown placeholder '{{{}}}', expected one of {}",
+                                name,
+                                names.join(", ")
+                            )
+                        })?;
+                    if !text.is_empty() {
+                        parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(TemplatePart::Field(field));
+                }
+                '}' => anyhow::bail!("unmatched '}}', write '}}}}' for a literal brace"),
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(TemplatePart::Text(text));
+        }
+
+        Ok(LineTemplate { parts })
+    }
+}
+
+/// Formats one output line with the columns selected in `options`.
+///
+/// Columns are padded to `widths` and separated by a space, unless a field
+/// separator is set, in which case they are joined by it without padding.
+/// Without a field separator, columns of width zero are left out, so a column
+/// that is empty for every file does not indent the paths.
+/// Sizes are aligned to the right, all other columns to the left. With `color`,
+/// versions are bold and paths dim, or red when a requested version is missing.
+/// A [`DisplayOptions::template`] replaces the columns and is never colored.
+fn format_line(
+    file_info: &FileInfo,
+    path: &str,
+    widths: &[usize],
+    options: &DisplayOptions,
+) -> String {
+    if let Some(template) = &options.template {
+        return template.expand(file_info, path, options);
+    }
+
+    let mut line = String::new();
+    for (column, &width) in Column::selected(options).into_iter().zip(widths) {
+        if width == 0 && options.field_separator.is_none() {
+            continue;
+        }
+        let value = column.value(file_info);
+        let cell = match &options.field_separator {
+            Some(_) => value,
+            None if column == Column::Size => format!("{:>width$}", value, width = width),
+            None => format!("{:<width$}", value, width = width),
+        };
+        match column {
+            Column::Version
+            | Column::ProductVersion
+            | Column::AssemblyVersion
+            | Column::SemanticVersion => line.push_str(&paint(&cell, STYLE_VERSION, options.color)),
+            _ => line.push_str(&cell),
+        }
+        line.push_str(options.field_separator.as_deref().unwrap_or(" "));
+    }
+
+    let missing_version = options.include_version && file_info.version.is_none();
+    let style = if missing_version && !file_info.is_dir {
+        STYLE_NO_VERSION
+    } else {
+        STYLE_PATH
+    };
+    line.push_str(&paint(path, style, options.color));
+    line
+}
+
+/// Formats the paths of a file and its alternate data streams for
+/// [`DisplayOptions::null_separated`], each terminated by a NUL character.
+///
+/// Paths are never shortened, so they can be passed on to other programs.
+fn null_record(file_info: &FileInfo, options: &DisplayOptions) -> String {
+    let path = format_entry_path(file_info, options);
+    let mut record = format!("{}\0", path);
+    for stream in &file_info.streams {
+        record.push_str(&format!("{}:{}\0", path, stream));
+    }
+    record
+}
+
+/// Formats the path of a listed entry; directories end in a path separator.
+fn format_entry_path(file_info: &FileInfo, options: &DisplayOptions) -> String {
+    let marker = if file_info.is_dir {
+        std::path::MAIN_SEPARATOR_STR
+    } else {
+        ""
+    };
+    match &file_info.root {
+        Some(root) => {
+            let options = DisplayOptions {
+                root: root.clone(),
+                ..options.clone()
+            };
+            format_path(&file_info.path, marker, &options)
+        }
+        None => format_path(&file_info.path, marker, options),
+    }
+}
+
+/// Formats the directory of a listed entry in the same style as its path;
+/// empty if the path has no parent.
+fn format_parent(file_info: &FileInfo, options: &DisplayOptions) -> String {
+    match file_info.path.parent() {
+        Some(parent) => {
+            let file_info = FileInfo {
+                path: parent.to_path_buf(),
+                root: file_info.root.clone(),
+                ..Default::default()
+            };
+            format_entry_path(&file_info, options)
+        }
+        None => String::new(),
+    }
+}
+
+/// Formats a file path according to the path style in `options`.
+///
+/// Relative paths fall back to the path as found if it is not inside the search
+/// directory. Files found by searching several directories are relative to
+/// their own [`FileInfo::root`]. With [`PathStyle::Both`] both representations are computed and
+/// joined by the field separator, or a tab if none is set. `marker` is appended
+/// to each formatted path.
+fn format_path(path: &Path, marker: &str, options: &DisplayOptions) -> String {
+    let relative = || format!("{}{}", relative_path(path, &options.root).display(), marker);
+    let absolute = || format!("{}{}", absolute_path(path).display(), marker);
+
+    match options.path_style {
+        PathStyle::Full => format!("{}{}", path.display(), marker),
+        PathStyle::Relative => relative(),
+        PathStyle::Absolute => absolute(),
+        PathStyle::Both => {
+            let separator = options.field_separator.as_deref().unwrap_or("\t");
+            format!("{}{}{}", relative(), separator, absolute())
+        }
+    }
+}
+
+/// Returns `path` relative to the search directory `root`, or the path as found
+/// if it is not inside `root`.
+fn relative_path<'a>(path: &'a Path, root: &Path) -> &'a Path {
+    path.strip_prefix(root).unwrap_or(path)
+}
+
+/// Returns `path` joined to the current directory if it is relative.
+fn absolute_path(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Parses a field separator given on the command line.
+///
+/// The escape sequence `\t` is replaced by a tab character, so a tab can be
+/// passed without shell specific quoting.
+///
+/// # Examples
+///
+/// ```
+/// use flist::output::parse_field_separator;
+///
+/// assert_eq!(parse_field_separator("\\t"), "\t");
+/// assert_eq!(parse_field_separator("|"), "|");
+/// ```
+pub fn parse_field_separator(separator: &str) -> String {
+    separator.replace("\\t", "\t")
+}
+
+/// Shortens a path for display by replacing its middle with `…`.
+///
+/// The start of the path and its file name are kept, since the file name is
+/// usually the most useful part. When the file name alone would fill the
+/// width, the first directory is still kept, up to a third of the width, and
+/// the start of the file name is cut instead. Paths that already fit are
+/// returned unchanged. The width is counted in characters.
+///
+/// # Arguments
+///
+/// * `path` - The path to shorten
+/// * `max_width` - Maximum number of characters of the result
+///
+/// # Examples
+///
+/// ```
+/// use flist::output::truncate_middle;
+///
+/// assert_eq!(truncate_middle("C:\\Windows\\System32\\kernel32.dll", 20), "C:\\Wind…kernel32.dll");
+/// assert_eq!(truncate_middle("rv/libz.so.1", 10), "rv/…z.so.1");
+/// assert_eq!(truncate_middle("short.dll", 20), "short.dll");
+/// ```
+pub fn truncate_middle(path: &str, max_width: usize) -> String {
+    let chars: Vec<char> = path.chars().collect();
+    if chars.len() <= max_width {
+        return path.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+
+    // One character is taken by the ellipsis
+    let budget = max_width - 1;
+    let is_separator = |c: &char| *c == '/' || *c == '\\';
+    let name_len = chars
+        .iter()
+        .rev()
+        .position(is_separator)
+        .unwrap_or(chars.len());
+    // The first directory including its separator, e.g. `rv/` or `/usr/`
+    let min_head = if name_len < chars.len() {
+        let leading = chars.iter().take_while(|c| is_separator(c)).count();
+        let first_len = chars[leading..]
+            .iter()
+            .position(is_separator)
+            .map_or(0, |end| leading + end + 1);
+        first_len.min(budget / 3)
+    } else {
+        0
+    };
+    let head_len = budget.saturating_sub(name_len).max(min_head);
+    let tail_len = budget - head_len;
+
+    let head: String = chars[..head_len].iter().collect();
+    let tail: String = chars[chars.len() - tail_len..].iter().collect();
+    format!("{}…{}", head, tail)
+}
+
+/// Writes results to a file in the given format.
+///
+/// Creates or overwrites the specified file with the file listing results.
+/// As text, each line contains the file path and optionally the version
+/// information; JSON and CSV are written as by [`write_json_to`] and
+/// [`write_csv_to`].
+///
+/// # Arguments
+///
+/// * `files` - Slice of file information to write
+/// * `output_path` - Path to the output file
+/// * `include_version` - Whether to include version information
+/// * `format` - The format of the file
+///
+/// # Returns
+///
+/// `Ok(())` on success, or an error if the file cannot be created or written.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::{Path, PathBuf};
+/// use flist::file_lister::FileInfo;
+/// use flist::output::{write_to_file, OutputFormat};
+///
+/// let files = vec![
+///     FileInfo { path: PathBuf::from("test.dll"), version: None, ..Default::default() },
+/// ];
+/// write_to_file(&files, Path::new("output.txt"), false, OutputFormat::Text).unwrap();
+/// ```
+pub fn write_to_file(
+    files: &[FileInfo],
+    output_path: &Path,
+    include_version: bool,
+    format: OutputFormat,
+) -> Result<(), anyhow::Error> {
+    write_output_file(
+        files,
+        output_path,
+        format,
+        &DisplayOptions {
+            include_version,
+            ..Default::default()
+        },
+        false,
+    )
+}
+
+/// Writes results to a file in the given format, using the given display options.
+///
+/// Text is written by [`write_to_file_with_options`], or
+/// [`write_to_file_append`] with `append`. JSON and CSV only use
+/// `include_version` of the options; CSV is appended by [`append_csv_to_file`].
+///
+/// # Arguments
+///
+/// * `files` - Slice of file information to write
+/// * `output_path` - Path to the output file
+/// * `format` - The format of the file, independent of the console format
+/// * `options` - Display options selecting the columns to write
+/// * `append` - Whether to extend the file instead of overwriting it
+///
+/// # Returns
+///
+/// `Ok(())` on success, or an error if the file cannot be written or JSON is
+/// to be appended, which would not result in valid JSON.
+pub fn write_output_file(
+    files: &[FileInfo],
+    output_path: &Path,
+    format: OutputFormat,
+    options: &DisplayOptions,
+    append: bool,
+) -> Result<(), anyhow::Error> {
+    match format {
+        OutputFormat::Text if append => write_to_file_append(files, output_path, options),
+        OutputFormat::Text => write_to_file_with_options(files, output_path, options),
+        OutputFormat::Json if append => anyhow::bail!("JSON output cannot be appended"),
+        OutputFormat::Json => write_json_to(File::create(output_path)?, files, options),
+        OutputFormat::Ndjson if append => {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(output_path)?;
+            write_ndjson_to(file, files, options)
+        }
+        OutputFormat::Ndjson => write_ndjson_to(File::create(output_path)?, files, options),
+        OutputFormat::Csv if append => append_csv_to_file(files, output_path, options),
+        OutputFormat::Csv => write_csv_to(File::create(output_path)?, files, options),
+        OutputFormat::Xml if append => anyhow::bail!("XML output cannot be appended"),
+        OutputFormat::Xml => write_xml_to(File::create(output_path)?, files, options),
+        OutputFormat::Markdown if append => anyhow::bail!("Markdown output cannot be appended"),
+        OutputFormat::Markdown => write_markdown_to(File::create(output_path)?, files, options),
+    }
+}
+
+/// Writes results to a file using the given display options.
+///
+/// Uses the same columns as the console output. Console-only options such as
+/// `max_width`, `quiet` and `color` are ignored, so the file always contains full
+/// paths and no escape sequences.
+/// With `file_summary`, the file starts and ends with the summary lines of the
+/// non-quiet console output, even if `quiet` is set.
+/// With `null_separated`, the file contains only the paths, each terminated by a
+/// NUL character, and no summary lines.
+///
+/// # Arguments
+///
+/// * `files` - Slice of file information to write
+/// * `output_path` - Path to the output file
+/// * `options` - Display options selecting the columns to write
+///
+/// # Returns
+///
+/// `Ok(())` on success, or an error if the file cannot be created or written.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::{Path, PathBuf};
+/// use flist::file_lister::FileInfo;
+/// use flist::output::{write_to_file_with_options, DisplayOptions};
+///
+/// let files = vec![
+///     FileInfo { path: PathBuf::from("test.dll"), version: None, ..Default::default() },
+/// ];
+/// let options = DisplayOptions { include_version: true, ..Default::default() };
+/// write_to_file_with_options(&files, Path::new("output.txt"), &options).unwrap();
+/// ```
+pub fn write_to_file_with_options(
+    files: &[FileInfo],
+    output_path: &Path,
+    options: &DisplayOptions,
+) -> Result<(), anyhow::Error> {
+    write_text_to(File::create(output_path)?, files, options)
+}
+
+/// Appends results to a file using the given display options.
+///
+/// Like [`write_to_file_with_options`], but the file is created if it does not
+/// exist and otherwise extended instead of truncated, so several runs can
+/// accumulate into one report. With `file_summary`, each run adds its own
+/// summary lines around its files.
+///
+/// # Arguments
+///
+/// * `files` - Slice of file information to write
+/// * `output_path` - Path to the output file
+/// * `options` - Display options selecting the columns to write
+///
+/// # Returns
+///
+/// `Ok(())` on success, or an error if the file cannot be opened or written.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::{Path, PathBuf};
+/// use flist::file_lister::FileInfo;
+/// use flist::output::{write_to_file_append, DisplayOptions};
+///
+/// let files = vec![
+///     FileInfo { path: PathBuf::from("test.dll"), version: None, ..Default::default() },
+/// ];
+/// write_to_file_append(&files, Path::new("report.txt"), &DisplayOptions::default()).unwrap();
+/// ```
+pub fn write_to_file_append(
+    files: &[FileInfo],
+    output_path: &Path,
+    options: &DisplayOptions,
+) -> Result<(), anyhow::Error> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(output_path)?;
+    write_text_to(file, files, options)
+}
+
+/// Writes the text layout of [`write_to_file_with_options`] to any writer.
+fn write_text_to<W: Write>(
+    writer: W,
+    files: &[FileInfo],
+    options: &DisplayOptions,
+) -> Result<(), anyhow::Error> {
+    let mut writer = BufWriter::new(writer);
+    let options = &DisplayOptions {
+        color: false,
+        ..options.clone()
+    };
+
+    if options.null_separated {
+        for file_info in files {
+            write!(writer, "{}", null_record(file_info, options))?;
+        }
+        writer.flush()?;
+        return Ok(());
+    }
+
+    if options.file_summary {
+        writeln!(writer, "{}", summary_line(files.len(), options))?;
+        writeln!(writer)?;
+    }
+
+    let widths = column_widths(files, options);
+    if options.group_by_dir {
+        for line in dir_group_lines(files, &widths, options) {
+            writeln!(writer, "{}", line)?;
+        }
+    } else {
+        for file_info in files {
+            let path = format_entry_path(file_info, options);
+            writeln!(
+                writer,
+                "{}",
+                format_line(file_info, &path, &widths, options)
+            )?;
+            for stream in &file_info.streams {
+                let stream_path = format!("{}:{}", path, stream);
+                writeln!(
+                    writer,
+                    "{}",
+                    format_line(file_info, &stream_path, &widths, options)
+                )?;
+            }
+        }
+    }
+
+    if options.file_summary {
+        writeln!(writer)?;
+        writeln!(writer, "{}", summary_line(files.len(), options))?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Version of the JSON summary layout written by [`write_summary_json`].
+///
+/// Increased only when existing fields change meaning or are removed, so that
+/// consumers can rely on a given version.
+pub const SUMMARY_SCHEMA_VERSION: u32 = 1;
+
+/// Parameters of a run, recorded in the JSON summary.
+#[derive(Debug, Clone, Default)]
+pub struct RunParameters {
+    /// Search directory
+    pub directory: PathBuf,
+    /// Search patterns, inline and read from a file
+    pub patterns: Vec<String>,
+    /// Whether version information was read
+    pub include_version: bool,
+    /// Minimum version filter
+    pub min_version: Option<FileVersion>,
+    /// Maximum version filter
+    pub max_version: Option<FileVersion>,
+    /// Excluded version patterns
+    pub exclude_versions: Vec<VersionPattern>,
+    /// Version of the reference file for `--at-least-version-of`
+    pub reference_version: Option<FileVersion>,
+    /// Earliest creation time filter
+    pub created_after: Option<SystemTime>,
+    /// Latest creation time filter
+    pub created_before: Option<SystemTime>,
+    /// Whether PE checksums were verified
+    pub verify_checksum: bool,
+    /// Minimum size filter in bytes
+    pub min_size: Option<u64>,
+    /// Maximum size filter in bytes
+    pub max_size: Option<u64>,
+    /// Earliest modification time filter
+    pub modified_after: Option<SystemTime>,
+    /// Latest modification time filter
+    pub modified_before: Option<SystemTime>,
+}
+
+/// Builds the aggregate summary of a run as JSON.
+///
+/// The summary contains the schema version, totals over `files`, the elapsed
+/// time and the run parameters, but not the individual files:
+///
+/// ```text
+/// {
+///   "schema_version": 1,
+///   "totals": { "files", "with_version", "without_version", "checksum_mismatches", "streams",
+///               "directories" },
+///   "timing": { "elapsed_ms" },
+///   "parameters": { "directory", "patterns", "include_version", "min_version",
+///                   "max_version", "exclude_versions", "reference_version",
+///                   "created_after", "created_before", "verify_checksum", "min_size",
+///                   "max_size", "modified_after", "modified_before" }
+/// }
+/// ```
+///
+/// Versions and times are written as strings, sizes as numbers of bytes, unset
+/// values as `null`. Directories listed with `--match-dirs` count towards
+/// `files` and `without_version`.
+///
+/// # Arguments
+///
+/// * `files` - The listed files
+/// * `parameters` - Parameters of the run
+/// * `elapsed` - Time taken by the run
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use flist::output::{summary_json, RunParameters};
+///
+/// let summary = summary_json(&[], &RunParameters::default(), Duration::from_millis(5));
+/// assert_eq!(summary["totals"]["files"], 0);
+/// assert_eq!(summary["timing"]["elapsed_ms"], 5);
+/// ```
+pub fn summary_json(
+    files: &[FileInfo],
+    parameters: &RunParameters,
+    elapsed: Duration,
+) -> serde_json::Value {
+    let with_version = files.iter().filter(|f| f.version.is_some()).count();
+    let version = |version: &Option<FileVersion>| version.map(|v| v.to_string());
+
+    json!({
+        "schema_version": SUMMARY_SCHEMA_VERSION,
+        "totals": {
+            "files": files.len(),
+            "with_version": with_version,
+            "without_version": files.len() - with_version,
+            "checksum_mismatches": files.iter().filter(|f| f.checksum_ok == Some(false)).count(),
+            "streams": files.iter().map(|f| f.streams.len()).sum::<usize>(),
+            "directories": files.iter().filter(|f| f.is_dir).count(),
+        },
+        "timing": {
+            "elapsed_ms": elapsed.as_millis() as u64,
+        },
+        "parameters": {
+            "directory": parameters.directory.display().to_string(),
+            "patterns": parameters.patterns,
+            "include_version": parameters.include_version,
+            "min_version": version(&parameters.min_version),
+            "max_version": version(&parameters.max_version),
+            "exclude_versions": parameters
+                .exclude_versions
+                .iter()
+                .map(|pattern| pattern.to_string())
+                .collect::<Vec<_>>(),
+            "reference_version": version(&parameters.reference_version),
+            "created_after": parameters.created_after.map(format_time),
+            "created_before": parameters.created_before.map(format_time),
+            "verify_checksum": parameters.verify_checksum,
+            "min_size": parameters.min_size,
+            "max_size": parameters.max_size,
+            "modified_after": parameters.modified_after.map(format_time),
+            "modified_before": parameters.modified_before.map(format_time),
+        },
+    })
+}
+
+/// Writes the aggregate summary of a run to a JSON file.
+///
+/// See [`summary_json`] for the layout. The file is written independently of
+/// the console and `--output` listings.
+///
+/// # Arguments
+///
+/// * `files` - The listed files
+/// * `output_path` - Path of the JSON file to create
+/// * `parameters` - Parameters of the run
+/// * `elapsed` - Time taken by the run
+///
+/// # Returns
+///
+/// * `Ok(())` - The summary was written successfully
+/// * `Err(_)` - An error occurred while writing the file
+pub fn write_summary_json(
+    files: &[FileInfo],
+    output_path: &Path,
+    parameters: &RunParameters,
+    elapsed: Duration,
+) -> Result<(), anyhow::Error> {
+    let file = File::create(output_path)?;
+    let mut writer = BufWriter::new(file);
+
+    serde_json::to_writer_pretty(&mut writer, &summary_json(files, parameters, elapsed))?;
+    writeln!(writer)?;
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// A field of a [`FileRecord`], in the order the fields are written.
+///
+/// The names are the JSON keys, CSV columns and XML attributes of the
+/// machine-readable formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordField {
+    Path,
+    AbsolutePath,
+    Version,
+    ProductVersion,
+    AssemblyVersion,
+    SemanticVersion,
+    Company,
+    Product,
+    Description,
+    Copyright,
+    Size,
+    Modified,
+    Created,
+    PeTimestamp,
+    Arch,
+    ChecksumOk,
+    Signed,
+    Hash,
+    Root,
+}
+
+impl RecordField {
+    /// All fields, in the order they are written.
+    pub const ALL: [RecordField; 19] = [
+        RecordField::Path,
+        RecordField::AbsolutePath,
+        RecordField::Version,
+        RecordField::ProductVersion,
+        RecordField::AssemblyVersion,
+        RecordField::SemanticVersion,
+        RecordField::Company,
+        RecordField::Product,
+        RecordField::Description,
+        RecordField::Copyright,
+        RecordField::Size,
+        RecordField::Modified,
+        RecordField::Created,
+        RecordField::PeTimestamp,
+        RecordField::Arch,
+        RecordField::ChecksumOk,
+        RecordField::Signed,
+        RecordField::Hash,
+        RecordField::Root,
+    ];
+
+    /// Returns the name of the field, e.g. `product_version`.
+    pub fn name(self) -> &'static str {
+        match self {
+            RecordField::Path => "path",
+            RecordField::AbsolutePath => "absolute_path",
+            RecordField::Version => "version",
+            RecordField::ProductVersion => "product_version",
+            RecordField::AssemblyVersion => "assembly_version",
+            RecordField::SemanticVersion => "semantic_version",
+            RecordField::Company => "company",
+            RecordField::Product => "product",
+            RecordField::Description => "description",
+            RecordField::Copyright => "copyright",
+            RecordField::Size => "size",
+            RecordField::Modified => "modified",
+            RecordField::Created => "created",
+            RecordField::PeTimestamp => "pe_timestamp",
+            RecordField::Arch => "arch",
+            RecordField::ChecksumOk => "checksum_ok",
+            RecordField::Signed => "signed",
+            RecordField::Hash => "hash",
+            RecordField::Root => "root",
+        }
+    }
+
+    /// Returns the column heading of the field in Markdown tables, e.g. `Product Version`.
+    fn label(self) -> &'static str {
+        match self {
+            RecordField::Path => "Path",
+            RecordField::AbsolutePath => "Absolute Path",
+            RecordField::Version => "Version",
+            RecordField::ProductVersion => "Product Version",
+            RecordField::AssemblyVersion => "Assembly Version",
+            RecordField::SemanticVersion => "Semantic Version",
+            RecordField::Company => "Company",
+            RecordField::Product => "Product",
+            RecordField::Description => "Description",
+            RecordField::Copyright => "Copyright",
+            RecordField::Size => "Size",
+            RecordField::Modified => "Modified",
+            RecordField::Created => "Created",
+            RecordField::PeTimestamp => "PE Timestamp",
+            RecordField::Arch => "Arch",
+            RecordField::ChecksumOk => "Checksum OK",
+            RecordField::Signed => "Signed",
+            RecordField::Hash => "Hash",
+            RecordField::Root => "Root",
+        }
+    }
+}
+
+/// A file as written by the machine-readable formats: JSON, NDJSON, CSV, XML
+/// and Markdown.
+///
+/// `path` is always set, in the style of [`DisplayOptions::path_style`]. With
+/// [`PathStyle::Both`] it is the relative path and `absolute_path` is set to the
+/// absolute one. Versions and version strings are set when they were
+/// requested for display and are known, since they may also be read only to
+/// filter. The other fields are set when they were read, which happens when
+/// they are shown or filtered on, e.g. `signed` with `--unsigned` and `hash`
+/// with `--dedup`. Unset fields are left out of the JSON objects:
+///
+/// ```text
+/// { "path": "bin/app.dll", "version": "1.2.3.4", "size": 4096, "modified": "2024-01-31T12:30:45Z" }
+/// ```
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::FileInfo;
+/// use flist::output::{DisplayOptions, FileRecord};
+///
+/// let info = FileInfo {
+///     path: PathBuf::from("app.dll"),
+///     version: Some("1.2.3.4".parse().unwrap()),
+///     signed: Some(false),
+///     ..Default::default()
+/// };
+/// let options = DisplayOptions { include_version: true, ..Default::default() };
+/// let record = FileRecord::new(&info, &options);
+/// assert_eq!(record.version.as_deref(), Some("1.2.3.4"));
+/// assert_eq!(record.signed, Some(false));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "serde",
+    schemars(description = "A file listed by flist; fields without a value are left out")
+)]
+pub struct FileRecord {
+    /// Path of the file, in the style selected with `--paths`
+    pub path: String,
+    /// Absolute path of the file, only set with `--paths both`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub absolute_path: Option<String>,
+    /// File version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Product version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product_version: Option<String>,
+    /// `AssemblyVersion` of a managed .NET assembly
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assembly_version: Option<String>,
+    /// `ProductVersion` string as a semantic version, e.g. `1.2.3-beta`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub semantic_version: Option<String>,
+    /// `CompanyName` version string
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub company: Option<String>,
+    /// `ProductName` version string
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product: Option<String>,
+    /// `FileDescription` version string
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// `LegalCopyright` version string
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub copyright: Option<String>,
+    /// Size in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Last modification time (UTC)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+    /// Creation time (UTC)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    /// Link time from the PE header (UTC)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pe_timestamp: Option<String>,
+    /// Target architecture from the PE header, e.g. `x64`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arch: Option<String>,
+    /// Whether the PE checksum matches the file contents
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum_ok: Option<bool>,
+    /// Whether the PE file has an embedded signature
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signed: Option<bool>,
+    /// BLAKE3 hash of the contents as hex
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    /// Search directory the file was found in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+}
+
+impl FileRecord {
+    /// Builds the record of a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_info` - The file to describe
+    /// * `options` - Display options selecting the path style, versions and
+    ///   version strings
+    pub fn new(file_info: &FileInfo, options: &DisplayOptions) -> Self {
+        let version = |version: Option<FileVersion>, requested: bool| {
+            version
+                .filter(|_| requested)
+                .map(|version| version.to_string())
+        };
+        let string = |field: StringField| {
+            file_info
+                .version_strings
+                .as_ref()
+                .and_then(|strings| strings.get(field))
+                .filter(|_| options.string_fields.contains(&field))
+                .map(str::to_string)
+        };
+
+        let path = &file_info.path;
+        let root = file_info.root.as_deref().unwrap_or(&options.root);
+        let (path, absolute_path) = match options.path_style {
+            PathStyle::Full => (path.display().to_string(), None),
+            PathStyle::Relative => (relative_path(path, root).display().to_string(), None),
+            PathStyle::Absolute => (absolute_path(path).display().to_string(), None),
+            PathStyle::Both => (
+                relative_path(path, root).display().to_string(),
+                Some(absolute_path(path).display().to_string()),
+            ),
+        };
+
+        Self {
+            path,
+            absolute_path,
+            version: version(file_info.version, options.include_version),
+            product_version: version(file_info.product_version, options.show_product_version),
+            assembly_version: version(file_info.assembly_version, options.show_assembly_version),
+            semantic_version: file_info
+                .semantic_version
+                .as_ref()
+                .filter(|_| options.show_semantic_version)
+                .map(|version| version.to_string()),
+            company: string(StringField::Company),
+            product: string(StringField::Product),
+            description: string(StringField::Description),
+            copyright: string(StringField::Copyright),
+            size: file_info.size,
+            modified: file_info.modified.map(format_time),
+            created: file_info.created.map(format_time),
+            pe_timestamp: file_info.pe_timestamp.map(format_time),
+            arch: file_info.arch.map(|arch| arch.to_string()),
+            checksum_ok: file_info.checksum_ok,
+            signed: file_info.signed,
+            hash: file_info.hash.clone(),
+            root: file_info
+                .root
+                .as_ref()
+                .map(|root| root.display().to_string()),
+        }
+    }
+
+    /// Returns a field as text, `None` if it is not set.
+    pub fn value(&self, field: RecordField) -> Option<String> {
+        match field {
+            RecordField::Path => Some(self.path.clone()),
+            RecordField::AbsolutePath => self.absolute_path.clone(),
+            RecordField::Version => self.version.clone(),
+            RecordField::ProductVersion => self.product_version.clone(),
+            RecordField::AssemblyVersion => self.assembly_version.clone(),
+            RecordField::SemanticVersion => self.semantic_version.clone(),
+            RecordField::Company => self.company.clone(),
+            RecordField::Product => self.product.clone(),
+            RecordField::Description => self.description.clone(),
+            RecordField::Copyright => self.copyright.clone(),
+            RecordField::Size => self.size.map(|size| size.to_string()),
+            RecordField::Modified => self.modified.clone(),
+            RecordField::Created => self.created.clone(),
+            RecordField::PeTimestamp => self.pe_timestamp.clone(),
+            RecordField::Arch => self.arch.clone(),
+            RecordField::ChecksumOk => self.checksum_ok.map(|ok| ok.to_string()),
+            RecordField::Signed => self.signed.map(|signed| signed.to_string()),
+            RecordField::Hash => self.hash.clone(),
+            RecordField::Root => self.root.clone(),
+        }
+    }
+}
+
+/// Returns the JSON Schema of the `--format json` output, an array of
+/// [`FileRecord`] objects.
+///
+/// The schema is derived from [`FileRecord`], the type the output is
+/// serialized from, so it cannot drift from the output. Each line of the
+/// `--format ndjson` output is one item of the array.
+///
+/// # Examples
+///
+/// ```
+/// use flist::output::json_schema;
+///
+/// let schema = json_schema();
+/// assert_eq!(schema["type"], "array");
+/// ```
+#[cfg(feature = "serde")]
+pub fn json_schema() -> serde_json::Value {
+    let mut schema = json!(schemars::schema_for!(Vec<FileRecord>));
+    schema["title"] = json!("flist --format json");
+    schema
+}
+
+/// Builds the records of `files`, see [`FileRecord::new`].
+fn file_records(files: &[FileInfo], options: &DisplayOptions) -> Vec<FileRecord> {
+    files
+        .iter()
+        .map(|file_info| FileRecord::new(file_info, options))
+        .collect()
+}
+
+/// Returns the columns of a table of `records`: the fields in `always`, and
+/// every other field that is set in at least one record, in field order.
+fn table_columns(records: &[FileRecord], always: &[RecordField]) -> Vec<RecordField> {
+    RecordField::ALL
+        .into_iter()
+        .filter(|field| {
+            always.contains(field) || records.iter().any(|record| record.value(*field).is_some())
+        })
+        .collect()
+}
+
+/// Builds the JSON object describing a single file, see [`FileRecord`].
+///
+/// # Arguments
+///
+/// * `file_info` - The file to describe
+/// * `options` - Display options selecting the path style, versions and version strings
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::FileInfo;
+/// use flist::output::{DisplayOptions, file_json};
+///
+/// let info = FileInfo { path: PathBuf::from("app.dll"), size: Some(4096), ..Default::default() };
+/// let json = file_json(&info, &DisplayOptions::default());
+/// assert_eq!(json.to_string(), r#"{"path":"app.dll","size":4096}"#);
+/// ```
+pub fn file_json(file_info: &FileInfo, options: &DisplayOptions) -> serde_json::Value {
+    json!(FileRecord::new(file_info, options))
+}
+
+/// Writes results to stdout as a JSON array, one object per file.
+///
+/// See [`FileRecord`] for the objects. No summary lines are written, so the
+/// output can be piped into tools such as `jq`.
+///
+/// # Arguments
+///
+/// * `files` - Slice of file information to write
+/// * `options` - Display options selecting the path style, versions and version strings
+///
+/// # Returns
+///
+/// * `Ok(())` - The results were written successfully
+/// * `Err(_)` - An error occurred while writing
+pub fn write_json(files: &[FileInfo], options: &DisplayOptions) -> Result<(), anyhow::Error> {
+    write_json_to(std::io::stdout().lock(), files, options)
+}
+
+/// Writes results as a JSON array to any writer, like [`write_json`].
+///
+/// # Arguments
+///
+/// * `writer` - Where to write the JSON
+/// * `files` - Slice of file information to write
+/// * `options` - Display options selecting the path style, versions and version strings
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::FileInfo;
+/// use flist::output::{DisplayOptions, write_json_to};
+///
+/// let files = vec![FileInfo { path: PathBuf::from("app.dll"), ..Default::default() }];
+/// let mut buffer = Vec::new();
+/// write_json_to(&mut buffer, &files, &DisplayOptions::default()).unwrap();
+/// assert!(String::from_utf8(buffer).unwrap().contains("\"app.dll\""));
+/// ```
+pub fn write_json_to<W: Write>(
+    writer: W,
+    files: &[FileInfo],
+    options: &DisplayOptions,
+) -> Result<(), anyhow::Error> {
+    let mut writer = BufWriter::new(writer);
+    serde_json::to_writer_pretty(&mut writer, &file_records(files, options))?;
+    writeln!(writer)?;
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes results to stdout as newline-delimited JSON, one object per line.
+///
+/// See [`FileRecord`] for the objects, which are the same as in [`write_json`].
+/// Each line can be parsed on its own, so consumers can process the results
+/// before the last one is written.
+///
+/// # Arguments
+///
+/// * `files` - Slice of file information to write
+/// * `options` - Display options selecting the path style, versions and version strings
+///
+/// # Returns
+///
+/// * `Ok(())` - The results were written successfully
+/// * `Err(_)` - An error occurred while writing
+pub fn write_ndjson(files: &[FileInfo], options: &DisplayOptions) -> Result<(), anyhow::Error> {
+    write_ndjson_to(std::io::stdout().lock(), files, options)
+}
+
+/// Writes results as newline-delimited JSON to any writer, like [`write_ndjson`].
+///
+/// # Arguments
+///
+/// * `writer` - Where to write the lines
+/// * `files` - Slice of file information to write
+/// * `options` - Display options selecting the path style, versions and version strings
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::FileInfo;
+/// use flist::output::{DisplayOptions, write_ndjson_to};
+///
+/// let files = vec![
+///     FileInfo { path: PathBuf::from("a.dll"), ..Default::default() },
+///     FileInfo { path: PathBuf::from("b.dll"), ..Default::default() },
+/// ];
+/// let mut buffer = Vec::new();
+/// write_ndjson_to(&mut buffer, &files, &DisplayOptions::default()).unwrap();
+/// assert_eq!(String::from_utf8(buffer).unwrap(), "{\"path\":\"a.dll\"}\n{\"path\":\"b.dll\"}\n");
+/// ```
+pub fn write_ndjson_to<W: Write>(
+    writer: W,
+    files: &[FileInfo],
+    options: &DisplayOptions,
+) -> Result<(), anyhow::Error> {
+    let mut writer = BufWriter::new(writer);
+    for file_info in files {
+        write_ndjson_line(&mut writer, file_info, options)?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes a single file as one line of newline-delimited JSON.
+///
+/// Used to print files one by one as they are found; the line is complete when
+/// this returns, but flushing is left to the writer.
+///
+/// # Arguments
+///
+/// * `writer` - Where to write the line
+/// * `file_info` - The file to write
+/// * `options` - Display options selecting the path style, versions and version strings
+pub fn write_ndjson_line<W: Write>(
+    writer: &mut W,
+    file_info: &FileInfo,
+    options: &DisplayOptions,
+) -> Result<(), anyhow::Error> {
+    serde_json::to_writer(&mut *writer, &FileRecord::new(file_info, options))?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// Columns the CSV output always has, whether or not they are set.
+pub const CSV_COLUMNS: [RecordField; 4] = [
+    RecordField::Path,
+    RecordField::Version,
+    RecordField::Size,
+    RecordField::Modified,
+];
+
+/// Writes results to stdout as CSV, see [`write_csv_to`].
+///
+/// # Arguments
+///
+/// * `files` - Slice of file information to write
+/// * `options` - Display options selecting the path style, versions and version strings
+///
+/// # Returns
+///
+/// * `Ok(())` - The results were written successfully
+/// * `Err(_)` - An error occurred while writing
+pub fn write_csv(files: &[FileInfo], options: &DisplayOptions) -> Result<(), anyhow::Error> {
+    write_csv_to(std::io::stdout().lock(), files, options)
+}
+
+/// Writes results as CSV to any writer.
+///
+/// The first row is the header, the names of the columns, followed by one row
+/// per file. The columns are the [`FileRecord`] fields: [`CSV_COLUMNS`] always,
+/// even if empty, and the other fields when set for at least one file, e.g.
+/// `arch` with `--show-arch`. Unknown values are empty. Fields containing
+/// commas, quotes or line breaks are quoted.
+///
+/// # Arguments
+///
+/// * `writer` - Where to write the CSV
+/// * `files` - Slice of file information to write
+/// * `options` - Display options selecting the path style, versions and version strings
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::FileInfo;
+/// use flist::output::{DisplayOptions, write_csv_to};
+///
+/// let files = vec![FileInfo { path: PathBuf::from("a,b.dll"), size: Some(10), ..Default::default() }];
+/// let mut buffer = Vec::new();
+/// write_csv_to(&mut buffer, &files, &DisplayOptions::default()).unwrap();
+/// assert_eq!(String::from_utf8(buffer).unwrap(), "path,version,size,modified\n\"a,b.dll\",,10,\n");
+/// ```
+pub fn write_csv_to<W: Write>(
+    writer: W,
+    files: &[FileInfo],
+    options: &DisplayOptions,
+) -> Result<(), anyhow::Error> {
+    write_csv_records(writer, files, options, true)
+}
+
+/// Appends results as CSV to a file, see [`write_csv_to`].
+///
+/// The file is created if it does not exist. The header row is only written
+/// when the file is empty, so several runs accumulate into one table. The runs
+/// should use the same options, so their rows have the same columns.
+///
+/// # Arguments
+///
+/// * `files` - Slice of file information to write
+/// * `output_path` - Path to the output file
+/// * `options` - Display options selecting the path style, versions and version strings
+///
+/// # Returns
+///
+/// `Ok(())` on success, or an error if the file cannot be opened or written.
+pub fn append_csv_to_file(
+    files: &[FileInfo],
+    output_path: &Path,
+    options: &DisplayOptions,
+) -> Result<(), anyhow::Error> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(output_path)?;
+    let header = file.metadata()?.len() == 0;
+    write_csv_records(file, files, options, header)
+}
+
+/// Writes one CSV row per file, preceded by the header if `header` is set.
+fn write_csv_records<W: Write>(
+    writer: W,
+    files: &[FileInfo],
+    options: &DisplayOptions,
+    header: bool,
+) -> Result<(), anyhow::Error> {
+    let records = file_records(files, options);
+    let columns = table_columns(&records, &CSV_COLUMNS);
+
+    let mut writer = csv::Writer::from_writer(writer);
+    if header {
+        writer.write_record(columns.iter().map(|field| field.name()))?;
+    }
+
+    for record in &records {
+        writer.write_record(
+            columns
+                .iter()
+                .map(|field| record.value(*field).unwrap_or_default()),
+        )?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes results to stdout as an XML document, see [`write_xml_to`].
+///
+/// # Arguments
+///
+/// * `files` - Slice of file information to write
+/// * `options` - Display options selecting the path style, versions and version strings
+///
+/// # Returns
+///
+/// * `Ok(())` - The results were written successfully
+/// * `Err(_)` - An error occurred while writing
+pub fn write_xml(files: &[FileInfo], options: &DisplayOptions) -> Result<(), anyhow::Error> {
+    write_xml_to(std::io::stdout().lock(), files, options)
+}
+
+/// Writes results as an XML document to any writer.
+///
+/// The root element `<files>` holds one empty `<file>` element per file, with
+/// an attribute per [`FileRecord`] field that is set. Unknown values are left
+/// out rather than written as empty attributes:
+///
+/// ```text
+/// <?xml version="1.0" encoding="UTF-8"?>
+/// <files>
+///   <file path="bin/app.dll" version="1.2.3.4" size="4096" modified="2024-01-31T12:30:45Z"/>
+/// </files>
+/// ```
+///
+/// # Arguments
+///
+/// * `writer` - Where to write the XML
+/// * `files` - Slice of file information to write
+/// * `options` - Display options selecting the path style, versions and version strings
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::FileInfo;
+/// use flist::output::{DisplayOptions, write_xml_to};
+///
+/// let files = vec![FileInfo { path: PathBuf::from("R&D.dll"), ..Default::default() }];
+/// let mut buffer = Vec::new();
+/// write_xml_to(&mut buffer, &files, &DisplayOptions::default()).unwrap();
+/// assert!(String::from_utf8(buffer).unwrap().contains(r#"<file path="R&amp;D.dll"/>"#));
+/// ```
+pub fn write_xml_to<W: Write>(
+    writer: W,
+    files: &[FileInfo],
+    options: &DisplayOptions,
+) -> Result<(), anyhow::Error> {
+    let mut writer = BufWriter::new(writer);
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(writer, "<files>")?;
+
+    for file_info in files {
+        let record = FileRecord::new(file_info, options);
+        write!(writer, "  <file")?;
+        for field in RecordField::ALL {
+            if let Some(value) = record.value(field) {
+                write!(writer, r#" {}="{}""#, field.name(), xml_escape(&value))?;
+            }
+        }
+        writeln!(writer, "/>")?;
+    }
+
+    writeln!(writer, "</files>")?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Escapes text for an XML attribute value.
+///
+/// Control characters that XML 1.0 cannot represent are replaced by U+FFFD.
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' => escaped.push_str("&#9;"),
+            '\n' => escaped.push_str("&#10;"),
+            '\r' => escaped.push_str("&#13;"),
+            c if c < ' ' => escaped.push('\u{FFFD}'),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Writes results to stdout as a Markdown table, see [`write_markdown_to`].
+///
+/// # Arguments
+///
+/// * `files` - Slice of file information to write
+/// * `options` - Display options selecting the path style, versions and version strings
+///
+/// # Returns
+///
+/// * `Ok(())` - The results were written successfully
+/// * `Err(_)` - An error occurred while writing
+pub fn write_markdown(files: &[FileInfo], options: &DisplayOptions) -> Result<(), anyhow::Error> {
+    write_markdown_to(std::io::stdout().lock(), files, options)
+}
+
+/// Writes results as a GitHub-flavored Markdown table to any writer.
+///
+/// The columns are the [`FileRecord`] fields: `Path`, `Version` when versions
+/// are requested, and every other field when any file has it, such as `Size`
+/// and `Modified`. Pipe characters in values are escaped so they do not split
+/// the row.
+///
+/// # Arguments
+///
+/// * `writer` - Where to write the table
+/// * `files` - Slice of file information to write
+/// * `options` - Display options selecting the path style, versions and version strings
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use flist::file_lister::FileInfo;
+/// use flist::output::{DisplayOptions, write_markdown_to};
+///
+/// let files = vec![FileInfo {
+///     path: PathBuf::from("app.dll"),
+///     version: Some("1.2.3.4".parse().unwrap()),
+///     ..Default::default()
+/// }];
+/// let options = DisplayOptions { include_version: true, ..Default::default() };
+/// let mut buffer = Vec::new();
+/// write_markdown_to(&mut buffer, &files, &options).unwrap();
+/// assert_eq!(
+///     String::from_utf8(buffer).unwrap(),
+///     "| Path | Version |\n| --- | --- |\n| app.dll | 1.2.3.4 |\n"
+/// );
+/// ```
+pub fn write_markdown_to<W: Write>(
+    writer: W,
+    files: &[FileInfo],
+    options: &DisplayOptions,
+) -> Result<(), anyhow::Error> {
+    let records = file_records(files, options);
+    let always: &[RecordField] = if options.include_version {
+        &[RecordField::Path, RecordField::Version]
+    } else {
+        &[RecordField::Path]
+    };
+    let columns = table_columns(&records, always);
+
+    let header: Vec<&str> = columns.iter().map(|field| field.label()).collect();
+    let separator: Vec<&str> = columns
+        .iter()
+        .map(|field| match field {
+            RecordField::Size => "---:",
+            _ => "---",
+        })
+        .collect();
+
+    let mut writer = BufWriter::new(writer);
+    writeln!(writer, "| {} |", header.join(" | "))?;
+    writeln!(writer, "| {} |", separator.join(" | "))?;
+    for record in &records {
+        let cells: Vec<String> = columns
+            .iter()
+            .map(|field| record.value(*field).unwrap_or_default().replace('|', "\\|"))
+            .collect();
+        writeln!(writer, "| {} |", cells.join(" | "))?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version_reader::{PeArch, VersionStrings};
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_write_json() {
+        let files = vec![
+            FileInfo {
+                path: PathBuf::from("bin/app.dll"),
+                version: Some("1.2.3.4".parse().unwrap()),
+                size: Some(4096),
+                modified: Some(std::time::UNIX_EPOCH + Duration::from_secs(86400)),
+                ..Default::default()
+            },
+            FileInfo {
+                path: PathBuf::from("readme.txt"),
+                ..Default::default()
+            },
+        ];
+
+        let options = DisplayOptions {
+            include_version: true,
+            ..Default::default()
+        };
+        let mut buffer = Vec::new();
+        write_json_to(&mut buffer, &files, &options).unwrap();
+        let parsed: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+        assert_eq!(
+            parsed,
+            json!([
+                {
+                    "path": "bin/app.dll",
+                    "version": "1.2.3.4",
+                    "size": 4096,
+                    "modified": "1970-01-02T00:00:00Z",
+                },
+                { "path": "readme.txt" },
+            ])
+        );
+
+        // Versions are left out unless requested
+        let json = file_json(&files[0], &DisplayOptions::default());
+        assert_eq!(json.get("version"), None);
+
+        let mut buffer = Vec::new();
+        write_json_to(&mut buffer, &[], &options).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), "[]\n");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_schema() {
+        let schema = json_schema();
+        assert_eq!(schema["type"], "array");
+
+        // Every field of the output is described, only the path is required
+        let item = &schema["$defs"]["FileRecord"];
+        let properties = item["properties"].as_object().unwrap();
+        let mut names: Vec<&str> = properties.keys().map(String::as_str).collect();
+        let mut fields: Vec<&str> = RecordField::ALL.iter().map(|field| field.name()).collect();
+        names.sort_unstable();
+        fields.sort_unstable();
+        assert_eq!(names, fields);
+        assert_eq!(item["required"], json!(["path"]));
+        assert_eq!(properties["size"]["type"], json!(["integer", "null"]));
+    }
+
+    #[test]
+    fn test_write_json_path_styles() {
+        let root = std::env::temp_dir().join("flist_root");
+        let path = root.join("sub").join("file.dll");
+        let relative = PathBuf::from("sub").join("file.dll").display().to_string();
+        let files = vec![FileInfo {
+            path: path.clone(),
+            ..Default::default()
+        }];
+        let json = |path_style: PathStyle| {
+            let options = DisplayOptions {
+                path_style,
+                root: root.clone(),
+                ..Default::default()
+            };
+            let mut buffer = Vec::new();
+            write_json_to(&mut buffer, &files, &options).unwrap();
+            serde_json::from_slice::<serde_json::Value>(&buffer).unwrap()
+        };
+
+        let full = path.display().to_string();
+        assert_eq!(json(PathStyle::Full), json!([{ "path": full }]));
+        assert_eq!(json(PathStyle::Relative), json!([{ "path": relative }]));
+        assert_eq!(json(PathStyle::Absolute), json!([{ "path": full }]));
+        // Both paths, as separate fields
+        assert_eq!(
+            json(PathStyle::Both),
+            json!([{ "path": relative, "absolute_path": full }])
+        );
+
+        // The same fields in the CSV header
+        let options = DisplayOptions {
+            path_style: PathStyle::Both,
+            root: root.clone(),
+            ..Default::default()
+        };
+        let mut buffer = Vec::new();
+        write_csv_to(&mut buffer, &files, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            format!(
+                "path,absolute_path,version,size,modified\n{},{},,,\n",
+                relative, full
+            )
+        );
+    }
+
+    #[test]
+    fn test_records_include_optional_fields() {
+        let time = std::time::UNIX_EPOCH + Duration::from_secs(86400);
+        let files = vec![
+            FileInfo {
+                path: PathBuf::from("app.dll"),
+                version: Some("1.2.3.4".parse().unwrap()),
+                product_version: Some("1.2.0.0".parse().unwrap()),
+                version_strings: Some(VersionStrings {
+                    company: Some("Contoso".to_string()),
+                    product: Some("App".to_string()),
+                    ..Default::default()
+                }),
+                size: Some(4096),
+                created: Some(time),
+                pe_timestamp: Some(time),
+                arch: Some(PeArch::X64),
+                checksum_ok: Some(false),
+                signed: Some(false),
+                hash: Some("af1349b9".to_string()),
+                ..Default::default()
+            },
+            FileInfo {
+                path: PathBuf::from("readme.txt"),
+                size: Some(10),
+                ..Default::default()
+            },
+        ];
+        let options = DisplayOptions {
+            include_version: true,
+            show_product_version: true,
+            string_fields: vec![StringField::Company],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            file_json(&files[0], &options),
+            json!({
+                "path": "app.dll",
+                "version": "1.2.3.4",
+                "product_version": "1.2.0.0",
+                "company": "Contoso",
+                "size": 4096,
+                "created": "1970-01-02T00:00:00Z",
+                "pe_timestamp": "1970-01-02T00:00:00Z",
+                "arch": "x64",
+                "checksum_ok": false,
+                "signed": false,
+                "hash": "af1349b9",
+            })
+        );
+
+        // The CSV columns are the same fields, empty where a file lacks them
+        let mut buffer = Vec::new();
+        write_csv_to(&mut buffer, &files, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "path,version,product_version,company,size,modified,created,pe_timestamp,arch,\
+             checksum_ok,signed,hash\n\
+             app.dll,1.2.3.4,1.2.0.0,Contoso,4096,,1970-01-02T00:00:00Z,1970-01-02T00:00:00Z,\
+             x64,false,false,af1349b9\n\
+             readme.txt,,,,10,,,,,,,\n"
+        );
+
+        let mut buffer = Vec::new();
+        write_xml_to(&mut buffer, &files[1..], &options).unwrap();
+        assert!(
+            String::from_utf8(buffer)
+                .unwrap()
+                .contains(r#"<file path="readme.txt" size="10"/>"#)
+        );
+    }
+
+    #[test]
+    fn test_write_ndjson() {
+        let files = vec![
+            FileInfo {
+                path: PathBuf::from("bin/app.dll"),
+                version: Some("1.2.3.4".parse().unwrap()),
+                size: Some(4096),
+                modified: Some(std::time::UNIX_EPOCH + Duration::from_secs(86400)),
+                ..Default::default()
+            },
+            FileInfo {
+                path: PathBuf::from("line\nbreak.txt"),
+                ..Default::default()
+            },
+        ];
+
+        let options = DisplayOptions {
+            include_version: true,
+            ..Default::default()
+        };
+        let mut buffer = Vec::new();
+        write_ndjson_to(&mut buffer, &files, &options).unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+
+        // One object per line, each the same as in the JSON array
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        for (line, file_info) in lines.iter().zip(&files) {
+            let parsed: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(parsed, file_json(file_info, &options));
+        }
+
+        let mut buffer = Vec::new();
+        write_ndjson_to(&mut buffer, &[], &options).unwrap();
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let files = vec![
+            FileInfo {
+                path: PathBuf::from("bin/app.dll"),
+                version: Some("1.2.3.4".parse().unwrap()),
+                size: Some(4096),
+                modified: Some(std::time::UNIX_EPOCH + Duration::from_secs(86400)),
+                ..Default::default()
+            },
+            FileInfo {
+                path: PathBuf::from("Setup, \"full\".exe"),
+                ..Default::default()
+            },
+        ];
+
+        let options = DisplayOptions {
+            include_version: true,
+            ..Default::default()
+        };
+        let mut buffer = Vec::new();
+        write_csv_to(&mut buffer, &files, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "path,version,size,modified\n\
+             bin/app.dll,1.2.3.4,4096,1970-01-02T00:00:00Z\n\
+             \"Setup, \"\"full\"\".exe\",,,\n"
+        );
+
+        // The version column stays, empty, when versions are not requested
+        let mut buffer = Vec::new();
+        write_csv_to(&mut buffer, &files[..1], &DisplayOptions::default()).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "path,version,size,modified\nbin/app.dll,,4096,1970-01-02T00:00:00Z\n"
+        );
+    }
+
+    #[test]
+    fn test_write_xml() {
+        let files = vec![
+            FileInfo {
+                path: PathBuf::from("bin/app.dll"),
+                version: Some("1.2.3.4".parse().unwrap()),
+                size: Some(4096),
+                modified: Some(std::time::UNIX_EPOCH + Duration::from_secs(86400)),
+                ..Default::default()
+            },
+            FileInfo {
+                path: PathBuf::from("R&D <\"new\"> 'x'\u{1}.exe"),
+                ..Default::default()
+            },
+        ];
+
+        let options = DisplayOptions {
+            include_version: true,
+            ..Default::default()
+        };
+        let mut buffer = Vec::new();
+        write_xml_to(&mut buffer, &files, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <files>\n\
+             \x20 <file path=\"bin/app.dll\" version=\"1.2.3.4\" size=\"4096\" modified=\"1970-01-02T00:00:00Z\"/>\n\
+             \x20 <file path=\"R&amp;D &lt;&quot;new&quot;&gt; &apos;x&apos;\u{FFFD}.exe\"/>\n\
+             </files>\n"
+        );
+
+        // Versions that were not requested are absent, as is an empty list's content
+        let mut buffer = Vec::new();
+        write_xml_to(&mut buffer, &files[..1], &DisplayOptions::default()).unwrap();
+        assert!(!String::from_utf8(buffer).unwrap().contains("version=\"1.2"));
+        let mut buffer = Vec::new();
+        write_xml_to(&mut buffer, &[], &options).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<files>\n</files>\n"
+        );
+    }
+
+    #[test]
+    fn test_write_markdown() {
+        let files = vec![
+            FileInfo {
+                path: PathBuf::from("bin/app.dll"),
+                version: Some("1.2.3.4".parse().unwrap()),
+                size: Some(4096),
+                modified: Some(std::time::UNIX_EPOCH + Duration::from_secs(86400)),
+                ..Default::default()
+            },
+            FileInfo {
+                path: PathBuf::from("a|b.exe"),
+                ..Default::default()
+            },
+        ];
+
+        let options = DisplayOptions {
+            include_version: true,
+            ..Default::default()
+        };
+        let mut buffer = Vec::new();
+        write_markdown_to(&mut buffer, &files, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "| Path | Version | Size | Modified |\n\
+             | --- | --- | ---: | --- |\n\
+             | bin/app.dll | 1.2.3.4 | 4096 | 1970-01-02T00:00:00Z |\n\
+             | a\\|b.exe |  |  |  |\n"
+        );
+
+        // Columns that were not requested or not collected are left out
+        let mut buffer = Vec::new();
+        write_markdown_to(&mut buffer, &files[1..], &DisplayOptions::default()).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "| Path |\n| --- |\n| a\\|b.exe |\n"
+        );
+    }
+
+    #[test]
+    fn test_write_output_file_in_other_format() {
+        let files = vec![FileInfo {
+            path: PathBuf::from("app.dll"),
+            version: Some("1.2.3.4".parse().unwrap()),
+            ..Default::default()
+        }];
+        // Console options of a text run
+        let options = DisplayOptions {
+            include_version: true,
+            max_width: Some(4),
+            file_summary: true,
+            ..Default::default()
+        };
+
+        let temp_file = std::env::temp_dir().join("flist_test_output_format.json");
+        write_output_file(&files, &temp_file, OutputFormat::Json, &options, false).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&temp_file).unwrap()).unwrap();
+        assert_eq!(json[0]["path"], "app.dll");
+        assert_eq!(json[0]["version"], "1.2.3.4");
+
+        assert!(write_output_file(&files, &temp_file, OutputFormat::Json, &options, true).is_err());
+        fs::remove_file(&temp_file).unwrap();
+    }
+
+    #[test]
+    fn test_duplicate_lines() {
+        let file = |name: &str, hash: Option<&str>| FileInfo {
+            path: PathBuf::from(name),
+            hash: hash.map(str::to_string),
+            ..Default::default()
+        };
+        let files = vec![
+            file("a/app.dll", Some("aaaa")),
+            file("a/core.dll", Some("cccc")),
+            file("a/unique.dll", Some("uuuu")),
+            file("b/app.dll", Some("aaaa")),
+            file("b/core.dll", Some("cccc")),
+            file("b/unreadable.dll", None),
+            file("c/app.dll", Some("aaaa")),
+        ];
+        assert_eq!(
+            duplicate_lines(&files),
+            vec![
+                "",
+                "Found 2 groups of identical files.",
+                "",
+                "aaaa (3 files)",
+                "  a/app.dll",
+                "  b/app.dll",
+                "  c/app.dll",
+                "",
+                "cccc (2 files)",
+                "  a/core.dll",
+                "  b/core.dll",
+            ]
+        );
+
+        assert_eq!(
+            duplicate_lines(&files[..3]),
+            vec!["", "Found 0 groups of identical files."]
+        );
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1024), "1.0 KB");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(20 << 20), "20.0 MB");
+        assert_eq!(format_size(1_395_864_371), "1.3 GB");
+        assert_eq!(format_size(3 << 50), "3072.0 TB");
+    }
+
+    #[test]
+    fn test_total_size_line() {
+        let file = |size: Option<u64>| FileInfo {
+            path: PathBuf::from("a.dll"),
+            size,
+            ..Default::default()
+        };
+        assert_eq!(total_size_line(&[]), None);
+        assert_eq!(total_size_line(&[file(None), file(None)]), None);
+        assert_eq!(
+            total_size_line(&[file(Some(1024)), file(Some(2048))]).unwrap(),
+            "Matched 2 files totaling 3.0 KB."
+        );
+        assert_eq!(
+            total_size_line(&[file(Some(100)), file(None)]).unwrap(),
+            "Matched 2 files totaling 100 B (1 files of unknown size)."
+        );
+    }
+
+    #[test]
+    fn test_version_summary_lines() {
+        let file = |version: Option<&str>| FileInfo {
+            path: PathBuf::from("app.dll"),
+            version: version.map(|v| v.parse().unwrap()),
+            ..Default::default()
+        };
+        let files = vec![
+            file(Some("10.0.1.0")),
+            file(Some("2.1")),
+            file(None),
+            file(Some("10.2.0.0")),
+            file(Some("2.0.0.7")),
+            file(Some("10.0.0.0")),
+        ];
+        assert_eq!(
+            version_summary_lines(&files),
+            vec![
+                "Major version  Files",
+                "2.x                2",
+                "10.x               3",
+                "unknown            1",
+                "5 files with version, 1 without.",
+            ]
+        );
+
+        assert_eq!(
+            version_summary_lines(&[]),
+            vec!["Major version  Files", "0 files with version, 0 without."]
+        );
+    }
+
+    #[test]
+    fn test_append_to_file() {
+        let files = |name: &str| {
+            vec![FileInfo {
+                path: PathBuf::from(name),
+                size: Some(1),
+                ..Default::default()
+            }]
+        };
+        let options = DisplayOptions {
+            file_summary: true,
+            ..Default::default()
+        };
+
+        let temp_file = std::env::temp_dir().join("flist_test_output_append.txt");
+        write_to_file_with_options(&files("old.dll"), &temp_file, &options).unwrap();
+        write_to_file_with_options(&files("a.dll"), &temp_file, &options).unwrap();
+        write_to_file_append(&files("b.dll"), &temp_file, &options).unwrap();
+        assert_eq!(
+            fs::read_to_string(&temp_file).unwrap(),
+            "Found 1 files.\n\na.dll\n\nFound 1 files.\nFound 1 files.\n\nb.dll\n\nFound 1 files.\n"
+        );
+        fs::remove_file(&temp_file).unwrap();
+
+        // The CSV header is written only into an empty file
+        let temp_file = std::env::temp_dir().join("flist_test_output_append.csv");
+        let _ = fs::remove_file(&temp_file);
+        let options = DisplayOptions::default();
+        append_csv_to_file(&files("a.dll"), &temp_file, &options).unwrap();
+        append_csv_to_file(&files("b.dll"), &temp_file, &options).unwrap();
+        assert_eq!(
+            fs::read_to_string(&temp_file).unwrap(),
+            "path,version,size,modified\na.dll,,1,\nb.dll,,1,\n"
+        );
+        fs::remove_file(&temp_file).unwrap();
+    }
+
+    #[test]
+    fn test_truncate_middle() {
+        // Short paths are untouched
+        assert_eq!(truncate_middle("a/b.dll", 10), "a/b.dll");
+        assert_eq!(truncate_middle("a/b.dll", 7), "a/b.dll");
+
+        // Start and file name are kept, the middle is elided
+        assert_eq!(
+            truncate_middle("/very/long/path/to/file.dll", 16),
+            "/very/l…file.dll"
+        );
+        assert_eq!(
+            truncate_middle("/very/long/path/to/file.dll", 16)
+                .chars()
+                .count(),
+            16
+        );
+
+        // File names longer than the width keep the first directory, up to a
+        // third of the width, and the end of the name
+        assert_eq!(truncate_middle("rv/libz.so.1", 10), "rv/…z.so.1");
+        assert_eq!(truncate_middle("dir/averyverylongname.dll", 8), "di…e.dll");
+        assert_eq!(
+            truncate_middle("/usr/lib/averyverylongname.dll", 16),
+            "/usr/…ngname.dll"
+        );
+        assert_eq!(truncate_middle("averyverylongname.dll", 8), "…ame.dll");
+
+        // Degenerate widths
+        assert_eq!(truncate_middle("abc/def", 1), "…");
+        assert_eq!(truncate_middle("abc/def", 0), "");
+    }
+
+    #[test]
+    fn test_parse_template() {
+        let template: LineTemplate = "{{{name}}} v{version}".parse().unwrap();
+        assert_eq!(
+            template.parts,
+            vec![
+                TemplatePart::Text("{".to_string()),
+                TemplatePart::Field(TemplateField::Name),
+                TemplatePart::Text("} v".to_string()),
+                TemplatePart::Field(TemplateField::Version),
+            ]
+        );
+        assert!(template.uses(TemplateField::Name));
+        assert!(!template.uses(TemplateField::Path));
+
+        for invalid in ["{nmae}", "{}", "{path", "path}", "{Path}"] {
+            assert!(invalid.parse::<LineTemplate>().is_err(), "{}", invalid);
+        }
+        let error = "{sise}".parse::<LineTemplate>().unwrap_err().to_string();
+        assert!(error.contains("'{sise}'"), "{}", error);
+        assert!(error.contains("{size}"), "{}", error);
+    }
+
+    #[test]
+    fn test_format_line_with_template() {
+        let file_info = FileInfo {
+            path: PathBuf::from("root").join("sub").join("app.dll"),
+            version: Some("1.2.3.4".parse().unwrap()),
+            size: Some(4096),
+            ..Default::default()
+        };
+        let options = DisplayOptions {
+            template: Some("{size};{name};{dir};{version};{arch}".parse().unwrap()),
+            path_style: PathStyle::Relative,
+            root: PathBuf::from("root"),
+            color: true,
+            ..Default::default()
+        };
+
+        // Columns and colors are replaced, unknown values are empty
+        let path = format_entry_path(&file_info, &options);
+        assert_eq!(
+            format_line(&file_info, &path, &fixed_column_widths(&options), &options),
+            "4096;app.dll;sub;1.2.3.4;"
+        );
+
+        let options = DisplayOptions {
+            template: Some("{path}".parse().unwrap()),
+            ..options
+        };
+        assert_eq!(
+            format_line(&file_info, &path, &[], &options),
+            Path::new("sub").join("app.dll").display().to_string()
+        );
+    }
+
+    #[test]
+    fn test_format_line_with_checksum() {
+        let file_info = FileInfo {
+            path: PathBuf::from("app.exe"),
+            version: Some("1.2.3.4".parse().unwrap()),
+            checksum_ok: Some(false),
+            ..Default::default()
+        };
+        let options = DisplayOptions {
+            include_version: true,
+            show_checksum: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_line(
+                &file_info,
+                "app.exe",
+                &fixed_column_widths(&options),
+                &options
+            ),
+            "1.2.3.4         MISMATCH app.exe"
+        );
+
+        let options = DisplayOptions {
+            show_checksum: true,
+            ..Default::default()
+        };
+        let file_info = FileInfo {
+            checksum_ok: None,
+            ..file_info
+        };
+        assert_eq!(
+            format_line(
+                &file_info,
+                "app.exe",
+                &fixed_column_widths(&options),
+                &options
+            ),
+            "         app.exe"
+        );
+    }
+
+    #[test]
+    fn test_timing_line() {
+        let phases = [
+            ("scan", Duration::from_millis(1234)),
+            ("output", Duration::from_micros(500)),
+        ];
+        assert_eq!(
+            timing_line(&phases, Duration::from_millis(1240)),
+            "Timing: scan 1.234 s, output 0.001 s, total 1.240 s."
+        );
+        assert_eq!(
+            timing_line(&[], Duration::from_millis(5)),
+            "Timing: total 0.005 s."
+        );
+    }
+
+    #[test]
+    fn test_group_by_dir() {
+        let file = |path: &str, version: Option<&str>| FileInfo {
+            path: PathBuf::from(path),
+            version: version.map(|v| v.parse().unwrap()),
+            ..Default::default()
+        };
+        let files = vec![
+            file("app/lib/core.dll", Some("10.0.19041.1")),
+            file("app/bin/app.exe", Some("1.2.3.4")),
+            file("readme.txt", None),
+            file("app/bin/helper.dll", None),
+        ];
+        let options = DisplayOptions {
+            include_version: true,
+            group_by_dir: true,
+            ..Default::default()
+        };
+
+        let widths = column_widths(&files, &options);
+        let text = dir_group_lines(&files, &widths, &options).join("\n");
+        assert_eq!(
+            text,
+            "DIR: .\n\
+             \x20              readme.txt\n\
+             \n\
+             DIR: app/bin\n\
+             \x20 1.2.3.4      app.exe\n\
+             \x20              helper.dll\n\
+             \n\
+             DIR: app/lib\n\
+             \x20 10.0.19041.1 core.dll"
+        );
+    }
+
+    #[test]
+    fn test_format_line_with_product_version() {
+        let file_info = FileInfo {
+            path: PathBuf::from("app.exe"),
+            version: Some("1.2.3.4".parse().unwrap()),
+            product_version: Some("10.0".parse().unwrap()),
+            ..Default::default()
+        };
+        let options = DisplayOptions {
+            include_version: true,
+            show_product_version: true,
+            field_separator: Some("|".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            format_line(
+                &file_info,
+                "app.exe",
+                &fixed_column_widths(&options),
+                &options
+            ),
+            "1.2.3.4|10.0.0.0|app.exe"
+        );
+
+        let options = DisplayOptions {
+            show_product_version: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_line(
+                &file_info,
+                "app.exe",
+                &fixed_column_widths(&options),
+                &options
+            ),
+            "10.0.0.0        app.exe"
+        );
+    }
+
+    #[test]
+    fn test_format_line_skips_empty_columns() {
+        let files = vec![
+            FileInfo {
+                path: PathBuf::from("./x.dll"),
+                ..Default::default()
+            },
+            FileInfo {
+                path: PathBuf::from("./y.dll"),
+                ..Default::default()
+            },
+        ];
+        let options = DisplayOptions {
+            include_version: true,
+            show_size: true,
+            ..Default::default()
+        };
+        let widths = column_widths(&files, &options);
+        assert_eq!(widths, vec![0, 0]);
+        assert_eq!(
+            format_line(&files[0], "./x.dll", &widths, &options),
+            "./x.dll"
+        );
+
+        // Delimited output keeps every column, so fields stay in place
+        let options = DisplayOptions {
+            field_separator: Some("|".to_string()),
+            ..options
+        };
+        let widths = column_widths(&files, &options);
+        assert_eq!(
+            format_line(&files[0], "./x.dll", &widths, &options),
+            "||./x.dll"
+        );
+    }
+
+    #[test]
+    fn test_format_line_with_version_strings() {
+        let file_info = FileInfo {
+            path: PathBuf::from("app.exe"),
+            version_strings: Some(VersionStrings {
+                company: Some("Contoso Ltd.".to_string()),
+                description: Some("Widget editor".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let options = DisplayOptions {
+            string_fields: vec![
+                StringField::Company,
+                StringField::Product,
+                StringField::Description,
+            ],
+            field_separator: Some("|".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            format_line(
+                &file_info,
+                "app.exe",
+                &fixed_column_widths(&options),
+                &options
+            ),
+            "Contoso Ltd.||Widget editor|app.exe"
+        );
+
+        // Files without strings get empty columns
+        let file_info = FileInfo {
+            version_strings: None,
+            ..file_info
+        };
+        assert_eq!(
+            format_line(
+                &file_info,
+                "app.exe",
+                &fixed_column_widths(&options),
+                &options
+            ),
+            "|||app.exe"
+        );
+    }
+
+    #[test]
+    fn test_semantic_version_output() {
+        let file_info = FileInfo {
+            path: PathBuf::from("app.dll"),
+            version: Some("1.2.3.0".parse().unwrap()),
+            semantic_version: Some("1.2.3-beta.2".parse().unwrap()),
+            ..Default::default()
+        };
+        let options = DisplayOptions {
+            include_version: true,
+            show_semantic_version: true,
+            field_separator: Some("|".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            format_line(
+                &file_info,
+                "app.dll",
+                &fixed_column_widths(&options),
+                &options
+            ),
+            "1.2.3.0|1.2.3-beta.2|app.dll"
+        );
+        assert_eq!(
+            file_json(&file_info, &options),
+            json!({ "path": "app.dll", "version": "1.2.3.0", "semantic_version": "1.2.3-beta.2" })
+        );
+
+        // Read only for a filter, it is not written
+        let options = DisplayOptions::default();
+        assert_eq!(
+            file_json(&file_info, &options),
+            json!({ "path": "app.dll" })
+        );
+    }
+
+    #[test]
+    fn test_format_line_with_created() {
+        let file_info = FileInfo {
+            path: PathBuf::from("app.exe"),
+            created: Some(std::time::UNIX_EPOCH + Duration::from_secs(86400)),
+            ..Default::default()
+        };
+        let options = DisplayOptions {
+            show_created: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_line(
+                &file_info,
+                "app.exe",
+                &fixed_column_widths(&options),
+                &options
+            ),
+            "1970-01-02T00:00:00Z app.exe"
+        );
+
+        // Unknown creation times leave the column empty
+        let file_info = FileInfo {
+            created: None,
+            ..file_info
+        };
+        assert_eq!(
+            format_line(
+                &file_info,
+                "app.exe",
+                &fixed_column_widths(&options),
+                &options
+            ),
+            format!("{:21}app.exe", "")
+        );
+    }
+
+    #[test]
+    fn test_format_line_with_pe_timestamp() {
+        let file_info = FileInfo {
+            path: PathBuf::from("app.exe"),
+            created: Some(std::time::UNIX_EPOCH + Duration::from_secs(86400)),
+            pe_timestamp: Some(std::time::UNIX_EPOCH + Duration::from_secs(3661)),
+            ..Default::default()
+        };
+        let options = DisplayOptions {
+            show_created: true,
+            show_pe_timestamp: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_line(
+                &file_info,
+                "app.exe",
+                &column_widths(std::slice::from_ref(&file_info), &options),
+                &options
+            ),
+            "1970-01-02T00:00:00Z 1970-01-01T01:01:01Z app.exe"
+        );
+    }
+
+    #[test]
+    fn test_format_line_with_arch() {
+        let file_info = FileInfo {
+            path: PathBuf::from("app.dll"),
+            version: Some("1.2.3.4".parse().unwrap()),
+            arch: Some(PeArch::Arm64),
+            ..Default::default()
+        };
+        let options = DisplayOptions {
+            include_version: true,
+            show_arch: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_line(
+                &file_info,
+                "app.dll",
+                &fixed_column_widths(&options),
+                &options
+            ),
+            "1.2.3.4         arm64    app.dll"
+        );
+    }
+
+    #[test]
+    fn test_format_line_with_field_separator() {
+        let file_info = FileInfo {
+            path: PathBuf::from("C:\\foo.dll"),
+            version: Some("1.2.3.4".parse().unwrap()),
+            ..Default::default()
+        };
+        let options = DisplayOptions {
+            include_version: true,
+            field_separator: Some("|".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            format_line(
+                &file_info,
+                "C:\\foo.dll",
+                &fixed_column_widths(&options),
+                &options
+            ),
+            "1.2.3.4|C:\\foo.dll"
+        );
+
+        // Missing versions keep an empty column
+        let file_info = FileInfo {
+            version: None,
+            ..file_info
+        };
+        assert_eq!(
+            format_line(
+                &file_info,
+                "C:\\foo.dll",
+                &fixed_column_widths(&options),
+                &options
+            ),
+            "|C:\\foo.dll"
+        );
+
+        // Without columns the separator never appears
+        let options = DisplayOptions {
+            field_separator: Some("\t".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            format_line(
+                &file_info,
+                "C:\\foo.dll",
+                &fixed_column_widths(&options),
+                &options
+            ),
+            "C:\\foo.dll"
+        );
+    }
+
+    #[test]
+    fn test_color_choice() {
+        assert!(ColorChoice::Auto.resolve(false, true));
+        assert!(!ColorChoice::Auto.resolve(true, true));
+        assert!(!ColorChoice::Auto.resolve(false, false));
+        assert!(ColorChoice::Always.resolve(true, false));
+        assert!(!ColorChoice::Never.resolve(false, true));
+    }
+
+    #[test]
+    fn test_format_line_with_color() {
+        let file_info = FileInfo {
+            path: PathBuf::from("app.dll"),
+            version: Some("1.2.3.4".parse().unwrap()),
+            ..Default::default()
+        };
+        let options = DisplayOptions {
+            include_version: true,
+            color: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_line(&file_info, "app.dll", &[7], &options),
+            "\x1b[1m1.2.3.4\x1b[0m \x1b[2mapp.dll\x1b[0m"
+        );
+
+        // Files without version are red
+        let file_info = FileInfo {
+            version: None,
+            ..file_info
+        };
+        assert_eq!(
+            format_line(&file_info, "app.dll", &[7], &options),
+            "\x1b[1m       \x1b[0m \x1b[31mapp.dll\x1b[0m"
+        );
+
+        // Files never contain escape sequences
+        let temp_file = std::env::temp_dir().join("flist_test_output_color.txt");
+        write_to_file_with_options(&[file_info], &temp_file, &options).unwrap();
+        assert_eq!(fs::read_to_string(&temp_file).unwrap(), "app.dll\n");
+        fs::remove_file(&temp_file).unwrap();
+    }
+
+    #[test]
+    fn test_null_separated() {
+        let files = vec![
+            FileInfo {
+                path: PathBuf::from("dir/my app.dll"),
+                version: Some("1.2.3.4".parse().unwrap()),
+                streams: vec!["Zone.Identifier".to_string()],
+                ..Default::default()
+            },
+            FileInfo {
+                path: PathBuf::from("dir/long/path/b.dll"),
+                ..Default::default()
+            },
+        ];
+        let options = DisplayOptions {
+            include_version: true,
+            max_width: Some(5),
+            color: true,
+            file_summary: true,
+            null_separated: true,
+            ..Default::default()
+        };
+
+        // Versions, colors and shortening are omitted
+        assert_eq!(
+            null_record(&files[0], &options),
+            "dir/my app.dll\0dir/my app.dll:Zone.Identifier\0"
+        );
+
+        let temp_file = std::env::temp_dir().join("flist_test_output_null.txt");
+        write_to_file_with_options(&files, &temp_file, &options).unwrap();
+        assert_eq!(
+            fs::read_to_string(&temp_file).unwrap(),
+            "dir/my app.dll\0dir/my app.dll:Zone.Identifier\0dir/long/path/b.dll\0"
+        );
+        fs::remove_file(&temp_file).unwrap();
+    }
+
+    #[test]
+    fn test_column_widths_fit_values() {
+        let files = vec![
+            FileInfo {
+                path: PathBuf::from("a.dll"),
+                version: Some("1.0".parse().unwrap()),
+                size: Some(512),
+                ..Default::default()
+            },
+            FileInfo {
+                path: PathBuf::from("b.dll"),
+                version: Some("10.20.30.40".parse().unwrap()),
+                size: Some(1048576),
+                ..Default::default()
+            },
+            FileInfo {
+                path: PathBuf::from("c.txt"),
+                ..Default::default()
+            },
+        ];
+        let options = DisplayOptions {
+            include_version: true,
+            show_size: true,
+            ..Default::default()
+        };
+
+        let widths = column_widths(&files, &options);
+        assert_eq!(widths, vec![11, 7]);
+        let lines: Vec<String> = files
+            .iter()
+            .map(|file_info| {
+                format_line(
+                    file_info,
+                    &file_info.path.display().to_string(),
+                    &widths,
+                    &options,
+                )
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "1.0.0.0         512 a.dll",
+                "10.20.30.40 1048576 b.dll",
+                "                    c.txt",
+            ]
+        );
+
+        // No files, no width
+        assert_eq!(column_widths(&[], &options), vec![0, 0]);
+        assert_eq!(fixed_column_widths(&options), vec![15, 12]);
+    }
+
+    #[test]
+    fn test_format_path_styles() {
+        let root = std::env::temp_dir().join("flist_root");
+        let path = root.join("sub").join("file.dll");
+        let relative = PathBuf::from("sub").join("file.dll");
+        let mut options = DisplayOptions {
+            root: root.clone(),
+            ..Default::default()
+        };
+
+        assert_eq!(format_path(&path, "", &options), path.display().to_string());
+
+        options.path_style = PathStyle::Relative;
+        assert_eq!(
+            format_path(&path, "", &options),
+            relative.display().to_string()
+        );
+
+        // Paths outside the root are kept as found
+        let outside = PathBuf::from("elsewhere").join("file.dll");
+        assert_eq!(
+            format_path(&outside, "", &options),
+            outside.display().to_string()
+        );
+
+        options.path_style = PathStyle::Absolute;
+        assert_eq!(format_path(&path, "", &options), path.display().to_string());
+        let absolute = format_path(&outside, "", &options);
+        assert!(Path::new(&absolute).is_absolute());
+
+        // Relative first, then absolute, joined by a tab or the field separator
+        options.path_style = PathStyle::Both;
+        assert_eq!(
+            format_path(&path, "", &options),
+            format!("{}\t{}", relative.display(), path.display())
+        );
+        options.field_separator = Some("|".to_string());
+        assert_eq!(
+            format_path(&path, "", &options),
+            format!("{}|{}", relative.display(), path.display())
+        );
+    }
+
+    #[test]
+    fn test_format_entry_path_uses_file_root() {
+        let file = FileInfo {
+            path: PathBuf::from("second").join("sub").join("file.dll"),
+            root: Some(PathBuf::from("second")),
+            ..Default::default()
+        };
+        let options = DisplayOptions {
+            path_style: PathStyle::Relative,
+            root: PathBuf::from("first"),
+            show_root: true,
+            field_separator: Some(",".to_string()),
+            ..Default::default()
+        };
+        let relative = PathBuf::from("sub").join("file.dll");
+        assert_eq!(
+            format_entry_path(&file, &options),
+            relative.display().to_string()
+        );
+        assert_eq!(
+            format_line(&file, "sub", &column_widths(&[], &options), &options),
+            "second,sub"
+        );
+    }
+
+    #[test]
+    fn test_format_entry_path_marks_directories() {
+        let root = PathBuf::from("root");
+        let dir = FileInfo {
+            path: root.join("app_cache"),
+            is_dir: true,
+            ..Default::default()
+        };
+        let sep = std::path::MAIN_SEPARATOR;
+        let mut options = DisplayOptions {
+            root: root.clone(),
+            path_style: PathStyle::Relative,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            format_entry_path(&dir, &options),
+            format!("app_cache{}", sep)
+        );
+
+        options.path_style = PathStyle::Both;
+        let both = format_entry_path(&dir, &options);
+        let (relative, absolute) = both.split_once('\t').unwrap();
+        assert_eq!(relative, format!("app_cache{}", sep));
+        assert!(absolute.ends_with(&format!("app_cache{}", sep)));
+
+        let file = FileInfo {
+            path: root.join("cache.db"),
+            ..Default::default()
+        };
+        options.path_style = PathStyle::Relative;
+        assert_eq!(format_entry_path(&file, &options), "cache.db");
+    }
+
+    #[test]
+    fn test_write_to_file_summary() {
+        let files = vec![FileInfo {
+            path: PathBuf::from("file1.dll"),
+            ..Default::default()
+        }];
+        let temp_file = std::env::temp_dir().join("flist_test_output_summary.txt");
+
+        // No summary by default
+        write_to_file_with_options(&files, &temp_file, &DisplayOptions::default()).unwrap();
+        let content = fs::read_to_string(&temp_file).unwrap();
+        assert_eq!(content, "file1.dll\n");
+
+        // The summary is written even in quiet mode
+        let options = DisplayOptions {
+            file_summary: true,
+            quiet: true,
+            ..Default::default()
+        };
+        write_to_file_with_options(&files, &temp_file, &options).unwrap();
+        let content = fs::read_to_string(&temp_file).unwrap();
+        assert_eq!(content, "Found 1 files.\n\nfile1.dll\n\nFound 1 files.\n");
+
+        // Limited results say how many were found
+        let options = DisplayOptions {
+            total: Some(5000),
+            ..options
+        };
+        write_to_file_with_options(&files, &temp_file, &options).unwrap();
+        let content = fs::read_to_string(&temp_file).unwrap();
+        assert_eq!(
+            content,
+            "Showing 1 of 5000 files.\n\nfile1.dll\n\nShowing 1 of 5000 files.\n"
+        );
+
+        fs::remove_file(&temp_file).unwrap();
+    }
+
+    #[test]
+    fn test_write_summary_json() {
+        let files = vec![
+            FileInfo {
+                path: PathBuf::from("a.dll"),
+                version: Some("1.2.0.0".parse().unwrap()),
+                checksum_ok: Some(false),
+                ..Default::default()
+            },
+            FileInfo {
+                path: PathBuf::from("b.txt"),
+                streams: vec!["Zone.Identifier".to_string()],
+                ..Default::default()
+            },
+        ];
+        let parameters = RunParameters {
+            directory: PathBuf::from("C:\\Program Files"),
+            patterns: vec!["*.dll".to_string(), "*.txt".to_string()],
+            include_version: true,
+            min_version: Some("1.0".parse().unwrap()),
+            ..Default::default()
+        };
+
+        let temp_file = std::env::temp_dir().join("flist_test_summary.json");
+        write_summary_json(&files, &temp_file, &parameters, Duration::from_millis(1234)).unwrap();
+
+        let content = fs::read_to_string(&temp_file).unwrap();
+        let summary: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(
+            summary,
+            json!({
+                "schema_version": 1,
+                "totals": {
+                    "files": 2,
+                    "with_version": 1,
+                    "without_version": 1,
+                    "checksum_mismatches": 1,
+                    "streams": 1,
+                    "directories": 0,
+                },
+                "timing": { "elapsed_ms": 1234 },
+                "parameters": {
+                    "directory": "C:\\Program Files",
+                    "patterns": ["*.dll", "*.txt"],
+                    "include_version": true,
+                    "min_version": "1.0.0.0",
+                    "max_version": null,
+                    "exclude_versions": [],
+                    "reference_version": null,
+                    "created_after": null,
+                    "created_before": null,
+                    "verify_checksum": false,
+                    "min_size": null,
+                    "max_size": null,
+                    "modified_after": null,
+                    "modified_before": null,
+                },
+            })
+        );
+        // Only aggregates are written, not the file list
+        assert!(!content.contains("a.dll"));
+
+        fs::remove_file(&temp_file).unwrap();
+    }
+
+    #[test]
+    fn test_same_group() {
+        let file = |path: &str, version: Option<&str>| FileInfo {
+            path: PathBuf::from(path),
+            version: version.map(|v| v.parse().unwrap()),
+            ..Default::default()
+        };
+        let a = file("dir/a.dll", Some("1.0"));
+        let b = file("dir/b.dll", Some("2.0"));
+        let c = file("other/c.dll", Some("1.0.0.0"));
+
+        assert!(same_group(&a, &b, SortKey::Path));
+        assert!(!same_group(&a, &c, SortKey::Path));
+        assert!(!same_group(&a, &b, SortKey::Version));
+        assert!(same_group(&a, &c, SortKey::Version));
+
+        // Extensions are grouped ignoring case
+        let d = file("other/d.DLL", None);
+        let e = file("dir/e.exe", Some("1.0"));
+        assert!(same_group(&a, &d, SortKey::Extension));
+        assert!(!same_group(&a, &e, SortKey::Extension));
+    }
+
+    #[test]
+    fn test_format_regression() {
+        let regression = VersionRegression {
+            earlier: FileInfo {
+                path: PathBuf::from("a/core.dll"),
+                version: Some("2.0.0.0".parse().unwrap()),
+                ..Default::default()
+            },
+            later: FileInfo {
+                path: PathBuf::from("b/core.dll"),
+                version: Some("1.0.0.0".parse().unwrap()),
+                ..Default::default()
+            },
+        };
+        assert_eq!(
+            format_regression(&regression),
+            "b/core.dll (1.0.0.0) < a/core.dll (2.0.0.0)"
+        );
+    }
+
+    #[test]
+    fn test_parse_field_separator() {
+        assert_eq!(parse_field_separator("\\t"), "\t");
+        assert_eq!(parse_field_separator(","), ",");
+        assert_eq!(parse_field_separator(" :: "), " :: ");
+    }
+
+    #[test]
+    fn test_write_to_file_without_version() {
+        let files = vec![
+            FileInfo {
+                path: PathBuf::from("file1.txt"),
+                version: None,
+                ..Default::default()
+            },
+            FileInfo {
+                path: PathBuf::from("file2.txt"),
+                version: None,
+                ..Default::default()
+            },
+        ];
+
+        let temp_file = std::env::temp_dir().join("flist_test_output.txt");
+        let result = write_to_file(&files, &temp_file, false, OutputFormat::Text);
+        assert!(result.is_ok());
+
+        let content = fs::read_to_string(&temp_file).unwrap();
+        assert!(content.contains("file1.txt"));
+        assert!(content.contains("file2.txt"));
+
+        fs::remove_file(&temp_file).unwrap();
+    }
+
+    #[test]
+    fn test_write_to_file_with_version() {
+        let files = vec![
+            FileInfo {
+                path: PathBuf::from("file1.dll"),
+                version: Some("1.0.0.0".parse::<FileVersion>().unwrap()),
+                ..Default::default()
+            },
+            FileInfo {
+                path: PathBuf::from("file2.dll"),
+                version: Some("2.0.0.0".parse::<FileVersion>().unwrap()),
+                ..Default::default()
+            },
+        ];
+
+        let temp_file = std::env::temp_dir().join("flist_test_output_version.txt");
+        let result = write_to_file(&files, &temp_file, true, OutputFormat::Text);
+        assert!(result.is_ok());
+
+        let content = fs::read_to_string(&temp_file).unwrap();
+        assert!(content.contains("1.0.0.0"));
         assert!(content.contains("2.0.0.0"));
         assert!(content.contains("file1.dll"));
         assert!(content.contains("file2.dll"));
@@ -163,21 +3859,40 @@ mod tests {
         fs::remove_file(&temp_file).unwrap();
     }
 
+    #[test]
+    fn test_write_to_file_with_streams() {
+        let files = vec![FileInfo {
+            path: PathBuf::from("setup.exe"),
+            streams: vec!["Zone.Identifier".to_string()],
+            ..Default::default()
+        }];
+
+        let temp_file = std::env::temp_dir().join("flist_test_output_streams.txt");
+        write_to_file(&files, &temp_file, false, OutputFormat::Text).unwrap();
+
+        let content = fs::read_to_string(&temp_file).unwrap();
+        assert_eq!(content, "setup.exe\nsetup.exe:Zone.Identifier\n");
+
+        fs::remove_file(&temp_file).unwrap();
+    }
+
     #[test]
     fn test_write_to_file_with_mixed_versions() {
         let files = vec![
             FileInfo {
                 path: PathBuf::from("file1.dll"),
                 version: Some("1.0.0.0".parse::<FileVersion>().unwrap()),
+                ..Default::default()
             },
             FileInfo {
                 path: PathBuf::from("file2.dll"),
                 version: None, // No version
+                ..Default::default()
             },
         ];
 
         let temp_file = std::env::temp_dir().join("flist_test_output_mixed.txt");
-        let result = write_to_file(&files, &temp_file, true);
+        let result = write_to_file(&files, &temp_file, true, OutputFormat::Text);
         assert!(result.is_ok());
 
         let content = fs::read_to_string(&temp_file).unwrap();
@@ -0,0 +1,242 @@
+//! Periodic progress reporting for long scans.
+//!
+//! This module provides [`ScanProgress`], counters updated by the pipeline as it
+//! finds and reads files, and [`Heartbeat`], a timer thread that prints these
+//! counters to stderr at a fixed interval. A plain line per interval suits CI
+//! logs and other non-interactive output, where a spinner is useless. On a
+//! terminal, [`ProgressBar`] shows the same counters on a single updating line.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// How often [`ProgressBar`] refreshes its counters.
+const BAR_REFRESH: Duration = Duration::from_millis(100);
+
+/// Counters describing how far a scan has progressed.
+///
+/// # Examples
+///
+/// ```
+/// use flist::progress::ScanProgress;
+///
+/// let progress = ScanProgress::default();
+/// progress.add_matched(2);
+/// progress.add_processed(true);
+/// assert_eq!(progress.message(), "processed 1 of 2 files (1 versions read)...");
+/// ```
+#[derive(Debug, Default)]
+pub struct ScanProgress {
+    seen: AtomicUsize,
+    matched: AtomicUsize,
+    processed: AtomicUsize,
+    versions: AtomicUsize,
+}
+
+impl ScanProgress {
+    /// Records an entry visited by the walk, whether or not it matches.
+    pub fn add_seen(&self) {
+        self.seen.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records files found by the search.
+    pub fn add_matched(&self, count: usize) {
+        self.matched.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Records a file whose information was collected.
+    ///
+    /// # Arguments
+    ///
+    /// * `version_read` - Whether version information was found in the file
+    pub fn add_processed(&self, version_read: bool) {
+        self.processed.fetch_add(1, Ordering::Relaxed);
+        if version_read {
+            self.versions.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns the number of entries visited by the walk.
+    pub fn seen(&self) -> usize {
+        self.seen.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of files found by the search.
+    pub fn matched(&self) -> usize {
+        self.matched.load(Ordering::Relaxed)
+    }
+
+    /// Formats the counters as a single progress line.
+    pub fn message(&self) -> String {
+        format!(
+            "processed {} of {} files ({} versions read)...",
+            self.processed.load(Ordering::Relaxed),
+            self.matched.load(Ordering::Relaxed),
+            self.versions.load(Ordering::Relaxed)
+        )
+    }
+}
+
+/// A thread printing [`ScanProgress::message`] to stderr at a fixed interval.
+///
+/// The thread stops when the heartbeat is dropped.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use flist::progress::{Heartbeat, ScanProgress};
+///
+/// let progress = Arc::new(ScanProgress::default());
+/// let heartbeat = Heartbeat::start(Duration::from_secs(10), Arc::clone(&progress));
+/// // ... scan, updating `progress` ...
+/// drop(heartbeat);
+/// ```
+#[derive(Debug)]
+pub struct Heartbeat {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Heartbeat {
+    /// Starts printing progress every `interval`.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - Time between two progress lines
+    /// * `progress` - The counters to print
+    pub fn start(interval: Duration, progress: Arc<ScanProgress>) -> Self {
+        Self::start_with(interval, move || eprintln!("{}", progress.message()))
+    }
+
+    /// Calls `beat` every `interval` until the heartbeat is dropped.
+    fn start_with(interval: Duration, mut beat: impl FnMut() + Send + 'static) -> Self {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let thread = std::thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                beat();
+            }
+        });
+
+        Self {
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for Heartbeat {
+    fn drop(&mut self) {
+        // Closing the channel wakes the thread immediately
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// A spinner on stderr showing [`ScanProgress::message`] while a scan runs.
+///
+/// The line is cleared when the progress bar is dropped, so results printed
+/// afterwards start on a clean line. Output printed while the scan runs should
+/// go through [`ProgressBar::suspend`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use flist::progress::{ProgressBar, ScanProgress};
+///
+/// let progress = Arc::new(ScanProgress::default());
+/// let bar = ProgressBar::start(Arc::clone(&progress));
+/// // ... scan, updating `progress` ...
+/// drop(bar);
+/// ```
+#[derive(Debug)]
+pub struct ProgressBar {
+    bar: indicatif::ProgressBar,
+    updates: Option<Heartbeat>,
+}
+
+impl ProgressBar {
+    /// Starts showing the counters of `progress` on stderr.
+    ///
+    /// # Arguments
+    ///
+    /// * `progress` - The counters to show
+    pub fn start(progress: Arc<ScanProgress>) -> Self {
+        let bar = indicatif::ProgressBar::new_spinner();
+        bar.enable_steady_tick(BAR_REFRESH);
+        let updated = bar.clone();
+        let updates =
+            Heartbeat::start_with(BAR_REFRESH, move || updated.set_message(progress.message()));
+
+        Self {
+            bar,
+            updates: Some(updates),
+        }
+    }
+
+    /// Hides the progress bar while `print` writes to the console.
+    pub fn suspend<R>(&self, print: impl FnOnce() -> R) -> R {
+        self.bar.suspend(print)
+    }
+}
+
+impl Drop for ProgressBar {
+    fn drop(&mut self) {
+        // Stop updating before clearing, so the line is not drawn again
+        drop(self.updates.take());
+        self.bar.finish_and_clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn test_progress_message() {
+        let progress = ScanProgress::default();
+        assert_eq!(
+            progress.message(),
+            "processed 0 of 0 files (0 versions read)..."
+        );
+
+        progress.add_matched(12000);
+        for i in 0..10 {
+            progress.add_processed(i % 2 == 0);
+        }
+        assert_eq!(
+            progress.message(),
+            "processed 10 of 12000 files (5 versions read)..."
+        );
+    }
+
+    #[test]
+    fn test_heartbeat_beats_until_dropped() {
+        let beats = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&beats);
+        let heartbeat = Heartbeat::start_with(Duration::from_millis(10), move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while beats.load(Ordering::SeqCst) < 2 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(beats.load(Ordering::SeqCst) >= 2);
+
+        // Dropping stops the thread without waiting for the next interval
+        let start = Instant::now();
+        drop(heartbeat);
+        let after_drop = beats.load(Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(beats.load(Ordering::SeqCst), after_drop);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
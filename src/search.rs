@@ -0,0 +1,404 @@
+//! A single entry point for embedding flist as a library.
+//!
+//! This module provides [`FlistBuilder`], which collects the options of a
+//! search and runs enumeration, collection, filtering and sorting in the same
+//! order as the command-line tool. New options are added as setters, so
+//! existing call sites keep compiling. [`FlistBuilder::run_with_stats`] also
+//! returns [`Stats`] on how many entries were visited and where the time went.
+
+use crate::file_lister::{
+    self, CollectOptions, EnumerateOptions, FileInfo, PathOrder, SortDirection, SortKey,
+    VersionField, WalkErrors,
+};
+use crate::file_version::{FileVersion, VersionPattern};
+use crate::progress::ScanProgress;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Counts and durations of a search, for tuning scans of large trees.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use flist::search::Stats;
+///
+/// let stats = Stats { seen: 120, matched: 12, filtered: 2, ..Default::default() };
+/// assert_eq!(stats.to_string(), "120 entries seen, 12 matched, 2 filtered out, 0 errors");
+/// assert_eq!(stats.phases()[0], ("enumerate", Duration::ZERO));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Entries visited by the walk, files and directories, matching or not
+    pub seen: usize,
+    /// Files matching the patterns
+    pub matched: usize,
+    /// Matching files removed by the filters
+    pub filtered: usize,
+    /// Entries that could not be read, such as directories without permission
+    pub errors: usize,
+    /// Time spent walking the directory tree
+    pub enumerate_time: Duration,
+    /// Time spent reading versions and other information from the files
+    pub read_time: Duration,
+    /// Time spent filtering and sorting the files
+    pub filter_time: Duration,
+}
+
+impl Stats {
+    /// Returns the name and duration of each phase, in the order they ran.
+    pub fn phases(&self) -> [(&'static str, Duration); 3] {
+        [
+            ("enumerate", self.enumerate_time),
+            ("read", self.read_time),
+            ("filter and sort", self.filter_time),
+        ]
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} entries seen, {} matched, {} filtered out, {} errors",
+            self.seen, self.matched, self.filtered, self.errors
+        )
+    }
+}
+
+/// Options of a search, run with [`FlistBuilder::run`].
+///
+/// Without setters, every file below the directory is listed, sorted by path,
+/// without version information.
+///
+/// # Examples
+///
+/// ```no_run
+/// use flist::search::FlistBuilder;
+///
+/// let files = FlistBuilder::new("C:\\Program Files")
+///     .pattern("*.dll")
+///     .exclude("Temp")
+///     .min_version("2.0".parse().unwrap())
+///     .run()
+///     .unwrap();
+/// for file in files {
+///     println!("{} {}", file.version.unwrap(), file.path.display());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FlistBuilder {
+    directory: PathBuf,
+    patterns: Vec<String>,
+    exclude: Vec<String>,
+    max_depth: Option<usize>,
+    ignore_case: bool,
+    include_version: bool,
+    version_field: VersionField,
+    min_version: Option<FileVersion>,
+    max_version: Option<FileVersion>,
+    exclude_versions: Vec<VersionPattern>,
+    sort_keys: Vec<SortKey>,
+    direction: SortDirection,
+    order: PathOrder,
+    unordered: bool,
+}
+
+impl FlistBuilder {
+    /// Starts a search of `directory` and its subdirectories.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            patterns: Vec::new(),
+            exclude: Vec::new(),
+            max_depth: None,
+            ignore_case: false,
+            include_version: false,
+            version_field: VersionField::File,
+            min_version: None,
+            max_version: None,
+            exclude_versions: Vec::new(),
+            sort_keys: Vec::new(),
+            direction: SortDirection::Ascending,
+            order: PathOrder::Lexicographic,
+            unordered: false,
+        }
+    }
+
+    /// Adds a glob pattern that file names are matched against.
+    ///
+    /// A file is listed if it matches any pattern. Without patterns, all files
+    /// are listed.
+    pub fn pattern(mut self, pattern: &str) -> Self {
+        self.patterns.push(pattern.to_string());
+        self
+    }
+
+    /// Adds a glob pattern of names to skip; matching directories are not searched.
+    pub fn exclude(mut self, pattern: &str) -> Self {
+        self.exclude.push(pattern.to_string());
+        self
+    }
+
+    /// Descends at most `depth` directory levels, 1 for only the directory itself.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Ignores letter case when matching patterns and excludes.
+    pub fn ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    /// Reads the file version of each file, see [`FileInfo::version`].
+    ///
+    /// Version filters and sorting by version read the version they need even
+    /// without this.
+    pub fn include_version(mut self, include_version: bool) -> Self {
+        self.include_version = include_version;
+        self
+    }
+
+    /// Selects the version the version filters use, the file version by default.
+    pub fn version_field(mut self, field: VersionField) -> Self {
+        self.version_field = field;
+        self
+    }
+
+    /// Keeps only files with at least this version.
+    pub fn min_version(mut self, version: FileVersion) -> Self {
+        self.min_version = Some(version);
+        self
+    }
+
+    /// Keeps only files with at most this version.
+    pub fn max_version(mut self, version: FileVersion) -> Self {
+        self.max_version = Some(version);
+        self
+    }
+
+    /// Drops files whose version matches `pattern`.
+    pub fn exclude_version(mut self, pattern: VersionPattern) -> Self {
+        self.exclude_versions.push(pattern);
+        self
+    }
+
+    /// Sorts by several keys, most significant first, instead of by path.
+    pub fn sort_by(mut self, keys: &[SortKey]) -> Self {
+        self.sort_keys = keys.to_vec();
+        self
+    }
+
+    /// Sets the direction of the sort, ascending by default.
+    pub fn sort_direction(mut self, direction: SortDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Sets how paths and names are compared, character by character by default.
+    pub fn path_order(mut self, order: PathOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Keeps files in the order the filesystem returns them instead of sorting.
+    pub fn unordered(mut self, unordered: bool) -> Self {
+        self.unordered = unordered;
+        self
+    }
+
+    /// Runs the search.
+    ///
+    /// Files are enumerated, their information is collected, then the version
+    /// filters are applied and the results are sorted.
+    ///
+    /// # Returns
+    ///
+    /// The matching files, or an error if a pattern is invalid.
+    pub fn run(&self) -> Result<Vec<FileInfo>, anyhow::Error> {
+        self.run_with_stats().map(|(files, _)| files)
+    }
+
+    /// Runs the search like [`FlistBuilder::run`], also counting entries and
+    /// timing each phase.
+    ///
+    /// # Returns
+    ///
+    /// The matching files and the [`Stats`] of the search, or an error if a
+    /// pattern is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use flist::search::FlistBuilder;
+    ///
+    /// let (files, stats) = FlistBuilder::new("D:\\").pattern("*.dll").run_with_stats().unwrap();
+    /// println!("{} files, {}", files.len(), stats);
+    /// for (phase, duration) in stats.phases() {
+    ///     println!("{}: {:?}", phase, duration);
+    /// }
+    /// ```
+    pub fn run_with_stats(&self) -> Result<(Vec<FileInfo>, Stats), anyhow::Error> {
+        let progress = Arc::new(ScanProgress::default());
+        let errors = Arc::new(WalkErrors::default());
+        let patterns: Vec<&str> = if self.patterns.is_empty() {
+            vec!["*"]
+        } else {
+            self.patterns.iter().map(String::as_str).collect()
+        };
+        let enumerate_options = EnumerateOptions {
+            max_depth: self.max_depth,
+            exclude: self.exclude.clone(),
+            ignore_case: self.ignore_case,
+            progress: Some(Arc::clone(&progress)),
+            errors: Some(Arc::clone(&errors)),
+            ..Default::default()
+        };
+        let start = Instant::now();
+        let files = file_lister::enumerate_files_with_options(
+            &self.directory,
+            &patterns,
+            &enumerate_options,
+        )?;
+        let enumerated = Instant::now();
+
+        let version_filters = self.min_version.is_some()
+            || self.max_version.is_some()
+            || !self.exclude_versions.is_empty();
+        let collect_options = CollectOptions {
+            include_version: self.include_version
+                || self.sort_keys.contains(&SortKey::Version)
+                || (version_filters && self.version_field == VersionField::File),
+            include_product_version: version_filters && self.version_field == VersionField::Product,
+            ..Default::default()
+        };
+        let mut files = file_lister::collect_file_info_with_options(files, &collect_options);
+        let read = Instant::now();
+        let collected = files.len();
+
+        if self.min_version.is_some() || self.max_version.is_some() {
+            files = file_lister::filter_by_version_of(
+                files,
+                self.min_version,
+                self.max_version,
+                self.version_field,
+            );
+        }
+        if !self.exclude_versions.is_empty() {
+            files = file_lister::filter_excluded_versions_of(
+                files,
+                &self.exclude_versions,
+                self.version_field,
+            );
+        }
+
+        if !self.unordered {
+            let keys = if self.sort_keys.is_empty() {
+                &[SortKey::Path][..]
+            } else {
+                &self.sort_keys
+            };
+            files = file_lister::sort_by_keys_with_order(files, keys, self.direction, self.order);
+        }
+
+        let stats = Stats {
+            seen: progress.seen(),
+            matched: progress.matched(),
+            filtered: collected - files.len(),
+            errors: errors.len(),
+            enumerate_time: enumerated - start,
+            read_time: read - enumerated,
+            filter_time: read.elapsed(),
+        };
+        Ok((files, stats))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_builder_run() {
+        let temp_dir = std::env::temp_dir().join("flist_test_builder");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("sub").join("deep")).unwrap();
+        fs::create_dir_all(temp_dir.join("skip")).unwrap();
+        for file in [
+            "b.dll",
+            "a.DLL",
+            "readme.txt",
+            "sub/c.dll",
+            "sub/deep/d.dll",
+            "skip/e.dll",
+        ] {
+            fs::write(temp_dir.join(file), "").unwrap();
+        }
+        let paths =
+            |files: Vec<FileInfo>| -> Vec<PathBuf> { files.into_iter().map(|f| f.path).collect() };
+
+        // Everything, sorted by path
+        let all = FlistBuilder::new(&temp_dir).run().unwrap();
+        assert_eq!(all.len(), 6);
+        assert!(all.windows(2).all(|pair| pair[0].path < pair[1].path));
+
+        let files = FlistBuilder::new(&temp_dir)
+            .pattern("*.dll")
+            .ignore_case(true)
+            .exclude("skip")
+            .max_depth(2)
+            .sort_by(&[SortKey::Name])
+            .sort_direction(SortDirection::Descending)
+            .run()
+            .unwrap();
+        assert_eq!(
+            paths(files),
+            vec![
+                temp_dir.join("sub").join("c.dll"),
+                temp_dir.join("b.dll"),
+                temp_dir.join("a.DLL"),
+            ]
+        );
+
+        // Files without version information do not pass a version filter
+        let files = FlistBuilder::new(&temp_dir)
+            .pattern("*.dll")
+            .min_version("1.0".parse().unwrap())
+            .run()
+            .unwrap();
+        assert!(files.is_empty());
+
+        assert!(FlistBuilder::new(&temp_dir).pattern("[").run().is_err());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_run_with_stats() {
+        let temp_dir = std::env::temp_dir().join("flist_test_builder_stats");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("sub")).unwrap();
+        for file in ["a.dll", "b.txt", "sub/c.dll"] {
+            fs::write(temp_dir.join(file), "").unwrap();
+        }
+
+        let (files, stats) = FlistBuilder::new(&temp_dir)
+            .pattern("*.dll")
+            .min_version("1.0".parse().unwrap())
+            .run_with_stats()
+            .unwrap();
+        assert!(files.is_empty());
+        // Three files and the subdirectory were visited, both DLLs filtered out
+        assert_eq!(
+            (stats.seen, stats.matched, stats.filtered, stats.errors),
+            (4, 2, 2, 0)
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
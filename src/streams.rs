@@ -0,0 +1,158 @@
+//! Alternate data stream enumeration on Windows.
+//!
+//! NTFS files can carry alternate data streams (ADS) next to their main
+//! contents, such as the `Zone.Identifier` stream that marks files downloaded
+//! from the internet. This module lists the names of these streams. Other
+//! platforms have no alternate data streams, so nothing is reported there.
+
+use std::path::Path;
+
+/// Lists the alternate data streams of a file.
+///
+/// The default (unnamed) data stream is not included. Stream names are returned
+/// without the `:` prefix and `:$DATA` type suffix, e.g. `Zone.Identifier`.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file to inspect
+///
+/// # Returns
+///
+/// The names of all alternate data streams, or an error if the file cannot be
+/// queried. On platforms other than Windows the list is always empty.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use flist::streams::alternate_streams;
+///
+/// for stream in alternate_streams(Path::new("setup.exe")).unwrap() {
+///     println!("setup.exe:{}", stream);
+/// }
+/// ```
+#[cfg(windows)]
+pub fn alternate_streams(path: &Path) -> Result<Vec<String>, anyhow::Error> {
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::shared::minwindef::LPVOID;
+    use winapi::shared::winerror::ERROR_HANDLE_EOF;
+    use winapi::um::errhandlingapi::GetLastError;
+    use winapi::um::fileapi::{
+        FindClose, FindFirstStreamW, FindNextStreamW, FindStreamInfoStandard,
+    };
+    use winapi::um::handleapi::INVALID_HANDLE_VALUE;
+
+    // WIN32_FIND_STREAM_DATA, not provided by winapi
+    #[repr(C)]
+    struct FindStreamData {
+        stream_size: i64,
+        stream_name: [u16; 260 + 36],
+    }
+
+    let wide_path: Vec<u16> = path
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    let mut data = FindStreamData {
+        stream_size: 0,
+        stream_name: [0; 260 + 36],
+    };
+
+    let handle = unsafe {
+        FindFirstStreamW(
+            wide_path.as_ptr(),
+            FindStreamInfoStandard,
+            &mut data as *mut FindStreamData as LPVOID,
+            0,
+        )
+    };
+    if handle == INVALID_HANDLE_VALUE {
+        let error = unsafe { GetLastError() };
+        if error == ERROR_HANDLE_EOF {
+            // No streams at all, e.g. on file systems without stream support
+            return Ok(Vec::new());
+        }
+        return Err(std::io::Error::from_raw_os_error(error as i32).into());
+    }
+
+    let mut streams = Vec::new();
+    loop {
+        let len = data
+            .stream_name
+            .iter()
+            .position(|&c| c == 0)
+            .unwrap_or(data.stream_name.len());
+        let raw_name = String::from_utf16_lossy(&data.stream_name[..len]);
+        if let Some(name) = stream_name(&raw_name) {
+            streams.push(name.to_string());
+        }
+
+        if unsafe { FindNextStreamW(handle, &mut data as *mut FindStreamData as LPVOID) } == 0 {
+            break;
+        }
+    }
+    unsafe { FindClose(handle) };
+
+    Ok(streams)
+}
+
+/// Lists the alternate data streams of a file (always empty on this platform).
+#[cfg(not(windows))]
+pub fn alternate_streams(_path: &Path) -> Result<Vec<String>, anyhow::Error> {
+    Ok(Vec::new())
+}
+
+/// Returns whether alternate data streams can be listed on this platform.
+pub fn is_supported() -> bool {
+    cfg!(windows)
+}
+
+/// Extracts the stream name from a raw name like `:Zone.Identifier:$DATA`.
+///
+/// Returns `None` for the default stream `::$DATA`.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn stream_name(raw_name: &str) -> Option<&str> {
+    let name = raw_name.strip_prefix(':').unwrap_or(raw_name);
+    let name = name.strip_suffix(":$DATA").unwrap_or(name);
+    if name.is_empty() { None } else { Some(name) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_name() {
+        assert_eq!(stream_name("::$DATA"), None);
+        assert_eq!(
+            stream_name(":Zone.Identifier:$DATA"),
+            Some("Zone.Identifier")
+        );
+        assert_eq!(stream_name(":custom"), Some("custom"));
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_alternate_streams() {
+        let temp_file = std::env::temp_dir().join("flist_test_ads.txt");
+        std::fs::write(&temp_file, "main contents").unwrap();
+
+        let mut stream_path = temp_file.clone().into_os_string();
+        stream_path.push(":Zone.Identifier");
+        if std::fs::write(&stream_path, "[ZoneTransfer]\r\nZoneId=3\r\n").is_ok() {
+            let streams = alternate_streams(&temp_file).unwrap();
+            assert_eq!(streams, vec!["Zone.Identifier".to_string()]);
+        }
+
+        std::fs::remove_file(&temp_file).unwrap();
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_alternate_streams_unsupported() {
+        assert!(!is_supported());
+        let streams = alternate_streams(Path::new("nonexistent.dll")).unwrap();
+        assert!(streams.is_empty());
+    }
+}
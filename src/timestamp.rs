@@ -0,0 +1,262 @@
+//! Parsing and formatting of points in time for the time filters.
+//!
+//! This module provides [`parse_time`] for the arguments of time filters such as
+//! `--created-after`, accepting ISO 8601 dates and times as well as ages relative
+//! to now, and [`format_time`] for displaying file times. All times are UTC.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Parses a point in time.
+///
+/// Accepted forms:
+///
+/// * `2024-01-31` - midnight at the start of the day
+/// * `2024-01-31T12:30` or `2024-01-31 12:30:45`, optionally followed by `Z`
+/// * `30s`, `15m`, `12h`, `7d` or `2w` - that long before now
+///
+/// Dates and times are interpreted as UTC.
+///
+/// # Arguments
+///
+/// * `spec` - The time to parse
+///
+/// # Returns
+///
+/// The point in time, or an error describing the expected formats.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use flist::timestamp::parse_time;
+///
+/// let time = parse_time("1970-01-02").unwrap();
+/// assert_eq!(time, UNIX_EPOCH + Duration::from_secs(86400));
+/// assert!(parse_time("7d").is_ok());
+/// ```
+pub fn parse_time(spec: &str) -> Result<SystemTime, anyhow::Error> {
+    parse_time_at(spec, SystemTime::now())
+}
+
+/// Parses a point in time like [`parse_time`], with relative ages counted back
+/// from `now`.
+///
+/// # Arguments
+///
+/// * `spec` - The time to parse
+/// * `now` - The reference point for relative ages
+///
+/// # Returns
+///
+/// The point in time, or an error describing the expected formats.
+pub fn parse_time_at(spec: &str, now: SystemTime) -> Result<SystemTime, anyhow::Error> {
+    let spec = spec.trim();
+    let parsed = match parse_age(spec) {
+        Some(age) => now.checked_sub(age),
+        None => parse_date_time(spec),
+    };
+
+    parsed.ok_or_else(|| {
+        anyhow::anyhow!(
+            "expected YYYY-MM-DD, YYYY-MM-DDTHH:MM[:SS] or an age such as 12h, 7d or 2w"
+        )
+    })
+}
+
+/// Formats a point in time as an ISO 8601 UTC timestamp, `2024-01-31T12:30:45Z`.
+///
+/// # Arguments
+///
+/// * `time` - The time to format
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use flist::timestamp::format_time;
+///
+/// let time = UNIX_EPOCH + Duration::from_secs(86400 + 3661);
+/// assert_eq!(format_time(time), "1970-01-02T01:01:01Z");
+/// ```
+pub fn format_time(time: SystemTime) -> String {
+    let seconds = match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_secs() as i64,
+        Err(before) => -(before.duration().as_secs_f64().ceil() as i64),
+    };
+    let (year, month, day) = civil_from_days(seconds.div_euclid(SECONDS_PER_DAY));
+    let second_of_day = seconds.rem_euclid(SECONDS_PER_DAY);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        second_of_day / 3600,
+        second_of_day / 60 % 60,
+        second_of_day % 60
+    )
+}
+
+/// Parses an age such as `7d` into a duration.
+fn parse_age(spec: &str) -> Option<Duration> {
+    let unit = spec.chars().last()?;
+    let seconds_per_unit = match unit.to_ascii_lowercase() {
+        's' => 1,
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => SECONDS_PER_DAY as u64,
+        'w' => 7 * SECONDS_PER_DAY as u64,
+        _ => return None,
+    };
+    let count: u64 = spec[..spec.len() - unit.len_utf8()].parse().ok()?;
+
+    Some(Duration::from_secs(count.checked_mul(seconds_per_unit)?))
+}
+
+/// Parses `YYYY-MM-DD` with an optional `THH:MM[:SS][Z]` time.
+fn parse_date_time(spec: &str) -> Option<SystemTime> {
+    let spec = spec.strip_suffix(['Z', 'z']).unwrap_or(spec);
+    let (date, time) = match spec.split_once(['T', 't', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (spec, None),
+    };
+
+    let numbers = |text: &str, separator: char| -> Option<Vec<u32>> {
+        text.split(separator)
+            .map(|part| part.parse::<u32>().ok())
+            .collect()
+    };
+
+    let [year, month, day] = <[u32; 3]>::try_from(numbers(date, '-')?).ok()?;
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year as i64, month) {
+        return None;
+    }
+
+    let (hour, minute, second) = match time {
+        None => (0, 0, 0),
+        Some(time) => match *numbers(time, ':')?.as_slice() {
+            [hour, minute] => (hour, minute, 0),
+            [hour, minute, second] => (hour, minute, second),
+            _ => return None,
+        },
+    };
+    if hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+
+    let seconds = days_from_civil(year as i64, month, day) * SECONDS_PER_DAY
+        + (hour * 3600 + minute * 60 + second) as i64;
+    match u64::try_from(seconds) {
+        Ok(seconds) => UNIX_EPOCH.checked_add(Duration::from_secs(seconds)),
+        Err(_) => UNIX_EPOCH.checked_sub(Duration::from_secs(seconds.unsigned_abs())),
+    }
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    // Algorithm from Howard Hinnant's "chrono-Compatible Low-Level Date Algorithms"
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146097 + day_of_era - 719468
+}
+
+/// Date of a day counted from 1970-01-01, the inverse of [`days_from_civil`].
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn test_parse_dates() {
+        assert_eq!(parse_time("1970-01-01").unwrap(), UNIX_EPOCH);
+        assert_eq!(parse_time("2000-03-01").unwrap(), at(951868800));
+        assert_eq!(parse_time("2024-02-29").unwrap(), at(1709164800));
+        assert_eq!(parse_time("2024-02-29T12:30").unwrap(), at(1709209800));
+        assert_eq!(parse_time("2024-02-29 12:30:15Z").unwrap(), at(1709209815));
+        assert_eq!(
+            parse_time("1969-12-31").unwrap(),
+            UNIX_EPOCH - Duration::from_secs(86400)
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_dates() {
+        for spec in [
+            "",
+            "2024",
+            "2024-13-01",
+            "2023-02-29",
+            "2024-01-32",
+            "2024-01-01T24:00",
+            "2024-01-01T12",
+            "yesterday",
+            "7x",
+        ] {
+            assert!(parse_time(spec).is_err(), "{}", spec);
+        }
+    }
+
+    #[test]
+    fn test_parse_ages() {
+        let now = at(1_000_000);
+        assert_eq!(parse_time_at("30s", now).unwrap(), at(1_000_000 - 30));
+        assert_eq!(parse_time_at("15m", now).unwrap(), at(1_000_000 - 900));
+        assert_eq!(parse_time_at("12h", now).unwrap(), at(1_000_000 - 43200));
+        assert_eq!(parse_time_at("7d", now).unwrap(), at(1_000_000 - 604800));
+        assert_eq!(parse_time_at("1W", now).unwrap(), at(1_000_000 - 604800));
+        assert!(parse_time_at("d", now).is_err());
+    }
+
+    #[test]
+    fn test_format_time_round_trip() {
+        for spec in [
+            "1970-01-01T00:00:00Z",
+            "1999-12-31T23:59:59Z",
+            "2024-02-29T12:30:15Z",
+            "1969-07-20T20:17:40Z",
+        ] {
+            let time = parse_time(spec).unwrap();
+            assert_eq!(format_time(time), spec);
+        }
+    }
+}
@@ -2,16 +2,106 @@
 //!
 //! This module provides functionality to extract version information from Windows
 //! executable files (.exe) and dynamic link libraries (.dll) by parsing the PE
-//! file format and reading the VS_FIXEDFILEINFO structure.
+//! file format and reading the VS_FIXEDFILEINFO structure, as well as strings
+//! such as the company and product name from the StringFileInfo table.
+//!
+//! Packed executables (UPX and similar) are supported on a best-effort basis.
+//! Packers usually keep the version resource readable, in which case it is
+//! returned as for any other file. When the resources cannot be reached because
+//! the packer moved them into compressed sections without raw file data, the
+//! readers return `Ok(None)` for the version instead of an error.
+//!
+//! With the `elf` feature (enabled by default), [`read_file_version`] also reads
+//! ELF shared objects. ELF has no version resource, so the version is taken
+//! from the numeric suffix of the `DT_SONAME` entry, see [`soname_version`].
+//! With the `macho` feature (enabled by default), Mach-O dylibs get the
+//! `current_version` of their `LC_ID_DYLIB` load command, see [`macho_version`].
+//! With the `msi` feature (enabled by default), Windows Installer packages get
+//! the `ProductVersion` property from the `Property` table of their database.
+//!
+//! Managed .NET assemblies additionally carry an `AssemblyVersion` in their CLI
+//! metadata, which may differ from the version resource. It is read separately
+//! by [`read_assembly_version`], so the file version stays the Win32 version.
+//!
+//! Parsing is separated from file access through the [`ByteSource`] trait. The
+//! path based readers map the file into memory, while the `*_from` variants
+//! accept any source of image bytes, such as a crafted buffer in a test.
 
-use crate::file_version::FileVersion;
+use crate::clr;
+use crate::file_version::{FileVersion, SemanticVersion};
+use clap::ValueEnum;
+use std::collections::HashMap;
+use std::fmt;
 use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-/// Reads file version information from a PE file (Windows executable or DLL).
+/// A source of PE image bytes.
 ///
-/// This function attempts to parse the file as a PE (Portable Executable) file
-/// and extract version information from the VS_FIXEDFILEINFO structure in the
-/// file's resources.
+/// # Examples
+///
+/// ```
+/// use flist::version_reader::{ByteSource, read_file_version_from};
+///
+/// let image: Vec<u8> = b"not a PE file".to_vec();
+/// assert_eq!(image.bytes().len(), 13);
+/// assert_eq!(read_file_version_from(&image).unwrap(), None);
+/// ```
+pub trait ByteSource {
+    /// Returns the complete image.
+    fn bytes(&self) -> &[u8];
+}
+
+impl ByteSource for pelite::FileMap {
+    fn bytes(&self) -> &[u8] {
+        self.as_ref()
+    }
+}
+
+impl ByteSource for [u8] {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+impl ByteSource for Vec<u8> {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+/// All version related information read from a PE file in a single pass.
+///
+/// Returned by [`read_all_version_fields`]. Fields that come from the version
+/// resource are empty when the file has no version resource, while header fields
+/// are always available for a valid PE file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PeVersionInfo {
+    /// File version from `VS_FIXEDFILEINFO.dwFileVersion`
+    pub file_version: Option<FileVersion>,
+    /// Product version from `VS_FIXEDFILEINFO.dwProductVersion`
+    pub product_version: Option<FileVersion>,
+    /// Strings of the first language block of the `StringFileInfo` table
+    /// (e.g. `CompanyName`, `ProductName`, `FileDescription`)
+    pub strings: HashMap<String, String>,
+    /// File flags (`dwFileFlags` masked by `dwFileFlagsMask`), e.g. debug or prerelease
+    pub flags: Option<u32>,
+    /// Raw `IMAGE_FILE_HEADER.TimeDateStamp` of the linker
+    pub link_time: u32,
+    /// Raw `IMAGE_FILE_HEADER.Machine` constant describing the target architecture
+    pub arch: u16,
+    /// `IMAGE_OPTIONAL_HEADER.Subsystem` (e.g. 2 = Windows GUI, 3 = Windows console)
+    pub subsystem: u16,
+    /// `AssemblyVersion` of a managed .NET assembly, see [`read_assembly_version`]
+    pub assembly_version: Option<FileVersion>,
+}
+
+/// Reads all version related fields from a PE file in a single pass.
+///
+/// The file is opened and parsed once, and the fixed version information, the
+/// version strings and the relevant header fields are extracted together. Use
+/// this instead of reading individual attributes when more than one is needed.
 ///
 /// # Arguments
 ///
@@ -19,252 +109,1276 @@ use std::path::Path;
 ///
 /// # Returns
 ///
-/// * `Ok(Some(FileVersion))` - Version information was successfully extracted
-/// * `Ok(None)` - File is not a PE file or has no version information
+/// * `Ok(Some(PeVersionInfo))` - The file is a PE file
+/// * `Ok(None)` - File is not a PE file or cannot be read
 /// * `Err(_)` - An error occurred while reading the file
 ///
 /// # Examples
 ///
 /// ```no_run
 /// use std::path::Path;
-/// use flist::version_reader::read_file_version;
+/// use flist::version_reader::read_all_version_fields;
 ///
-/// let version = read_file_version(Path::new("C:\\Windows\\System32\\kernel32.dll")).unwrap();
-/// if let Some(v) = version {
-///     println!("Version: {}", v);
+/// let info = read_all_version_fields(Path::new("C:\\Windows\\System32\\kernel32.dll")).unwrap();
+/// if let Some(info) = info {
+///     println!("File version: {:?}", info.file_version);
+///     println!("Company: {:?}", info.strings.get("CompanyName"));
 /// }
 /// ```
-#[cfg(windows)]
-pub fn read_file_version(path: &Path) -> Result<Option<FileVersion>, anyhow::Error> {
-    use pelite::pe32::PeFile as PeFile32;
-    use pelite::pe64::PeFile;
-    use pelite::FileMap;
+pub fn read_all_version_fields(path: &Path) -> Result<Option<PeVersionInfo>, anyhow::Error> {
+    match pelite::FileMap::open(path) {
+        Ok(file_map) => read_all_version_fields_from(&file_map),
+        Err(_) => Ok(None), // Not a valid file or can't read
+    }
+}
 
-    // Try to read the file
-    let file_map = match FileMap::open(path) {
-        Ok(map) => map,
-        Err(_) => return Ok(None), // Not a valid file or can't read
+/// Reads all version related fields from a PE image in a [`ByteSource`].
+///
+/// This is the parsing half of [`read_all_version_fields`].
+///
+/// # Arguments
+///
+/// * `source` - The image bytes to parse
+///
+/// # Returns
+///
+/// * `Ok(Some(PeVersionInfo))` - The bytes are a PE image
+/// * `Ok(None)` - The bytes are not a PE image
+/// * `Err(_)` - An error occurred while parsing the image
+pub fn read_all_version_fields_from<S: ByteSource + ?Sized>(
+    source: &S,
+) -> Result<Option<PeVersionInfo>, anyhow::Error> {
+    use pelite::{PeFile, Wrap};
+
+    let pe = match PeFile::from_bytes(source.bytes()) {
+        Ok(pe) => pe,
+        Err(_) => return Ok(None), // Not a PE file
+    };
+
+    let file_header = pe.file_header();
+    let subsystem = match pe.optional_header() {
+        Wrap::T32(header) => header.Subsystem,
+        Wrap::T64(header) => header.Subsystem,
     };
 
-    // Try as 64-bit PE first
-    if let Ok(pe) = PeFile::from_bytes(&file_map) {
-        return extract_version_from_pe64(pe);
+    let mut info = PeVersionInfo {
+        link_time: file_header.TimeDateStamp,
+        arch: file_header.Machine,
+        subsystem,
+        assembly_version: clr_metadata(pe).and_then(clr::assembly_version),
+        ..Default::default()
+    };
+
+    // Resources are optional, a PE without version resource still has headers
+    let version_info = match pe.resources().map(|res| res.version_info()) {
+        Ok(Ok(vi)) => vi,
+        _ => return Ok(Some(info)),
+    };
+
+    if let Some(fixed) = version_info.fixed() {
+        info.file_version = Some(vs_version_to_file_version(fixed.dwFileVersion));
+        info.product_version = Some(vs_version_to_file_version(fixed.dwProductVersion));
+        info.flags = Some(fixed.dwFileFlags & fixed.dwFileFlagsMask);
     }
 
-    // Try as 32-bit PE
-    if let Ok(pe) = PeFile32::from_bytes(&file_map) {
-        return extract_version_from_pe32(pe);
+    if let Some(&lang) = version_info.translation().first() {
+        version_info.strings(lang, |key, value| {
+            info.strings.insert(key.to_string(), value.to_string());
+        });
     }
 
-    // Not a PE file
-    Ok(None)
+    Ok(Some(info))
 }
 
-#[cfg(windows)]
-fn extract_version_from_pe64(
-    pe: pelite::pe64::PeFile,
-) -> Result<Option<FileVersion>, anyhow::Error> {
-    use pelite::pe64::Pe;
-    use pelite::resources::FindError;
+/// The well-known strings of a version resource's `StringFileInfo` table.
+///
+/// Returned by [`read_version_strings`]. Strings that are missing or empty in
+/// the table are `None`.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use flist::version_reader::{StringField, VersionStrings};
+///
+/// let table = HashMap::from([("CompanyName".to_string(), "Contoso Ltd.".to_string())]);
+/// let strings = VersionStrings::from_table(&table);
+/// assert_eq!(strings.get(StringField::Company), Some("Contoso Ltd."));
+/// assert_eq!(strings.product, None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VersionStrings {
+    /// `CompanyName`
+    pub company: Option<String>,
+    /// `ProductName`
+    pub product: Option<String>,
+    /// `FileDescription`
+    pub description: Option<String>,
+    /// `LegalCopyright`
+    pub copyright: Option<String>,
+    /// `ProductVersion` as written by the vendor, e.g. `1.2.3-beta`, see
+    /// [`VersionStrings::semantic_version`]
+    pub product_version: Option<String>,
+}
 
-    // Get resources
-    let resources = match pe.resources() {
-        Ok(res) => res,
-        Err(_) => return Ok(None), // No resources
-    };
+/// A string of [`VersionStrings`], e.g. to select columns for display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StringField {
+    /// `CompanyName`
+    Company,
+    /// `ProductName`
+    Product,
+    /// `FileDescription`
+    Description,
+    /// `LegalCopyright`
+    Copyright,
+}
 
-    // Try to find version info
-    let version_info = match resources.version_info() {
-        Ok(vi) => vi,
-        Err(FindError::NotFound) => return Ok(None), // No version info
-        Err(_) => return Ok(None),                   // Other error
-    };
+impl VersionStrings {
+    /// Picks the well-known strings from a table of version strings.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Keys and values of a `StringFileInfo` language block
+    pub fn from_table(table: &HashMap<String, String>) -> Self {
+        let get = |key: &str| {
+            table
+                .get(key)
+                .map(|value| value.trim_end_matches('\0').trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
 
-    // Get the fixed file info which contains the version
-    let fixed = match version_info.fixed() {
-        Some(f) => f,
-        None => return Ok(None),
-    };
+        Self {
+            company: get("CompanyName"),
+            product: get("ProductName"),
+            description: get("FileDescription"),
+            copyright: get("LegalCopyright"),
+            product_version: get("ProductVersion"),
+        }
+    }
 
-    // Extract version parts from dwFileVersion
-    // VS_VERSION is a u64 where (in little-endian):
-    // - Bits 0-15: minor (low word of MS dword)
-    // - Bits 16-31: major (high word of MS dword)
-    // - Bits 32-47: private (low word of LS dword)
-    // - Bits 48-63: build (high word of LS dword)
-    let file_ver_raw =
-        unsafe { std::mem::transmute::<pelite::image::VS_VERSION, u64>(fixed.dwFileVersion) };
-    let minor = (file_ver_raw & 0xFFFF) as u32;
-    let major = ((file_ver_raw >> 16) & 0xFFFF) as u32;
-    let private = ((file_ver_raw >> 32) & 0xFFFF) as u32;
-    let build = ((file_ver_raw >> 48) & 0xFFFF) as u32;
-
-    Ok(Some(FileVersion::new(
-        Some(major),
-        Some(minor),
-        Some(build),
-        Some(private),
-    )))
-}
-
-#[cfg(windows)]
-fn extract_version_from_pe32(
-    pe: pelite::pe32::PeFile,
-) -> Result<Option<FileVersion>, anyhow::Error> {
-    use pelite::pe32::Pe;
-    use pelite::resources::FindError;
+    /// Returns the string of the given field.
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - Which string to return
+    pub fn get(&self, field: StringField) -> Option<&str> {
+        match field {
+            StringField::Company => self.company.as_deref(),
+            StringField::Product => self.product.as_deref(),
+            StringField::Description => self.description.as_deref(),
+            StringField::Copyright => self.copyright.as_deref(),
+        }
+    }
 
-    // Get resources
-    let resources = match pe.resources() {
-        Ok(res) => res,
-        Err(_) => return Ok(None), // No resources
-    };
+    /// Parses the `ProductVersion` string as a [`SemanticVersion`].
+    ///
+    /// Unlike the numeric product version of the fixed file info, the string
+    /// keeps prerelease suffixes such as `-beta`. Strings that are not a
+    /// semantic version, e.g. four part versions or `10.0 (WinBuild)`, give
+    /// `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use flist::version_reader::VersionStrings;
+    ///
+    /// let table = HashMap::from([("ProductVersion".to_string(), "1.2.3-beta".to_string())]);
+    /// let version = VersionStrings::from_table(&table).semantic_version().unwrap();
+    /// assert_eq!(version.to_string(), "1.2.3-beta");
+    /// assert!(version.is_prerelease());
+    /// ```
+    pub fn semantic_version(&self) -> Option<SemanticVersion> {
+        self.product_version.as_deref()?.parse().ok()
+    }
+}
+
+/// Reads the well-known version strings from a PE file.
+///
+/// A version resource may hold strings in several languages. The block of
+/// `language` is used if the file has one, otherwise the first block.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file to read
+/// * `language` - Preferred language ID, e.g. `0x0409` for U.S. English
+///
+/// # Returns
+///
+/// * `Ok(Some(VersionStrings))` - The file has a version string table
+/// * `Ok(None)` - File is not a PE file, has no string table or cannot be read
+/// * `Err(_)` - An error occurred while reading the file
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use flist::version_reader::read_version_strings;
+///
+/// let strings = read_version_strings(Path::new("C:\\Windows\\notepad.exe"), None).unwrap();
+/// if let Some(strings) = strings {
+///     println!("Company: {:?}", strings.company);
+/// }
+/// ```
+pub fn read_version_strings(
+    path: &Path,
+    language: Option<u16>,
+) -> Result<Option<VersionStrings>, anyhow::Error> {
+    match pelite::FileMap::open(path) {
+        Ok(file_map) => read_version_strings_from(&file_map, language),
+        Err(_) => Ok(None), // Not a valid file or can't read
+    }
+}
+
+/// Reads the well-known version strings from a PE image in a [`ByteSource`].
+///
+/// This is the parsing half of [`read_version_strings`].
+///
+/// # Arguments
+///
+/// * `source` - The image bytes to parse
+/// * `language` - Preferred language ID, the first language block otherwise
+///
+/// # Returns
+///
+/// * `Ok(Some(VersionStrings))` - The image has a version string table
+/// * `Ok(None)` - The bytes are not a PE image or have no string table
+/// * `Err(_)` - An error occurred while parsing the image
+pub fn read_version_strings_from<S: ByteSource + ?Sized>(
+    source: &S,
+    language: Option<u16>,
+) -> Result<Option<VersionStrings>, anyhow::Error> {
+    use pelite::PeFile;
 
-    // Try to find version info
-    let version_info = match resources.version_info() {
-        Ok(vi) => vi,
-        Err(FindError::NotFound) => return Ok(None), // No version info
-        Err(_) => return Ok(None),                   // Other error
+    let pe = match PeFile::from_bytes(source.bytes()) {
+        Ok(pe) => pe,
+        Err(_) => return Ok(None), // Not a PE file
+    };
+    let version_info = match pe.resources().map(|res| res.version_info()) {
+        Ok(Ok(vi)) => vi,
+        _ => return Ok(None),
     };
 
-    // Get the fixed file info which contains the version
-    let fixed = match version_info.fixed() {
-        Some(f) => f,
-        None => return Ok(None),
+    let translations = version_info.translation();
+    let lang = language
+        .and_then(|id| translations.iter().find(|lang| lang.lang_id == id))
+        .or_else(|| translations.first());
+    let Some(&lang) = lang else {
+        return Ok(None);
     };
 
-    // Extract version parts from dwFileVersion
-    let file_ver_raw =
-        unsafe { std::mem::transmute::<pelite::image::VS_VERSION, u64>(fixed.dwFileVersion) };
-    let minor = (file_ver_raw & 0xFFFF) as u32;
-    let major = ((file_ver_raw >> 16) & 0xFFFF) as u32;
-    let private = ((file_ver_raw >> 32) & 0xFFFF) as u32;
-    let build = ((file_ver_raw >> 48) & 0xFFFF) as u32;
-
-    Ok(Some(FileVersion::new(
-        Some(major),
-        Some(minor),
-        Some(build),
-        Some(private),
-    )))
-}
-
-/// Read file version information from a PE file (cross-platform stub)
-/// On non-Windows platforms, this can still read PE files using pelite
-#[cfg(not(windows))]
-pub fn read_file_version(path: &Path) -> Result<Option<FileVersion>, anyhow::Error> {
-    use pelite::pe32::PeFile as PeFile32;
-    use pelite::pe64::PeFile;
-    use pelite::FileMap;
+    let mut table = HashMap::new();
+    version_info.strings(lang, |key, value| {
+        table.insert(key.to_string(), value.to_string());
+    });
+    Ok(Some(VersionStrings::from_table(&table)))
+}
 
-    // Try to read the file
-    let file_map = match FileMap::open(path) {
-        Ok(map) => map,
-        Err(_) => return Ok(None), // Not a valid file or can't read
+/// Reads the link time of a PE file from `IMAGE_FILE_HEADER.TimeDateStamp`.
+///
+/// The linker stores the time as seconds since 1970-01-01 UTC. Linkers set up
+/// for reproducible builds (e.g. MSVC with `/Brepro`) store zero or a hash of
+/// the image instead, so the result is not always the real build time: zero
+/// reads as 1970-01-01 and a hash as an arbitrary date.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file to read
+///
+/// # Returns
+///
+/// * `Ok(Some(SystemTime))` - The time stamp of the PE file
+/// * `Ok(None)` - File is not a PE file or cannot be read
+/// * `Err(_)` - An error occurred while reading the file
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use flist::timestamp::format_time;
+/// use flist::version_reader::read_pe_timestamp;
+///
+/// if let Some(time) = read_pe_timestamp(Path::new("app.exe")).unwrap() {
+///     println!("Linked at {}", format_time(time));
+/// }
+/// ```
+pub fn read_pe_timestamp(path: &Path) -> Result<Option<SystemTime>, anyhow::Error> {
+    match pelite::FileMap::open(path) {
+        Ok(file_map) => read_pe_timestamp_from(&file_map),
+        Err(_) => Ok(None), // Not a valid file or can't read
+    }
+}
+
+/// Reads the link time from a PE image in a [`ByteSource`].
+///
+/// This is the parsing half of [`read_pe_timestamp`].
+///
+/// # Arguments
+///
+/// * `source` - The image bytes to parse
+///
+/// # Returns
+///
+/// * `Ok(Some(SystemTime))` - The time stamp of the PE image
+/// * `Ok(None)` - The bytes are not a PE image
+/// * `Err(_)` - An error occurred while parsing the image
+pub fn read_pe_timestamp_from<S: ByteSource + ?Sized>(
+    source: &S,
+) -> Result<Option<SystemTime>, anyhow::Error> {
+    use pelite::PeFile;
+
+    let pe = match PeFile::from_bytes(source.bytes()) {
+        Ok(pe) => pe,
+        Err(_) => return Ok(None), // Not a PE file
     };
+    let seconds = pe.file_header().TimeDateStamp;
+    Ok(Some(UNIX_EPOCH + Duration::from_secs(seconds.into())))
+}
+
+/// Target architecture of a PE file, from `IMAGE_FILE_HEADER.Machine`.
+///
+/// Parsed from and displayed as `x86`, `x64`, `arm` or `arm64`. Other machine
+/// types keep their raw constant and are displayed in hex, e.g. `0x0200`.
+///
+/// # Examples
+///
+/// ```
+/// use flist::version_reader::PeArch;
+///
+/// assert_eq!(PeArch::from_machine(0x8664), PeArch::X64);
+/// assert_eq!(PeArch::from_machine(0x0200).to_string(), "0x0200");
+/// assert_eq!("ARM64".parse::<PeArch>().unwrap(), PeArch::Arm64);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PeArch {
+    /// `IMAGE_FILE_MACHINE_I386`
+    X86,
+    /// `IMAGE_FILE_MACHINE_AMD64`
+    X64,
+    /// `IMAGE_FILE_MACHINE_ARM`, `THUMB` and `ARMNT`
+    Arm,
+    /// `IMAGE_FILE_MACHINE_ARM64`
+    Arm64,
+    /// Any other machine type
+    Other(u16),
+}
 
-    // Try as 64-bit PE first
-    if let Ok(pe) = PeFile::from_bytes(&file_map) {
-        return extract_version_from_pe64_cross(pe);
+impl PeArch {
+    /// Returns the architecture of an `IMAGE_FILE_HEADER.Machine` constant.
+    pub fn from_machine(machine: u16) -> Self {
+        match machine {
+            0x014C => PeArch::X86,
+            0x8664 => PeArch::X64,
+            0x01C0 | 0x01C2 | 0x01C4 => PeArch::Arm,
+            0xAA64 => PeArch::Arm64,
+            other => PeArch::Other(other),
+        }
     }
+}
+
+impl FromStr for PeArch {
+    type Err = anyhow::Error;
 
-    // Try as 32-bit PE
-    if let Ok(pe) = PeFile32::from_bytes(&file_map) {
-        return extract_version_from_pe32_cross(pe);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "x86" => Ok(PeArch::X86),
+            "x64" => Ok(PeArch::X64),
+            "arm" => Ok(PeArch::Arm),
+            "arm64" => Ok(PeArch::Arm64),
+            _ => anyhow::bail!("expected x86, x64, arm or arm64"),
+        }
     }
+}
 
-    // Not a PE file
-    Ok(None)
+impl fmt::Display for PeArch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PeArch::X86 => f.write_str("x86"),
+            PeArch::X64 => f.write_str("x64"),
+            PeArch::Arm => f.write_str("arm"),
+            PeArch::Arm64 => f.write_str("arm64"),
+            PeArch::Other(machine) => write!(f, "0x{:04X}", machine),
+        }
+    }
 }
 
-#[cfg(not(windows))]
-fn extract_version_from_pe64_cross(
-    pe: pelite::pe64::PeFile,
-) -> Result<Option<FileVersion>, anyhow::Error> {
-    use pelite::pe64::Pe;
-    use pelite::resources::FindError;
+/// Reads the target architecture of a PE file from `IMAGE_FILE_HEADER.Machine`.
+///
+/// The machine constant is reported as stored, rather than derived from
+/// whether the optional header is PE32 or PE32+, which does not tell x64 and
+/// ARM64 apart.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file to read
+///
+/// # Returns
+///
+/// * `Ok(Some(PeArch))` - The architecture of the PE file
+/// * `Ok(None)` - File is not a PE file or cannot be read
+/// * `Err(_)` - An error occurred while reading the file
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use flist::version_reader::{read_pe_arch, PeArch};
+///
+/// if read_pe_arch(Path::new("app.dll")).unwrap() == Some(PeArch::X86) {
+///     println!("still 32-bit");
+/// }
+/// ```
+pub fn read_pe_arch(path: &Path) -> Result<Option<PeArch>, anyhow::Error> {
+    match pelite::FileMap::open(path) {
+        Ok(file_map) => read_pe_arch_from(&file_map),
+        Err(_) => Ok(None), // Not a valid file or can't read
+    }
+}
 
-    let resources = match pe.resources() {
-        Ok(res) => res,
-        Err(_) => return Ok(None),
-    };
+/// Reads the target architecture from a PE image in a [`ByteSource`].
+///
+/// This is the parsing half of [`read_pe_arch`].
+///
+/// # Arguments
+///
+/// * `source` - The image bytes to parse
+///
+/// # Returns
+///
+/// * `Ok(Some(PeArch))` - The architecture of the PE image
+/// * `Ok(None)` - The bytes are not a PE image
+/// * `Err(_)` - An error occurred while parsing the image
+pub fn read_pe_arch_from<S: ByteSource + ?Sized>(
+    source: &S,
+) -> Result<Option<PeArch>, anyhow::Error> {
+    use pelite::PeFile;
 
-    let version_info = match resources.version_info() {
-        Ok(vi) => vi,
-        Err(FindError::NotFound) => return Ok(None),
-        Err(_) => return Ok(None),
+    let pe = match PeFile::from_bytes(source.bytes()) {
+        Ok(pe) => pe,
+        Err(_) => return Ok(None), // Not a PE file
     };
+    Ok(Some(PeArch::from_machine(pe.file_header().Machine)))
+}
 
-    let fixed = match version_info.fixed() {
-        Some(f) => f,
-        None => return Ok(None),
-    };
+/// Checks whether a PE file carries an embedded signature.
+///
+/// Authenticode signatures are stored in the certificate table, which the
+/// security data directory points to. Only the presence of the table is
+/// checked: the signature is not validated, so a tampered or expired
+/// signature still counts as signed.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file to check
+///
+/// # Returns
+///
+/// * `Ok(Some(true))` - The PE file has a certificate table
+/// * `Ok(Some(false))` - The PE file has no certificate table
+/// * `Ok(None)` - File is not a PE file or cannot be read
+/// * `Err(_)` - An error occurred while reading the file
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use flist::version_reader::read_pe_signed;
+///
+/// if read_pe_signed(Path::new("app.exe")).unwrap() == Some(false) {
+///     println!("not signed");
+/// }
+/// ```
+pub fn read_pe_signed(path: &Path) -> Result<Option<bool>, anyhow::Error> {
+    match pelite::FileMap::open(path) {
+        Ok(file_map) => read_pe_signed_from(&file_map),
+        Err(_) => Ok(None), // Not a valid file or can't read
+    }
+}
+
+/// Checks whether a PE image in a [`ByteSource`] carries an embedded signature.
+///
+/// This is the parsing half of [`read_pe_signed`].
+///
+/// # Arguments
+///
+/// * `source` - The image bytes to parse
+///
+/// # Returns
+///
+/// * `Ok(Some(bool))` - Whether the PE image has a certificate table
+/// * `Ok(None)` - The bytes are not a PE image
+/// * `Err(_)` - An error occurred while parsing the image
+pub fn read_pe_signed_from<S: ByteSource + ?Sized>(
+    source: &S,
+) -> Result<Option<bool>, anyhow::Error> {
+    use pelite::PeFile;
+    use pelite::image::IMAGE_DIRECTORY_ENTRY_SECURITY;
 
-    let file_ver_raw =
-        unsafe { std::mem::transmute::<pelite::image::VS_VERSION, u64>(fixed.dwFileVersion) };
-    let minor = (file_ver_raw & 0xFFFF) as u32;
-    let major = ((file_ver_raw >> 16) & 0xFFFF) as u32;
-    let private = ((file_ver_raw >> 32) & 0xFFFF) as u32;
-    let build = ((file_ver_raw >> 48) & 0xFFFF) as u32;
+    let bytes = source.bytes();
+    let pe = match PeFile::from_bytes(bytes) {
+        Ok(pe) => pe,
+        Err(_) => return Ok(None), // Not a PE file
+    };
+    // Unlike the other directories, the security directory holds a file
+    // offset rather than an RVA. A table past the end of the file, as left
+    // behind by truncation, does not count.
+    let signed = pe
+        .data_directory()
+        .get(IMAGE_DIRECTORY_ENTRY_SECURITY)
+        .is_some_and(|directory| {
+            let end = directory.VirtualAddress as usize + directory.Size as usize;
+            directory.VirtualAddress != 0 && directory.Size != 0 && end <= bytes.len()
+        });
+    Ok(Some(signed))
+}
 
-    Ok(Some(FileVersion::new(
-        Some(major),
-        Some(minor),
-        Some(build),
-        Some(private),
-    )))
+/// Reads the `AssemblyVersion` of a managed .NET assembly.
+///
+/// Managed assemblies have a COM descriptor (CLI header) pointing to their
+/// metadata, whose `Assembly` table holds the version the runtime binds to. It
+/// can differ from the file version of the version resource, which
+/// [`read_file_version`] returns.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file to read
+///
+/// # Returns
+///
+/// * `Ok(Some(FileVersion))` - The assembly version
+/// * `Ok(None)` - File is not a managed assembly, or cannot be read
+/// * `Err(_)` - An error occurred while reading the file
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use flist::version_reader::{read_assembly_version, read_file_version};
+///
+/// let path = Path::new("Newtonsoft.Json.dll");
+/// let file_version = read_file_version(path).unwrap();
+/// let assembly_version = read_assembly_version(path).unwrap();
+/// println!("{:?} {:?}", file_version, assembly_version);
+/// ```
+pub fn read_assembly_version(path: &Path) -> Result<Option<FileVersion>, anyhow::Error> {
+    match pelite::FileMap::open(path) {
+        Ok(file_map) => read_assembly_version_from(&file_map),
+        Err(_) => Ok(None), // Not a valid file or can't read
+    }
 }
 
-#[cfg(not(windows))]
-fn extract_version_from_pe32_cross(
-    pe: pelite::pe32::PeFile,
+/// Reads the `AssemblyVersion` from a PE image in a [`ByteSource`].
+///
+/// This is the parsing half of [`read_assembly_version`].
+///
+/// # Arguments
+///
+/// * `source` - The image bytes to parse
+///
+/// # Returns
+///
+/// * `Ok(Some(FileVersion))` - The assembly version
+/// * `Ok(None)` - The bytes are not a managed assembly
+/// * `Err(_)` - An error occurred while parsing the image
+pub fn read_assembly_version_from<S: ByteSource + ?Sized>(
+    source: &S,
 ) -> Result<Option<FileVersion>, anyhow::Error> {
-    use pelite::pe32::Pe;
-    use pelite::resources::FindError;
+    use pelite::PeFile;
 
-    let resources = match pe.resources() {
-        Ok(res) => res,
-        Err(_) => return Ok(None),
+    let pe = match PeFile::from_bytes(source.bytes()) {
+        Ok(pe) => pe,
+        Err(_) => return Ok(None), // Not a PE file
     };
+    Ok(clr_metadata(pe).and_then(clr::assembly_version))
+}
 
-    let version_info = match resources.version_info() {
-        Ok(vi) => vi,
-        Err(FindError::NotFound) => return Ok(None),
-        Err(_) => return Ok(None),
-    };
+/// Returns the CLI metadata of a managed PE image, located through the COM
+/// descriptor directory, or `None` for native images.
+fn clr_metadata(pe: pelite::PeFile<'_>) -> Option<&[u8]> {
+    use pelite::image::IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR;
 
-    let fixed = match version_info.fixed() {
-        Some(f) => f,
-        None => return Ok(None),
-    };
+    let directory = pe
+        .data_directory()
+        .get(IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR)?;
+    if directory.VirtualAddress == 0 {
+        return None;
+    }
+    // IMAGE_COR20_HEADER: cb, runtime version, then the metadata directory
+    let header = pe.derva_slice::<u8>(directory.VirtualAddress, 16).ok()?;
+    let rva = u32::from_le_bytes(header[8..12].try_into().ok()?);
+    let size = u32::from_le_bytes(header[12..16].try_into().ok()?);
+    pe.derva_slice::<u8>(rva, size as usize).ok()
+}
 
-    let file_ver_raw =
-        unsafe { std::mem::transmute::<pelite::image::VS_VERSION, u64>(fixed.dwFileVersion) };
-    let minor = (file_ver_raw & 0xFFFF) as u32;
-    let major = ((file_ver_raw >> 16) & 0xFFFF) as u32;
-    let private = ((file_ver_raw >> 32) & 0xFFFF) as u32;
-    let build = ((file_ver_raw >> 48) & 0xFFFF) as u32;
+fn vs_version_to_file_version(version: pelite::image::VS_VERSION) -> FileVersion {
+    let raw = unsafe { std::mem::transmute::<pelite::image::VS_VERSION, u64>(version) };
+    FileVersion::from_vs_version_u64(raw)
+}
 
-    Ok(Some(FileVersion::new(
-        Some(major),
-        Some(minor),
-        Some(build),
-        Some(private),
-    )))
+/// Checksum stored in a PE optional header together with the computed value.
+///
+/// Returned by [`compute_pe_checksum`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeChecksum {
+    /// Value of `IMAGE_OPTIONAL_HEADER.CheckSum`
+    pub stored: u32,
+    /// Checksum computed over the file contents
+    pub computed: u32,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::path::PathBuf;
+impl PeChecksum {
+    /// Returns whether the stored checksum matches the computed one.
+    ///
+    /// Returns `None` if the stored checksum is `0`, which means the linker did
+    /// not set a checksum and there is nothing to verify.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flist::version_reader::PeChecksum;
+    ///
+    /// assert_eq!(PeChecksum { stored: 0x1234, computed: 0x1234 }.is_valid(), Some(true));
+    /// assert_eq!(PeChecksum { stored: 0x1234, computed: 0x4321 }.is_valid(), Some(false));
+    /// assert_eq!(PeChecksum { stored: 0, computed: 0x4321 }.is_valid(), None);
+    /// ```
+    pub fn is_valid(&self) -> Option<bool> {
+        if self.stored == 0 {
+            None
+        } else {
+            Some(self.stored == self.computed)
+        }
+    }
+}
 
-    #[test]
-    #[cfg(windows)]
-    fn test_read_version_from_system_dll() {
-        // Test with a known Windows system DLL
-        let system_root = std::env::var("SystemRoot").unwrap_or_else(|_| "C:\\Windows".to_string());
-        let dll_path = PathBuf::from(system_root)
-            .join("System32")
+/// Computes the checksum of a PE image and reads the stored checksum.
+///
+/// Uses the same algorithm as `CheckSumMappedFile`: the file is summed as
+/// 16-bit little-endian words with end-around carry, skipping the stored
+/// checksum field, and the file length is added to the result. A mismatch
+/// between the stored and computed value indicates a corrupted or modified file.
+///
+/// # Arguments
+///
+/// * `image` - The complete contents of the file
+///
+/// # Returns
+///
+/// The stored and computed checksum, or `None` if the data is not a PE file.
+///
+/// # Examples
+///
+/// ```
+/// use flist::version_reader::compute_pe_checksum;
+///
+/// assert_eq!(compute_pe_checksum(b"not a PE file"), None);
+/// ```
+pub fn compute_pe_checksum(image: &[u8]) -> Option<PeChecksum> {
+    let (checksum_offset, stored) = checksum_field(image)?;
+
+    let mut sum = ChecksumSum::new(checksum_offset);
+    sum.update(image);
+
+    Some(PeChecksum {
+        stored,
+        computed: sum.finish(),
+    })
+}
+
+/// Locates the stored checksum in the headers of a PE image.
+///
+/// Returns the file offset of `IMAGE_OPTIONAL_HEADER.CheckSum` and its value, or
+/// `None` if `header` does not start with a PE header that includes the field.
+fn checksum_field(header: &[u8]) -> Option<(usize, u32)> {
+    let read_u32 = |offset: usize| -> Option<u32> {
+        let bytes = header.get(offset..offset.checked_add(4)?)?;
+        Some(u32::from_le_bytes(bytes.try_into().ok()?))
+    };
+
+    if header.get(0..2)? != b"MZ" {
+        return None;
+    }
+    let nt_offset = read_u32(0x3C)? as usize;
+    if header.get(nt_offset..nt_offset.checked_add(4)?)? != b"PE\0\0" {
+        return None;
+    }
+
+    // Signature (4 bytes) + IMAGE_FILE_HEADER (20 bytes) + offset within the
+    // optional header, identical for PE32 and PE32+
+    let checksum_offset = nt_offset + 4 + 20 + 64;
+    Some((checksum_offset, read_u32(checksum_offset)?))
+}
+
+/// Running PE checksum over data supplied in consecutive chunks.
+///
+/// Chunks may have any length, a word split across two chunks is carried over.
+struct ChecksumSum {
+    checksum_offset: u64,
+    sum: u64,
+    length: u64,
+    pending: Option<u8>,
+}
+
+impl ChecksumSum {
+    fn new(checksum_offset: usize) -> Self {
+        Self {
+            checksum_offset: checksum_offset as u64,
+            sum: 0,
+            length: 0,
+            pending: None,
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        if let Some(low) = self.pending {
+            let Some((&high, rest)) = data.split_first() else {
+                return;
+            };
+            self.pending = None;
+            self.add_word(self.length - 1, low, high);
+            self.length += 1;
+            data = rest;
+        }
+
+        let mut words = data.chunks_exact(2);
+        for word in &mut words {
+            self.add_word(self.length, word[0], word[1]);
+            self.length += 2;
+        }
+        if let [low] = words.remainder() {
+            self.pending = Some(*low);
+            self.length += 1;
+        }
+    }
+
+    fn add_word(&mut self, offset: u64, low: u8, high: u8) {
+        // The stored checksum itself is skipped, as two words at its offset
+        if offset == self.checksum_offset || offset == self.checksum_offset + 2 {
+            return;
+        }
+        self.sum += low as u64 | ((high as u64) << 8);
+        self.sum = (self.sum & 0xFFFF) + (self.sum >> 16);
+    }
+
+    fn finish(mut self) -> u32 {
+        if let Some(low) = self.pending.take() {
+            self.add_word(self.length - 1, low, 0);
+        }
+        let sum = (self.sum & 0xFFFF) + (self.sum >> 16);
+        (sum as u32).wrapping_add(self.length as u32)
+    }
+}
+
+/// Default size of the buffer used to stream file contents, 64 KiB.
+pub const DEFAULT_READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Verifies the checksum stored in a PE file.
+///
+/// Streams the file with a buffer of [`DEFAULT_READ_BUFFER_SIZE`] bytes, see
+/// [`verify_pe_checksum_with_buffer`].
+///
+/// # Arguments
+///
+/// * `path` - Path to the file to verify
+///
+/// # Returns
+///
+/// * `Ok(Some(true))` - The stored checksum matches the file contents
+/// * `Ok(Some(false))` - The stored checksum does not match
+/// * `Ok(None)` - File is not a PE file, cannot be read, or has no checksum set
+/// * `Err(_)` - An error occurred while reading the file
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use flist::version_reader::verify_pe_checksum;
+///
+/// if verify_pe_checksum(Path::new("app.exe")).unwrap() == Some(false) {
+///     println!("Checksum mismatch");
+/// }
+/// ```
+pub fn verify_pe_checksum(path: &Path) -> Result<Option<bool>, anyhow::Error> {
+    verify_pe_checksum_with_buffer(path, DEFAULT_READ_BUFFER_SIZE)
+}
+
+/// Verifies the checksum stored in a PE file, reading it in chunks of
+/// `buffer_size` bytes.
+///
+/// The file is read instead of mapped, since its exact length is part of the
+/// checksum and mappings are padded to the page size on some platforms. Larger
+/// buffers mean fewer read calls, which helps on network shares.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file to verify
+/// * `buffer_size` - Size of the read buffer in bytes, at least 1
+///
+/// # Returns
+///
+/// Same as [`verify_pe_checksum`].
+pub fn verify_pe_checksum_with_buffer(
+    path: &Path,
+    buffer_size: usize,
+) -> Result<Option<bool>, anyhow::Error> {
+    use std::io::Read;
+
+    let mut file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(_) => return Ok(None), // Not a valid file or can't read
+    };
+
+    // Read the DOS header, then up to and including the checksum field
+    let mut header = Vec::new();
+    (&mut file).take(0x40).read_to_end(&mut header)?;
+    let nt_offset = match header.get(0x3C..0x40) {
+        Some(bytes) => u32::from_le_bytes(bytes.try_into()?) as u64,
+        None => return Ok(None),
+    };
+    let header_length = (nt_offset + 4 + 20 + 64 + 4).saturating_sub(header.len() as u64);
+    (&mut file).take(header_length).read_to_end(&mut header)?;
+
+    let Some((checksum_offset, stored)) = checksum_field(&header) else {
+        return Ok(None);
+    };
+
+    let mut sum = ChecksumSum::new(checksum_offset);
+    sum.update(&header);
+    let mut buffer = vec![0u8; buffer_size.max(1)];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        sum.update(&buffer[..read]);
+    }
+
+    let checksum = PeChecksum {
+        stored,
+        computed: sum.finish(),
+    };
+    Ok(checksum.is_valid())
+}
+
+/// Reads file version information from a PE file (Windows executable or DLL).
+///
+/// This function attempts to parse the file as a PE (Portable Executable) file
+/// and extract version information from the VS_FIXEDFILEINFO structure in the
+/// file's resources.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file to read
+///
+/// # Returns
+///
+/// * `Ok(Some(FileVersion))` - Version information was successfully extracted
+/// * `Ok(None)` - File is not a PE file or has no version information
+/// * `Err(_)` - An error occurred while reading the file
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use flist::version_reader::read_file_version;
+///
+/// let version = read_file_version(Path::new("C:\\Windows\\System32\\kernel32.dll")).unwrap();
+/// if let Some(v) = version {
+///     println!("Version: {}", v);
+/// }
+/// ```
+pub fn read_file_version(path: &Path) -> Result<Option<FileVersion>, anyhow::Error> {
+    match pelite::FileMap::open(path) {
+        Ok(file_map) => read_file_version_from(&file_map),
+        Err(_) => Ok(None), // Not a valid file or can't read
+    }
+}
+
+/// Reads file version information from a PE image in a [`ByteSource`].
+///
+/// This is the parsing half of [`read_file_version`].
+///
+/// # Arguments
+///
+/// * `source` - The image bytes to parse
+///
+/// # Returns
+///
+/// * `Ok(Some(FileVersion))` - Version information was successfully extracted
+/// * `Ok(None)` - The bytes are not a PE image or have no version information
+/// * `Err(_)` - An error occurred while parsing the image
+pub fn read_file_version_from<S: ByteSource + ?Sized>(
+    source: &S,
+) -> Result<Option<FileVersion>, anyhow::Error> {
+    use pelite::PeFile;
+
+    // PeFile accepts both 32-bit (PE32) and 64-bit (PE32+) images
+    match PeFile::from_bytes(source.bytes()) {
+        Ok(pe) => Ok(extract_version(pe)),
+        // Not a PE file, maybe an ELF shared object, a Mach-O dylib or an MSI package
+        Err(_) => {
+            let bytes = source.bytes();
+            Ok(read_elf_version(bytes)
+                .or_else(|| read_macho_version(bytes))
+                .or_else(|| read_msi_version(bytes)))
+        }
+    }
+}
+
+/// Reads the file version from the fixed file info of a PE image's version
+/// resource, `None` if the image has no readable version resource.
+fn extract_version(pe: pelite::PeFile) -> Option<FileVersion> {
+    let resources = pe.resources().ok()?;
+    let version_info = resources.version_info().ok()?;
+    let fixed = version_info.fixed()?;
+
+    Some(vs_version_to_file_version(fixed.dwFileVersion))
+}
+
+/// Identifies a file independently of the path it was found under.
+///
+/// On Unix, hard links share the device and inode number. Elsewhere the
+/// canonical path is used, which resolves symbolic links but not hard links.
+#[cfg(unix)]
+type FileKey = (u64, u64);
+#[cfg(not(unix))]
+type FileKey = std::path::PathBuf;
+
+#[cfg(unix)]
+fn file_key(path: &Path) -> Option<FileKey> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_key(path: &Path) -> Option<FileKey> {
+    std::fs::canonicalize(path).ok()
+}
+
+/// A cache of file versions, so a file reachable under several paths is
+/// parsed only once. It holds the file version of [`read_file_version`] and,
+/// when the product version is needed, all fields of [`read_all_version_fields`].
+///
+/// Entries are keyed by the file itself rather than its path: the device and
+/// inode number on Unix, so all hard links of a file share one entry, and the
+/// canonical path elsewhere. Different files never share an entry, even with
+/// identical contents. The cache assumes files do not change while it is used,
+/// so create a new one for each scan.
+///
+/// The cache can be shared between threads.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use flist::version_reader::VersionCache;
+///
+/// let cache = VersionCache::new();
+/// let first = cache.get_or_read(Path::new("app/lib/core.dll")).unwrap();
+/// // A hard link to the same file is not parsed again
+/// let second = cache.get_or_read(Path::new("tools/core.dll")).unwrap();
+/// assert_eq!(first, second);
+/// ```
+#[derive(Debug, Default)]
+pub struct VersionCache {
+    versions: Mutex<HashMap<FileKey, Option<FileVersion>>>,
+    /// Results of [`read_all_version_fields`], kept apart from `versions` as
+    /// they are only read when the product version is needed
+    all_fields: Mutex<HashMap<FileKey, Option<PeVersionInfo>>>,
+}
+
+impl VersionCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the version of a file, reading it with [`read_file_version`]
+    /// unless the same file was read before.
+    ///
+    /// Files whose identity cannot be determined, e.g. because they are not
+    /// accessible, are read without caching. Errors are not cached.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the file to read
+    ///
+    /// # Returns
+    ///
+    /// The same as [`read_file_version`].
+    pub fn get_or_read(&self, path: &Path) -> Result<Option<FileVersion>, anyhow::Error> {
+        Self::get_or_insert(&self.versions, path, read_file_version)
+    }
+
+    /// Returns all version fields of a PE file, reading them with
+    /// [`read_all_version_fields`] unless the same file was read before.
+    ///
+    /// Caching works as for [`VersionCache::get_or_read`].
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the file to read
+    ///
+    /// # Returns
+    ///
+    /// The same as [`read_all_version_fields`].
+    pub fn get_or_read_all(&self, path: &Path) -> Result<Option<PeVersionInfo>, anyhow::Error> {
+        Self::get_or_insert(&self.all_fields, path, read_all_version_fields)
+    }
+
+    /// Returns the number of files in the cache.
+    pub fn len(&self) -> usize {
+        let versions = lock(&self.versions);
+        let all_fields = lock(&self.all_fields);
+        versions.len()
+            + all_fields
+                .keys()
+                .filter(|key| !versions.contains_key(key))
+                .count()
+    }
+
+    /// Returns whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        lock(&self.versions).is_empty() && lock(&self.all_fields).is_empty()
+    }
+
+    /// Returns the cached value of a file in `map`, or reads and caches it.
+    fn get_or_insert<T: Clone>(
+        map: &Mutex<HashMap<FileKey, T>>,
+        path: &Path,
+        read: impl Fn(&Path) -> Result<T, anyhow::Error>,
+    ) -> Result<T, anyhow::Error> {
+        let Some(key) = file_key(path) else {
+            return read(path);
+        };
+        if let Some(value) = lock(map).get(&key) {
+            return Ok(value.clone());
+        }
+
+        // Read without holding the lock, so other files are read in parallel
+        let value = read(path)?;
+        lock(map).insert(key, value.clone());
+        Ok(value)
+    }
+}
+
+/// Locks a map of the [`VersionCache`].
+fn lock<T>(map: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    // The map stays consistent even if a thread panicked while holding the lock
+    map.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Reads the version of an ELF shared object from its soname.
+///
+/// Returns `None` for bytes that are not an ELF image, have no `DT_SONAME`
+/// entry or have a soname without numeric suffix.
+#[cfg(feature = "elf")]
+fn read_elf_version(bytes: &[u8]) -> Option<FileVersion> {
+    let elf = goblin::elf::Elf::parse(bytes).ok()?;
+    soname_version(elf.soname?)
+}
+
+/// ELF support is disabled, no version is read from ELF images.
+#[cfg(not(feature = "elf"))]
+fn read_elf_version(_bytes: &[u8]) -> Option<FileVersion> {
+    None
+}
+
+/// Extracts the version from the suffix of a shared object name.
+///
+/// The version is the part after `.so.`, with one to four numeric parts, as
+/// in `libfoo.so.1.2.3`. Names without such a suffix have no version.
+///
+/// # Arguments
+///
+/// * `soname` - The soname or file name of a shared object
+///
+/// # Returns
+///
+/// The version, or `None` if the name has no numeric version suffix.
+///
+/// # Examples
+///
+/// ```
+/// use flist::version_reader::soname_version;
+///
+/// assert_eq!(soname_version("libfoo.so.1.2.3"), Some("1.2.3".parse().unwrap()));
+/// assert_eq!(soname_version("libc.so.6"), Some("6".parse().unwrap()));
+/// assert_eq!(soname_version("libfoo.so"), None);
+/// ```
+pub fn soname_version(soname: &str) -> Option<FileVersion> {
+    let (_, suffix) = soname.rsplit_once(".so.")?;
+    let parts = suffix
+        .split('.')
+        .map(|part| part.parse::<u32>().ok())
+        .collect::<Option<Vec<u32>>>()?;
+    if parts.len() > 4 {
+        return None;
+    }
+
+    let part = |index: usize| parts.get(index).copied();
+    Some(FileVersion::new(part(0), part(1), part(2), part(3)))
+}
+
+/// Reads the current version of a Mach-O dylib from its `LC_ID_DYLIB` command.
+///
+/// Universal binaries are read from the slice of the host architecture, or
+/// else from the first slice that is a dylib. Returns `None` for bytes that are
+/// not a Mach-O image or lack the load command, such as executables.
+#[cfg(feature = "macho")]
+fn read_macho_version(bytes: &[u8]) -> Option<FileVersion> {
+    use goblin::mach::cputype::{CPU_TYPE_ARM64, CPU_TYPE_X86, CPU_TYPE_X86_64};
+    use goblin::mach::load_command::CommandVariant;
+    use goblin::mach::{Mach, MachO, SingleArch};
+
+    let dylib_version = |macho: &MachO| {
+        macho
+            .load_commands
+            .iter()
+            .find_map(|command| match &command.command {
+                CommandVariant::IdDylib(dylib) => Some(macho_version(dylib.dylib.current_version)),
+                _ => None,
+            })
+    };
+
+    match Mach::parse(bytes).ok()? {
+        Mach::Binary(macho) => dylib_version(&macho),
+        Mach::Fat(fat) => {
+            let host = if cfg!(target_arch = "x86_64") {
+                Some(CPU_TYPE_X86_64)
+            } else if cfg!(target_arch = "aarch64") {
+                Some(CPU_TYPE_ARM64)
+            } else if cfg!(target_arch = "x86") {
+                Some(CPU_TYPE_X86)
+            } else {
+                None
+            };
+            let arches = fat.arches().ok()?;
+            let host_index = arches.iter().position(|arch| Some(arch.cputype) == host);
+            let version_at = |index: usize| match fat.get(index) {
+                Ok(SingleArch::MachO(macho)) => dylib_version(&macho),
+                _ => None,
+            };
+
+            host_index
+                .and_then(version_at)
+                .or_else(|| (0..arches.len()).find_map(version_at))
+        }
+    }
+}
+
+/// Mach-O support is disabled, no version is read from Mach-O images.
+#[cfg(not(feature = "macho"))]
+fn read_macho_version(_bytes: &[u8]) -> Option<FileVersion> {
+    None
+}
+
+/// Signature of OLE compound documents, the container format of MSI packages.
+#[cfg(feature = "msi")]
+const OLE_SIGNATURE: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+/// Reads the version of a Windows Installer package from the `ProductVersion`
+/// property.
+///
+/// Returns `None` for bytes that are not an MSI database, such as other OLE
+/// documents, and for packages without a parsable `ProductVersion`.
+#[cfg(feature = "msi")]
+fn read_msi_version(bytes: &[u8]) -> Option<FileVersion> {
+    use msi::{Expr, Package, Select};
+
+    // Checked first, so that other files are not parsed as compound documents
+    if !bytes.starts_with(&OLE_SIGNATURE) {
+        return None;
+    }
+    let mut package = Package::open(std::io::Cursor::new(bytes)).ok()?;
+    if !package.has_table("Property") {
+        return None;
+    }
+    let query =
+        Select::table("Property").with(Expr::col("Property").eq(Expr::string("ProductVersion")));
+    let mut rows = package.select_rows(query).ok()?;
+    let row = rows.next()?;
+    row["Value"].as_str()?.trim().parse().ok()
+}
+
+/// MSI support is disabled, no version is read from Windows Installer packages.
+#[cfg(not(feature = "msi"))]
+fn read_msi_version(_bytes: &[u8]) -> Option<FileVersion> {
+    None
+}
+
+/// Reads the `ProductVersion` property of a Windows Installer package.
+///
+/// [`read_file_version`] returns the same version as the file version of an
+/// MSI package, which has no separate file version. This reads only MSI
+/// packages, so it tells them apart from other files without a PE product
+/// version. Requires the `msi` feature.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file to read
+///
+/// # Returns
+///
+/// * `Ok(Some(FileVersion))` - The product version of the package
+/// * `Ok(None)` - File is not an MSI package, has no `ProductVersion`, or cannot be read
+/// * `Err(_)` - An error occurred while reading the file
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use flist::version_reader::read_msi_product_version;
+///
+/// let version = read_msi_product_version(Path::new("setup.msi")).unwrap();
+/// println!("ProductVersion: {:?}", version);
+/// ```
+pub fn read_msi_product_version(path: &Path) -> Result<Option<FileVersion>, anyhow::Error> {
+    match pelite::FileMap::open(path) {
+        Ok(file_map) => Ok(read_msi_version(file_map.bytes())),
+        Err(_) => Ok(None), // Not a valid file or can't read
+    }
+}
+
+/// Decodes a Mach-O dylib version as stored in load commands.
+///
+/// The version is packed as `X.Y.Z` into 32 bits: 16 bits for `X` and 8 bits
+/// each for `Y` and `Z`.
+///
+/// # Arguments
+///
+/// * `encoded` - The packed version, e.g. `current_version` of `LC_ID_DYLIB`
+///
+/// # Examples
+///
+/// ```
+/// use flist::version_reader::macho_version;
+///
+/// assert_eq!(macho_version(0x0001_0203), "1.2.3".parse().unwrap());
+/// ```
+pub fn macho_version(encoded: u32) -> FileVersion {
+    FileVersion::new(
+        Some(encoded >> 16),
+        Some((encoded >> 8) & 0xFF),
+        Some(encoded & 0xFF),
+        None,
+    )
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    #[cfg(windows)]
+    fn test_read_version_from_system_dll() {
+        // Test with a known Windows system DLL
+        let system_root = std::env::var("SystemRoot").unwrap_or_else(|_| "C:\\Windows".to_string());
+        let dll_path = PathBuf::from(system_root)
+            .join("System32")
             .join("kernel32.dll");
 
         if dll_path.exists() {
@@ -284,6 +1398,852 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(windows)]
+    fn test_read_all_version_fields_from_system_dll() {
+        let system_root = std::env::var("SystemRoot").unwrap_or_else(|_| "C:\\Windows".to_string());
+        let dll_path = PathBuf::from(system_root)
+            .join("System32")
+            .join("kernel32.dll");
+
+        if dll_path.exists() {
+            let info = read_all_version_fields(&dll_path).unwrap().unwrap();
+            assert_eq!(info.file_version, read_file_version(&dll_path).unwrap());
+            assert!(info.product_version.is_some());
+            assert!(info.strings.contains_key("CompanyName"));
+            assert_ne!(info.arch, 0);
+        }
+    }
+
+    #[test]
+    fn test_read_all_version_fields_from_non_pe_file() {
+        let temp_file = std::env::temp_dir().join("flist_test_not_a_pe.dll");
+        std::fs::write(&temp_file, b"definitely not a PE file").unwrap();
+
+        let result = read_all_version_fields(&temp_file);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), None);
+
+        std::fs::remove_file(&temp_file).unwrap();
+    }
+
+    #[test]
+    fn test_read_all_version_fields_from_nonexistent_file() {
+        let result = read_all_version_fields(&PathBuf::from("nonexistent.dll"));
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), None);
+    }
+
+    /// Builds a minimal buffer with the PE headers needed to locate the checksum.
+    fn checksum_test_image() -> Vec<u8> {
+        let mut image = vec![0u8; 0x200];
+        image[0..2].copy_from_slice(b"MZ");
+        image[0x3C..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+        image[0x80..0x84].copy_from_slice(b"PE\0\0");
+        for (i, byte) in image.iter_mut().enumerate().skip(0x100) {
+            *byte = (i * 7) as u8;
+        }
+        image
+    }
+
+    #[test]
+    fn test_compute_pe_checksum() {
+        let mut image = checksum_test_image();
+        let checksum_offset = 0x80 + 4 + 20 + 64;
+
+        // No checksum set
+        let checksum = compute_pe_checksum(&image).unwrap();
+        assert_eq!(checksum.stored, 0);
+        assert_eq!(checksum.is_valid(), None);
+
+        // The stored checksum field is excluded from the sum
+        let computed = checksum.computed;
+        image[checksum_offset..checksum_offset + 4].copy_from_slice(&computed.to_le_bytes());
+        let checksum = compute_pe_checksum(&image).unwrap();
+        assert_eq!(checksum.computed, computed);
+        assert_eq!(checksum.is_valid(), Some(true));
+
+        // Tampering with the contents invalidates the checksum
+        image[0x150] ^= 0xFF;
+        assert_eq!(compute_pe_checksum(&image).unwrap().is_valid(), Some(false));
+    }
+
+    #[test]
+    fn test_compute_pe_checksum_known_value() {
+        // Words: 0x5A4D ("MZ"), 0xFFFF, 0x0080 at 0x3C, 0x4550 ("PE"), 0x0002 at 0xFE
+        // 0x5A4D + 0xFFFF folds to 0x5A4D, + 0x0080 + 0x4550 + 0x0002 = 0xA01F,
+        // plus the file length 0x100
+        let mut image = vec![0u8; 0x100];
+        image[0..2].copy_from_slice(b"MZ");
+        image[2..4].copy_from_slice(&[0xFF, 0xFF]);
+        image[0x3C] = 0x80;
+        image[0x80..0x84].copy_from_slice(b"PE\0\0");
+        image[0xFE] = 0x02;
+        image[0x80 + 88..0x80 + 92].copy_from_slice(&0xDEADBEEFu32.to_le_bytes());
+
+        let checksum = compute_pe_checksum(&image).unwrap();
+        assert_eq!(checksum.stored, 0xDEADBEEF);
+        assert_eq!(checksum.computed, 0xA01F + 0x100);
+    }
+
+    #[test]
+    fn test_verify_pe_checksum_uses_file_length() {
+        // Odd sized file, so any padding would change the computed checksum
+        let mut image = checksum_test_image();
+        image.push(0x5A);
+        let checksum_offset = 0x80 + 4 + 20 + 64;
+        let computed = compute_pe_checksum(&image).unwrap().computed;
+        image[checksum_offset..checksum_offset + 4].copy_from_slice(&computed.to_le_bytes());
+
+        let temp_file = std::env::temp_dir().join("flist_test_checksum.exe");
+        std::fs::write(&temp_file, &image).unwrap();
+        assert_eq!(verify_pe_checksum(&temp_file).unwrap(), Some(true));
+        std::fs::remove_file(&temp_file).unwrap();
+    }
+
+    #[test]
+    fn test_verify_pe_checksum_with_any_buffer_size() {
+        let mut image = checksum_test_image();
+        image.extend((0..1001).map(|i| (i * 7) as u8));
+        let checksum_offset = 0x80 + 4 + 20 + 64;
+        let computed = compute_pe_checksum(&image).unwrap().computed;
+        image[checksum_offset..checksum_offset + 4].copy_from_slice(&computed.to_le_bytes());
+
+        let temp_file = std::env::temp_dir().join("flist_test_checksum_buffer.exe");
+        std::fs::write(&temp_file, &image).unwrap();
+        // Odd sizes split words across reads
+        for buffer_size in [1, 2, 3, 7, 64, 4096, DEFAULT_READ_BUFFER_SIZE] {
+            assert_eq!(
+                verify_pe_checksum_with_buffer(&temp_file, buffer_size).unwrap(),
+                Some(true),
+                "buffer size {}",
+                buffer_size
+            );
+        }
+
+        image[0x300] ^= 0xFF;
+        std::fs::write(&temp_file, &image).unwrap();
+        assert_eq!(
+            verify_pe_checksum_with_buffer(&temp_file, 3).unwrap(),
+            Some(false)
+        );
+        std::fs::remove_file(&temp_file).unwrap();
+    }
+
+    #[test]
+    fn test_compute_pe_checksum_rejects_non_pe() {
+        assert_eq!(compute_pe_checksum(b""), None);
+        assert_eq!(compute_pe_checksum(b"MZ"), None);
+
+        // MZ header pointing to missing PE signature
+        let mut image = checksum_test_image();
+        image[0x80..0x84].copy_from_slice(b"XX\0\0");
+        assert_eq!(compute_pe_checksum(&image), None);
+    }
+
+    /// Builds a PE32+ image laid out like a UPX packed file: the resource
+    /// directory points into a section that has no raw data in the file.
+    ///
+    /// A real packed binary is not committed as a fixture, since packed
+    /// executables are routinely flagged by virus scanners.
+    fn packed_test_image() -> Vec<u8> {
+        let mut image = vec![0u8; 0x400];
+        let put_u16 = |image: &mut Vec<u8>, offset: usize, value: u16| {
+            image[offset..offset + 2].copy_from_slice(&value.to_le_bytes())
+        };
+        let put_u32 = |image: &mut Vec<u8>, offset: usize, value: u32| {
+            image[offset..offset + 4].copy_from_slice(&value.to_le_bytes())
+        };
+
+        // DOS header and NT signature
+        image[0..2].copy_from_slice(b"MZ");
+        put_u32(&mut image, 0x3C, 0x40);
+        image[0x40..0x44].copy_from_slice(b"PE\0\0");
+
+        // IMAGE_FILE_HEADER: x64, two sections, PE32+ optional header size
+        put_u16(&mut image, 0x44, 0x8664);
+        put_u16(&mut image, 0x46, 2);
+        put_u16(&mut image, 0x54, 240);
+        put_u16(&mut image, 0x56, 0x22);
+
+        // IMAGE_OPTIONAL_HEADER64
+        let optional = 0x58;
+        put_u16(&mut image, optional, 0x20B);
+        put_u32(&mut image, optional + 32, 0x1000); // SectionAlignment
+        put_u32(&mut image, optional + 36, 0x200); // FileAlignment
+        put_u32(&mut image, optional + 56, 0x3000); // SizeOfImage
+        put_u32(&mut image, optional + 60, 0x200); // SizeOfHeaders
+        put_u16(&mut image, optional + 68, 2); // Subsystem
+        put_u32(&mut image, optional + 108, 16); // NumberOfRvaAndSizes
+        put_u32(&mut image, optional + 112 + 2 * 8, 0x1800); // Resource directory RVA
+        put_u32(&mut image, optional + 112 + 2 * 8 + 4, 0x100); // Resource directory size
+
+        // UPX0: virtual only, holds the (unreachable) resources
+        let sections = optional + 240;
+        image[sections..sections + 4].copy_from_slice(b"UPX0");
+        put_u32(&mut image, sections + 8, 0x1000);
+        put_u32(&mut image, sections + 12, 0x1000);
+
+        // UPX1: the compressed payload
+        let section = sections + 40;
+        image[section..section + 4].copy_from_slice(b"UPX1");
+        put_u32(&mut image, section + 8, 0x1000);
+        put_u32(&mut image, section + 12, 0x2000);
+        put_u32(&mut image, section + 16, 0x200);
+        put_u32(&mut image, section + 20, 0x200);
+
+        image
+    }
+
+    #[test]
+    fn test_read_version_from_packed_pe() {
+        let temp_file = std::env::temp_dir().join("flist_test_packed.exe");
+        std::fs::write(&temp_file, packed_test_image()).unwrap();
+
+        // Unreachable resources are reported as "no version", not as an error
+        let result = read_file_version(&temp_file);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), None);
+
+        // Header fields are still available
+        let info = read_all_version_fields(&temp_file).unwrap().unwrap();
+        assert_eq!(info.file_version, None);
+        assert_eq!(info.arch, 0x8664);
+        assert_eq!(info.subsystem, 2);
+
+        std::fs::remove_file(&temp_file).unwrap();
+    }
+
+    /// Builds a PE image with a single `.rsrc` section holding a version
+    /// resource with the given file version.
+    ///
+    /// `pe32_plus` selects a 64-bit (PE32+) or a 32-bit (PE32) image. The
+    /// product version equals the file version.
+    fn version_test_image(pe32_plus: bool, version: [u16; 4]) -> Vec<u8> {
+        version_test_image_with_product(pe32_plus, version, version)
+    }
+
+    /// Builds a minimal 64-bit little-endian ELF shared object whose dynamic
+    /// section holds `soname` as `DT_SONAME`, if given.
+    #[cfg(feature = "elf")]
+    pub(crate) fn elf_test_image(soname: Option<&str>) -> Vec<u8> {
+        const DYNAMIC: usize = 0xB0;
+        const STRTAB: usize = DYNAMIC + 4 * 16;
+
+        let mut strtab = vec![0u8];
+        strtab.extend(soname.unwrap_or_default().as_bytes());
+        strtab.push(0);
+        let size = (STRTAB + strtab.len()) as u64;
+
+        let mut image = Vec::new();
+        // ELF header: 64-bit, little-endian, shared object for x86-64
+        image.extend(b"\x7fELF\x02\x01\x01");
+        image.resize(16, 0);
+        image.extend(3u16.to_le_bytes()); // e_type = ET_DYN
+        image.extend(62u16.to_le_bytes()); // e_machine = EM_X86_64
+        image.extend(1u32.to_le_bytes()); // e_version
+        image.extend(0u64.to_le_bytes()); // e_entry
+        image.extend(0x40u64.to_le_bytes()); // e_phoff
+        image.extend(0u64.to_le_bytes()); // e_shoff
+        image.extend(0u32.to_le_bytes()); // e_flags
+        for value in [64u16, 56, 2, 64, 0, 0] {
+            // e_ehsize, e_phentsize, e_phnum, e_shentsize, e_shnum, e_shstrndx
+            image.extend(value.to_le_bytes());
+        }
+
+        // PT_LOAD mapping the whole file at address 0, then PT_DYNAMIC
+        for (kind, offset, length) in [(1u32, 0, size), (2, DYNAMIC as u64, 64)] {
+            image.extend(kind.to_le_bytes());
+            image.extend(4u32.to_le_bytes()); // p_flags = PF_R
+            image.extend(offset.to_le_bytes()); // p_offset
+            image.extend(offset.to_le_bytes()); // p_vaddr
+            image.extend(offset.to_le_bytes()); // p_paddr
+            image.extend(length.to_le_bytes()); // p_filesz
+            image.extend(length.to_le_bytes()); // p_memsz
+            image.extend(8u64.to_le_bytes()); // p_align
+        }
+        assert_eq!(image.len(), DYNAMIC);
+
+        // DT_SONAME (or DT_DEBUG without soname), DT_STRTAB, DT_STRSZ, DT_NULL
+        let first = if soname.is_some() {
+            (14u64, 1)
+        } else {
+            (21, 0)
+        };
+        for (tag, value) in [first, (5, STRTAB as u64), (10, strtab.len() as u64), (0, 0)] {
+            image.extend(tag.to_le_bytes());
+            image.extend(value.to_le_bytes());
+        }
+        image.extend(strtab);
+        image
+    }
+
+    #[cfg(feature = "elf")]
+    #[test]
+    fn test_read_elf_soname_version() {
+        let image = elf_test_image(Some("libfoo.so.1.2.3"));
+        assert_eq!(
+            read_file_version_from(&image).unwrap(),
+            Some("1.2.3".parse().unwrap())
+        );
+
+        // No soname, or a soname without version, is no version information
+        assert_eq!(read_file_version_from(&elf_test_image(None)).unwrap(), None);
+        let image = elf_test_image(Some("libfoo.so"));
+        assert_eq!(read_file_version_from(&image).unwrap(), None);
+    }
+
+    /// Builds a minimal 64-bit Mach-O image for `cputype`, a dylib with the
+    /// given packed current version or an executable without `LC_ID_DYLIB`.
+    #[cfg(feature = "macho")]
+    pub(crate) fn macho_test_image(cputype: u32, current_version: Option<u32>) -> Vec<u8> {
+        let mut command = Vec::new();
+        if let Some(version) = current_version {
+            let name = b"@rpath/libfoo.dylib\0\0\0\0\0";
+            command.extend(0xDu32.to_le_bytes()); // LC_ID_DYLIB
+            command.extend((24 + name.len() as u32).to_le_bytes()); // cmdsize
+            command.extend(24u32.to_le_bytes()); // name offset
+            command.extend(2u32.to_le_bytes()); // timestamp
+            command.extend(version.to_le_bytes()); // current_version
+            command.extend(0x0001_0000u32.to_le_bytes()); // compatibility_version
+            command.extend(name);
+        }
+
+        let mut image = Vec::new();
+        image.extend(0xFEED_FACFu32.to_le_bytes()); // MH_MAGIC_64
+        image.extend(cputype.to_le_bytes());
+        image.extend(0u32.to_le_bytes()); // cpusubtype
+        let filetype: u32 = if current_version.is_some() { 6 } else { 2 }; // MH_DYLIB, MH_EXECUTE
+        image.extend(filetype.to_le_bytes());
+        image.extend((current_version.is_some() as u32).to_le_bytes()); // ncmds
+        image.extend((command.len() as u32).to_le_bytes()); // sizeofcmds
+        image.extend(0u32.to_le_bytes()); // flags
+        image.extend(0u32.to_le_bytes()); // reserved
+        image.extend(command);
+        image
+    }
+
+    /// Combines Mach-O images into a universal binary.
+    #[cfg(feature = "macho")]
+    fn fat_test_image(slices: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        const ALIGN: usize = 0x1000;
+
+        let mut image = Vec::new();
+        image.extend(0xCAFE_BABEu32.to_be_bytes()); // FAT_MAGIC
+        image.extend((slices.len() as u32).to_be_bytes());
+        for (index, (cputype, slice)) in slices.iter().enumerate() {
+            image.extend(cputype.to_be_bytes());
+            image.extend(0u32.to_be_bytes()); // cpusubtype
+            image.extend((((index + 1) * ALIGN) as u32).to_be_bytes()); // offset
+            image.extend((slice.len() as u32).to_be_bytes()); // size
+            image.extend(12u32.to_be_bytes()); // align as a power of 2
+        }
+        for (_, slice) in slices {
+            image.resize(image.len().next_multiple_of(ALIGN), 0);
+            image.extend(slice);
+        }
+        image
+    }
+
+    #[cfg(feature = "macho")]
+    #[test]
+    fn test_read_macho_dylib_version() {
+        const X86_64: u32 = 0x0100_0007;
+        const ARM64: u32 = 0x0100_000C;
+
+        let image = macho_test_image(X86_64, Some(0x0001_0203));
+        assert_eq!(
+            read_file_version_from(&image).unwrap(),
+            Some("1.2.3".parse().unwrap())
+        );
+
+        // Executables have no LC_ID_DYLIB
+        let image = macho_test_image(X86_64, None);
+        assert_eq!(read_file_version_from(&image).unwrap(), None);
+
+        // Universal binaries prefer the host slice, otherwise the first dylib
+        let image = fat_test_image(&[
+            (ARM64, macho_test_image(ARM64, Some(0x0001_0000))),
+            (X86_64, macho_test_image(X86_64, Some(0x0002_0000))),
+        ]);
+        let expected = if cfg!(target_arch = "x86_64") {
+            "2.0.0"
+        } else {
+            "1.0.0"
+        };
+        assert_eq!(
+            read_file_version_from(&image).unwrap(),
+            Some(expected.parse().unwrap())
+        );
+        let image = fat_test_image(&[
+            (ARM64, macho_test_image(ARM64, None)),
+            (X86_64, macho_test_image(X86_64, Some(0x0003_0100))),
+        ]);
+        assert_eq!(
+            read_file_version_from(&image).unwrap(),
+            Some("3.1.0".parse().unwrap())
+        );
+    }
+
+    /// Builds an MSI package whose `Property` table holds `ProductVersion`, if
+    /// given, after another property.
+    #[cfg(feature = "msi")]
+    pub(crate) fn msi_test_package(product_version: Option<&str>) -> Vec<u8> {
+        use msi::{Column, Insert, Package, PackageType, Value};
+
+        let cursor = std::io::Cursor::new(Vec::new());
+        let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+        let columns = vec![
+            Column::build("Property").primary_key().id_string(72),
+            Column::build("Value").text_string(0),
+        ];
+        package.create_table("Property", columns).unwrap();
+        let mut properties = vec![("ProductName", "Foo")];
+        properties.extend(product_version.map(|version| ("ProductVersion", version)));
+        let rows = properties
+            .into_iter()
+            .map(|(name, value)| vec![Value::from(name), Value::from(value)])
+            .collect();
+        package
+            .insert_rows(Insert::into("Property").rows(rows))
+            .unwrap();
+        package.into_inner().unwrap().into_inner()
+    }
+
+    #[cfg(feature = "msi")]
+    #[test]
+    fn test_read_msi_product_version() {
+        let package = msi_test_package(Some("10.2.345"));
+        assert_eq!(
+            read_file_version_from(&package).unwrap(),
+            Some("10.2.345".parse().unwrap())
+        );
+
+        // No ProductVersion property, or an OLE signature without a database
+        assert_eq!(
+            read_file_version_from(&msi_test_package(None)).unwrap(),
+            None
+        );
+        let mut bytes = OLE_SIGNATURE.to_vec();
+        bytes.resize(512, 0);
+        assert_eq!(read_file_version_from(&bytes).unwrap(), None);
+    }
+
+    #[test]
+    fn test_version_cache() {
+        let temp_dir = std::env::temp_dir().join("flist_test_version_cache");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let original = temp_dir.join("core.dll");
+        let link = temp_dir.join("core_link.dll");
+        let copy = temp_dir.join("core_copy.dll");
+        std::fs::write(&original, version_test_image(true, [1, 2, 3, 4])).unwrap();
+        std::fs::hard_link(&original, &link).unwrap();
+        std::fs::copy(&original, &copy).unwrap();
+
+        let cache = VersionCache::new();
+        assert!(cache.is_empty());
+        let expected = Some("1.2.3.4".parse().unwrap());
+        assert_eq!(cache.get_or_read(&original).unwrap(), expected);
+
+        // Rewriting the file keeps its inode, so the cached version is returned
+        // for both links, while the copy is a different file and is read
+        std::fs::write(&original, version_test_image(true, [9, 9, 9, 9])).unwrap();
+        assert_eq!(cache.get_or_read(&original).unwrap(), expected);
+        #[cfg(unix)]
+        assert_eq!(cache.get_or_read(&link).unwrap(), expected);
+        assert_eq!(cache.get_or_read(&copy).unwrap(), expected);
+        assert_eq!(cache.len(), 2);
+
+        // Files that cannot be identified are read without caching
+        assert_eq!(
+            cache.get_or_read(&temp_dir.join("missing.dll")).unwrap(),
+            None
+        );
+        assert_eq!(cache.len(), 2);
+
+        // All version fields, as read for the product version, are cached the
+        // same way, by file rather than by path
+        let cache = VersionCache::new();
+        let info = cache.get_or_read_all(&copy).unwrap().unwrap();
+        assert_eq!(info.file_version, expected);
+        assert_eq!(info.product_version, expected);
+        std::fs::write(&copy, version_test_image(true, [9, 9, 9, 9])).unwrap();
+        assert_eq!(cache.get_or_read_all(&copy).unwrap(), Some(info));
+        assert_eq!(
+            cache.get_or_read(&copy).unwrap(),
+            Some("9.9.9.9".parse().unwrap())
+        );
+        assert_eq!(cache.len(), 1);
+        assert!(!cache.is_empty());
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_macho_version() {
+        assert_eq!(macho_version(0), "0.0.0".parse().unwrap());
+        assert_eq!(macho_version(0x04D2_0A05), "1234.10.5".parse().unwrap());
+        assert_eq!(macho_version(u32::MAX), "65535.255.255".parse().unwrap());
+    }
+
+    #[test]
+    fn test_soname_version() {
+        assert_eq!(soname_version("libfoo.so.1"), Some("1".parse().unwrap()));
+        assert_eq!(
+            soname_version("libssl.so.1.1.1.23"),
+            Some("1.1.1.23".parse().unwrap())
+        );
+        assert_eq!(soname_version("libfoo.so"), None);
+        assert_eq!(soname_version("libfoo.so.1a"), None);
+        assert_eq!(soname_version("libfoo.so.1..2"), None);
+        assert_eq!(soname_version("libfoo.so.1.2.3.4.5"), None);
+        assert_eq!(soname_version("foo.dll"), None);
+    }
+
+    /// Builds a PE image like [`version_test_image`] with a product version
+    /// that differs from the file version.
+    fn version_test_image_with_product(
+        pe32_plus: bool,
+        version: [u16; 4],
+        product_version: [u16; 4],
+    ) -> Vec<u8> {
+        let mut image = vec![0u8; 0x400];
+        let put_u16 = |image: &mut Vec<u8>, offset: usize, value: u16| {
+            image[offset..offset + 2].copy_from_slice(&value.to_le_bytes())
+        };
+        let put_u32 = |image: &mut Vec<u8>, offset: usize, value: u32| {
+            image[offset..offset + 4].copy_from_slice(&value.to_le_bytes())
+        };
+        let optional_size = if pe32_plus { 240 } else { 224 };
+        let directories = if pe32_plus { 112 } else { 96 };
+
+        // DOS header and NT signature
+        image[0..2].copy_from_slice(b"MZ");
+        put_u32(&mut image, 0x3C, 0x40);
+        image[0x40..0x44].copy_from_slice(b"PE\0\0");
+
+        // IMAGE_FILE_HEADER: one section
+        put_u16(&mut image, 0x44, if pe32_plus { 0x8664 } else { 0x14C });
+        put_u16(&mut image, 0x46, 1);
+        put_u16(&mut image, 0x54, optional_size as u16);
+        put_u16(&mut image, 0x56, if pe32_plus { 0x22 } else { 0x102 });
+
+        // Optional header
+        let optional = 0x58;
+        put_u16(&mut image, optional, if pe32_plus { 0x20B } else { 0x10B });
+        put_u32(&mut image, optional + 32, 0x1000); // SectionAlignment
+        put_u32(&mut image, optional + 36, 0x200); // FileAlignment
+        put_u32(&mut image, optional + 56, 0x2000); // SizeOfImage
+        put_u32(&mut image, optional + 60, 0x200); // SizeOfHeaders
+        put_u16(&mut image, optional + 68, 2); // Subsystem
+        put_u32(&mut image, optional + directories - 4, 16); // NumberOfRvaAndSizes
+        put_u32(&mut image, optional + directories + 2 * 8, 0x1000); // Resource directory RVA
+        put_u32(&mut image, optional + directories + 2 * 8 + 4, 0x200); // Resource directory size
+
+        // .rsrc section mapped from file offset 0x200 to RVA 0x1000
+        let section = optional + optional_size;
+        image[section..section + 5].copy_from_slice(b".rsrc");
+        put_u32(&mut image, section + 8, 0x200);
+        put_u32(&mut image, section + 12, 0x1000);
+        put_u32(&mut image, section + 16, 0x200);
+        put_u32(&mut image, section + 20, 0x200);
+        put_u32(&mut image, section + 36, 0x4000_0040);
+
+        // Resource tree: RT_VERSION (16) -> ID 1 -> language 0x409 -> data
+        let rsrc = 0x200;
+        put_u16(&mut image, rsrc + 0x0E, 1);
+        put_u32(&mut image, rsrc + 0x10, 16);
+        put_u32(&mut image, rsrc + 0x14, 0x8000_0018);
+        put_u16(&mut image, rsrc + 0x26, 1);
+        put_u32(&mut image, rsrc + 0x28, 1);
+        put_u32(&mut image, rsrc + 0x2C, 0x8000_0030);
+        put_u16(&mut image, rsrc + 0x3E, 1);
+        put_u32(&mut image, rsrc + 0x40, 0x409);
+        put_u32(&mut image, rsrc + 0x44, 0x48);
+        put_u32(&mut image, rsrc + 0x48, 0x1058); // Data RVA
+        put_u32(&mut image, rsrc + 0x4C, 92); // Data size
+
+        // VS_VERSIONINFO header, key and VS_FIXEDFILEINFO
+        let info = rsrc + 0x58;
+        put_u16(&mut image, info, 92);
+        put_u16(&mut image, info + 2, 52);
+        for (i, c) in "VS_VERSION_INFO".encode_utf16().enumerate() {
+            put_u16(&mut image, info + 6 + 2 * i, c);
+        }
+        let fixed = info + 40;
+        let [major, minor, build, private] = version.map(u32::from);
+        put_u32(&mut image, fixed, 0xFEEF_04BD);
+        put_u32(&mut image, fixed + 4, 0x0001_0000);
+        put_u32(&mut image, fixed + 8, (major << 16) | minor);
+        put_u32(&mut image, fixed + 12, (build << 16) | private);
+        let [major, minor, build, private] = product_version.map(u32::from);
+        put_u32(&mut image, fixed + 16, (major << 16) | minor);
+        put_u32(&mut image, fixed + 20, (build << 16) | private);
+
+        image
+    }
+
+    /// Builds a managed variant of [`version_test_image`]: a COM descriptor at
+    /// RVA 0x1100 pointing to metadata with the given assembly version.
+    fn managed_test_image(pe32_plus: bool, version: [u16; 4], assembly: [u16; 4]) -> Vec<u8> {
+        let mut image = version_test_image(pe32_plus, version);
+        let metadata = crate::clr::tests::test_metadata(&[0x00], Some(assembly));
+        let (header, start) = (0x300, 0x348);
+        assert!(start + metadata.len() <= image.len());
+
+        let directories = 0x58 + if pe32_plus { 112 } else { 96 };
+        let com_descriptor = directories + 14 * 8;
+        image[com_descriptor..com_descriptor + 4].copy_from_slice(&0x1100u32.to_le_bytes());
+        image[com_descriptor + 4..com_descriptor + 8].copy_from_slice(&72u32.to_le_bytes());
+
+        // IMAGE_COR20_HEADER: cb, runtime 2.5, metadata directory
+        image[header..header + 4].copy_from_slice(&72u32.to_le_bytes());
+        image[header + 4..header + 8].copy_from_slice(&[2, 0, 5, 0]);
+        image[header + 8..header + 12].copy_from_slice(&0x1148u32.to_le_bytes());
+        image[header + 12..header + 16].copy_from_slice(&(metadata.len() as u32).to_le_bytes());
+        image[start..start + metadata.len()].copy_from_slice(&metadata);
+        image
+    }
+
+    #[test]
+    fn test_read_assembly_version_from_bytes() {
+        for pe32_plus in [false, true] {
+            let image = managed_test_image(pe32_plus, [1, 2, 3, 4], [1, 0, 0, 0]);
+            assert_eq!(
+                read_assembly_version_from(&image).unwrap(),
+                Some("1.0.0.0".parse().unwrap())
+            );
+            // The file version still comes from the version resource
+            assert_eq!(
+                read_file_version_from(&image).unwrap(),
+                Some("1.2.3.4".parse().unwrap())
+            );
+            let info = read_all_version_fields_from(image.as_slice())
+                .unwrap()
+                .unwrap();
+            assert_eq!(info.assembly_version, Some("1.0.0.0".parse().unwrap()));
+        }
+
+        // Native images have no COM descriptor
+        let image = version_test_image(true, [1, 2, 3, 4]);
+        assert_eq!(read_assembly_version_from(&image).unwrap(), None);
+        assert_eq!(
+            read_assembly_version_from(b"not a PE".as_slice()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_read_file_version_from_bytes_64bit() {
+        let image = version_test_image(true, [10, 2, 19041, 1234]);
+        let version = read_file_version_from(&image).unwrap();
+        assert_eq!(version, Some("10.2.19041.1234".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_read_file_version_from_bytes_32bit() {
+        let image = version_test_image(false, [1, 65535, 0, 7]);
+        let version = read_file_version_from(&image).unwrap();
+        assert_eq!(version, Some("1.65535.0.7".parse().unwrap()));
+    }
+
+    /// Path of a sample PE image committed under `tests/fixtures`.
+    fn fixture(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join(name)
+    }
+
+    #[test]
+    fn test_fixtures_match_test_images() {
+        // The committed fixtures were written by version_test_image; a change
+        // to the builder must regenerate them
+        let image = std::fs::read(fixture("version32.dll")).unwrap();
+        assert_eq!(image, version_test_image(false, [1, 2, 3, 4]));
+        let image = std::fs::read(fixture("version64.dll")).unwrap();
+        assert_eq!(image, version_test_image(true, [10, 0, 19041, 1]));
+    }
+
+    #[test]
+    fn test_read_all_version_fields_from_bytes() {
+        let image = version_test_image(false, [4, 3, 2, 1]);
+        let info = read_all_version_fields_from(image.as_slice())
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.file_version, Some("4.3.2.1".parse().unwrap()));
+        assert_eq!(info.product_version, Some("4.3.2.1".parse().unwrap()));
+        assert_eq!(info.arch, 0x14C);
+        assert!(info.strings.is_empty());
+    }
+
+    #[test]
+    fn test_read_product_version_from_bytes() {
+        for pe32_plus in [false, true] {
+            let image = version_test_image_with_product(pe32_plus, [1, 2, 3, 4], [10, 0, 22621, 1]);
+            let info = read_all_version_fields_from(image.as_slice())
+                .unwrap()
+                .unwrap();
+            assert_eq!(info.file_version, Some("1.2.3.4".parse().unwrap()));
+            assert_eq!(info.product_version, Some("10.0.22621.1".parse().unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_version_strings_from_table() {
+        let table = HashMap::from([
+            ("CompanyName".to_string(), "Contoso Ltd.".to_string()),
+            ("ProductName".to_string(), "Widget\0".to_string()),
+            ("FileDescription".to_string(), "  ".to_string()),
+            ("InternalName".to_string(), "widget".to_string()),
+        ]);
+        let strings = VersionStrings::from_table(&table);
+        assert_eq!(
+            strings,
+            VersionStrings {
+                company: Some("Contoso Ltd.".to_string()),
+                product: Some("Widget".to_string()),
+                description: None,
+                copyright: None,
+                product_version: None,
+            }
+        );
+        assert_eq!(strings.get(StringField::Product), Some("Widget"));
+        assert_eq!(strings.get(StringField::Copyright), None);
+    }
+
+    #[test]
+    fn test_version_strings_semantic_version() {
+        let semantic_version = |product_version: &str| {
+            let table =
+                HashMap::from([("ProductVersion".to_string(), product_version.to_string())]);
+            VersionStrings::from_table(&table)
+                .semantic_version()
+                .map(|version| version.to_string())
+        };
+        assert_eq!(
+            semantic_version("1.2.3-beta\0"),
+            Some("1.2.3-beta".to_string())
+        );
+        assert_eq!(semantic_version("v2.0"), Some("2.0.0".to_string()));
+        assert_eq!(semantic_version("10.0.19041.1"), None);
+        assert_eq!(semantic_version("10.0 (WinBuild.160101.0800)"), None);
+        assert_eq!(VersionStrings::default().semantic_version(), None);
+    }
+
+    #[test]
+    fn test_read_version_strings_without_string_table() {
+        // The test image has a fixed version but no StringFileInfo
+        let image = version_test_image(true, [1, 0, 0, 0]);
+        assert_eq!(read_version_strings_from(&image, None).unwrap(), None);
+        assert_eq!(
+            read_version_strings_from(&image, Some(0x409)).unwrap(),
+            None
+        );
+        assert_eq!(
+            read_version_strings_from(b"not a PE".as_slice(), None).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_read_version_strings_from_system_dll() {
+        let path = Path::new("C:\\Windows\\System32\\kernel32.dll");
+        let strings = read_version_strings(path, None).unwrap().unwrap();
+        assert!(strings.company.is_some());
+        // An unknown language falls back to the first block
+        assert_eq!(
+            read_version_strings(path, Some(0x7FFF)).unwrap(),
+            Some(strings)
+        );
+    }
+
+    #[test]
+    fn test_read_pe_timestamp_from_bytes() {
+        for pe32_plus in [false, true] {
+            let mut image = version_test_image(pe32_plus, [1, 0, 0, 0]);
+            // 2024-01-31T12:30:45Z
+            image[0x48..0x4C].copy_from_slice(&1_706_704_245u32.to_le_bytes());
+            assert_eq!(
+                read_pe_timestamp_from(&image).unwrap(),
+                Some(UNIX_EPOCH + Duration::from_secs(1_706_704_245))
+            );
+        }
+
+        // Reproducible builds may leave the stamp at zero
+        assert_eq!(
+            read_pe_timestamp_from(&packed_test_image()).unwrap(),
+            Some(UNIX_EPOCH)
+        );
+        assert_eq!(read_pe_timestamp_from(b"MZ".as_slice()).unwrap(), None);
+        assert_eq!(
+            read_pe_timestamp(Path::new("nonexistent.dll")).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_pe_arch() {
+        assert_eq!(PeArch::from_machine(0x014C), PeArch::X86);
+        assert_eq!(PeArch::from_machine(0x01C4), PeArch::Arm);
+        assert_eq!(PeArch::from_machine(0xAA64), PeArch::Arm64);
+        assert_eq!(PeArch::from_machine(0x0200), PeArch::Other(0x0200));
+
+        for arch in [PeArch::X86, PeArch::X64, PeArch::Arm, PeArch::Arm64] {
+            assert_eq!(arch.to_string().parse::<PeArch>().unwrap(), arch);
+        }
+        assert_eq!("X64".parse::<PeArch>().unwrap(), PeArch::X64);
+        assert!("amd64".parse::<PeArch>().is_err());
+        assert!("0x0200".parse::<PeArch>().is_err());
+    }
+
+    #[test]
+    fn test_read_pe_arch_from_bytes() {
+        // The machine constant is reported, not the PE32/PE32+ class
+        let mut image = version_test_image(true, [1, 0, 0, 0]);
+        assert_eq!(read_pe_arch_from(&image).unwrap(), Some(PeArch::X64));
+        image[0x44..0x46].copy_from_slice(&0xAA64u16.to_le_bytes());
+        assert_eq!(read_pe_arch_from(&image).unwrap(), Some(PeArch::Arm64));
+
+        let image = version_test_image(false, [1, 0, 0, 0]);
+        assert_eq!(read_pe_arch_from(&image).unwrap(), Some(PeArch::X86));
+        assert_eq!(read_pe_arch_from(b"MZ".as_slice()).unwrap(), None);
+        assert_eq!(read_pe_arch(Path::new("nonexistent.dll")).unwrap(), None);
+    }
+
+    #[test]
+    fn test_read_pe_signed_from_bytes() {
+        for pe32_plus in [false, true] {
+            let mut image = version_test_image(pe32_plus, [1, 0, 0, 0]);
+            assert_eq!(read_pe_signed_from(&image).unwrap(), Some(false));
+
+            // Certificate table (directory 4) at the end of the file
+            let security = 0x58 + if pe32_plus { 112 } else { 96 } + 4 * 8;
+            let offset = image.len() as u32;
+            image.extend_from_slice(&[0u8; 0x10]);
+            image[security..security + 4].copy_from_slice(&offset.to_le_bytes());
+            image[security + 4..security + 8].copy_from_slice(&0x10u32.to_le_bytes());
+            assert_eq!(read_pe_signed_from(&image).unwrap(), Some(true));
+
+            // A table cut off by truncation is not a signature
+            image.truncate(offset as usize + 8);
+            assert_eq!(read_pe_signed_from(&image).unwrap(), Some(false));
+        }
+
+        assert_eq!(read_pe_signed_from(b"MZ".as_slice()).unwrap(), None);
+        assert_eq!(read_pe_signed(Path::new("nonexistent.dll")).unwrap(), None);
+    }
+
+    #[test]
+    fn test_read_from_bytes_rejects_non_pe() {
+        let image = b"MZ but nothing else".to_vec();
+        assert_eq!(read_file_version_from(&image).unwrap(), None);
+        assert!(read_all_version_fields_from(&image).unwrap().is_none());
+        assert_eq!(read_file_version_from(&packed_test_image()).unwrap(), None);
+    }
+
     #[test]
     fn test_read_version_from_nonexistent_file() {
         let path = PathBuf::from("nonexistent.dll");
@@ -0,0 +1,105 @@
+//! Version reading against the sample PE images in `tests/fixtures`.
+//!
+//! The fixtures are tiny hand-crafted images, so these tests run the same on
+//! every platform:
+//!
+//! * `version32.dll` - PE32 (x86) with file version 1.2.3.4
+//! * `version64.dll` - PE32+ (x64) with file version 10.0.19041.1
+//! * `noversion.dll` - PE32+ (x64) without a resource directory
+
+use flist::file_lister::{
+    CollectOptions, VersionField, collect_file_info_with_options, filter_older_than_of,
+    read_reference_version,
+};
+use flist::file_version::FileVersion;
+use flist::version_reader::{
+    PeArch, read_all_version_fields, read_file_version, read_pe_arch, read_pe_signed,
+};
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+}
+
+fn version(spec: &str) -> Option<FileVersion> {
+    Some(spec.parse().unwrap())
+}
+
+#[test]
+fn test_read_file_version_32bit() {
+    let path = fixture("version32.dll");
+    assert_eq!(read_file_version(&path).unwrap(), version("1.2.3.4"));
+    assert_eq!(read_pe_arch(&path).unwrap(), Some(PeArch::X86));
+    assert_eq!(read_pe_signed(&path).unwrap(), Some(false));
+}
+
+#[test]
+fn test_read_file_version_64bit() {
+    let path = fixture("version64.dll");
+    assert_eq!(read_file_version(&path).unwrap(), version("10.0.19041.1"));
+    assert_eq!(read_pe_arch(&path).unwrap(), Some(PeArch::X64));
+}
+
+#[test]
+fn test_read_file_version_without_version_resource() {
+    let path = fixture("noversion.dll");
+    assert_eq!(read_file_version(&path).unwrap(), None);
+
+    // Still a PE image, only the version resource is missing
+    let info = read_all_version_fields(&path).unwrap().unwrap();
+    assert_eq!(info.file_version, None);
+    assert_eq!(info.arch, 0x8664);
+}
+
+#[test]
+fn test_read_product_version() {
+    for (name, expected) in [
+        ("version32.dll", "1.2.3.4"),
+        ("version64.dll", "10.0.19041.1"),
+    ] {
+        let info = read_all_version_fields(&fixture(name)).unwrap().unwrap();
+        assert_eq!(info.file_version, version(expected));
+        assert_eq!(info.product_version, version(expected));
+    }
+}
+
+#[test]
+fn test_at_least_version_of() {
+    let options = CollectOptions {
+        include_version: true,
+        include_product_version: true,
+        ..Default::default()
+    };
+    let files = collect_file_info_with_options(
+        ["version32.dll", "version64.dll", "noversion.dll"]
+            .iter()
+            .map(|name| fixture(name))
+            .collect(),
+        &options,
+    );
+    let older_than = |reference: &str, field: VersionField| {
+        let reference = read_reference_version(&fixture(reference), field).unwrap();
+        filter_older_than_of(files.clone(), reference, field)
+            .into_iter()
+            .map(|file| {
+                file.path
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // Files without a version are never reported, nor is the reference itself
+    for field in [VersionField::File, VersionField::Product] {
+        assert_eq!(older_than("version64.dll", field), vec!["version32.dll"]);
+        assert!(older_than("version32.dll", field).is_empty());
+    }
+
+    let error = read_reference_version(&fixture("noversion.dll"), VersionField::File).unwrap_err();
+    assert!(error.to_string().contains("has no version information"));
+}